@@ -1,4 +1,5 @@
-use clap::{Args, Parser, Subcommand};
+use clap::{error::ErrorKind, ArgAction, Args, CommandFactory, Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "sync-dotconfigs")]
@@ -9,35 +10,137 @@ pub struct SyncDotfilesArgs {
     #[clap(short, long)]
     pub config_path: Option<String>,
 
+    /// Provide custom path to a directory of *.ron config fragments merged into one config (e.g. ~/.config/sync-dotfiles/conf.d)
+    #[clap(long, value_name = "DIR", conflicts_with = "config_path")]
+    pub config_dir: Option<String>,
+
+    /// Force the format of the config file (ron, toml or json) instead of detecting it from its extension
+    #[clap(long, global = true, value_name = "FORMAT")]
+    pub config_format: Option<String>,
+
+    /// Print the path of the config file that would be used and exit
+    #[clap(long)]
+    pub print_config_path: bool,
+
+    /// Print the files that would be copied or removed instead of changing them
+    #[clap(long, global = true)]
+    pub dry_run: bool,
+
+    /// Assume yes for all confirmation prompts (e.g. before cleaning)
+    #[clap(short = 'y', long, global = true)]
+    pub assume_yes: bool,
+
+    /// Temporarily make read-only destination files writable when pushing
+    #[clap(long, global = true)]
+    pub make_writable: bool,
+
+    /// Override the dotconfigs path of the config file (local path or git URL)
+    #[clap(long, global = true, value_name = "PATH")]
+    pub dotconfigs_path: Option<String>,
+
+    /// Override the home directory used to resolve config paths
+    #[clap(long, global = true, value_name = "DIR")]
+    pub home: Option<PathBuf>,
+
+    /// Only sync the configs of the given profile
+    #[clap(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Only print errors, warnings and summaries
+    #[clap(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Print the effective runtime configuration (config file, hash algorithm, dotconfigs directory, jobs) before running
+    #[clap(short, long, global = true)]
+    pub verbose: bool,
+
+    /// Hash algorithm of the config hashes (sha1, sha256 or blake3), saved with the config file
+    #[clap(long, global = true, value_name = "ALGO")]
+    pub hash_algo: Option<String>,
+
+    /// Fail when a config path to be hashed is missing instead of treating it as empty
+    #[clap(long, global = true)]
+    pub strict_hash: bool,
+
+    /// Verify that every copied config matches its source after it is written
+    #[clap(long, global = true)]
+    pub verify_after: bool,
+
+    /// Pull the changed configs without updating their hashes in the config file
+    #[clap(long, global = true)]
+    pub no_metadata_update: bool,
+
+    /// Pull even if the dotconfigs directory seems to lack the free space for the configs
+    #[clap(long, global = true)]
+    pub no_space_check: bool,
+
+    /// Stop at the first config that fails to sync instead of reporting all failures at the end
+    #[clap(long, global = true, overrides_with = "continue_on_error")]
+    pub fail_fast: bool,
+
+    /// Keep syncing the other configs when a config fails to sync (default)
+    #[clap(long, global = true, overrides_with = "fail_fast")]
+    pub continue_on_error: bool,
+
+    /// Write optional values of the RON config file as Some(...) when saving it
+    #[clap(long, global = true)]
+    pub explicit_some: bool,
+
+    /// Maximum number of threads used to hash and copy configs (default: number of CPUs)
+    #[clap(short, long, global = true, value_name = "N")]
+    pub jobs: Option<usize>,
+
+    /// Process the configs in parallel (use --parallel=false to run serially, in a deterministic order)
+    #[clap(long, global = true, value_name = "BOOL", default_value_t = true, action = ArgAction::Set)]
+    pub parallel: bool,
+
+    /// Print the changes of the config file as a diff before saving it
+    #[clap(long, global = true)]
+    pub show_config_diff: bool,
+
+    /// Save the config file gzip compressed (e.g. as config.ron.gz)
+    #[clap(long, global = true)]
+    pub compress_config: bool,
+
+    /// Expand the config paths containing wildcards (e.g. ~/.config/systemd/user/*.service) into the files they match
+    #[clap(long, global = true)]
+    pub expand_globs_in_config: bool,
+
     #[command(subcommand)]
-    pub command: Commands,
+    pub command: Option<Commands>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Force push configs from dotconfigs directory into your local system
     #[clap(short_flag = 'f')]
-    ForcePush,
+    ForcePush(FilterArgs),
 
     /// Force pull configs from your local system into the dotconfigs directory
     #[clap(short_flag = 'F')]
-    ForcePull,
+    ForcePull(FilterArgs),
 
     /// Update your dotconfigs directory with the latest configs
     #[clap(short_flag = 'u')]
-    Pull,
+    Pull(PullArgs),
 
     /// Update your local system configs with the configs from the dotconfigs directory
     #[clap(short_flag = 'U')]
-    Push,
+    Push(FilterArgs),
+
+    /// Sync configs in both directions and print a one-line summary
+    Sync,
+
+    /// Prints which configs changed locally or in the dotconfigs directory, without syncing
+    Status,
 
     /// Clear the metadata of config entries in the sync-dotfiles config
     #[clap(short_flag = 'x')]
-    ClearMetadata,
+    ClearMetadata(ClearMetadataArgs),
 
     /// Prints a new sync-dotfiles configuration
     #[clap(name = "new", short_flag = 'n')]
-    PrintNew,
+    PrintNew(PrintNewArgs),
 
     /// Prints the currently used sync-dotfiles config file
     #[clap(name = "printconf", short_flag = 'P')]
@@ -52,6 +155,17 @@ pub enum Commands {
     #[command(arg_required_else_help = true)]
     Add(AddArgs),
 
+    /// Rename a config entry, moving its copy inside the dotconfigs directory along
+    #[command(arg_required_else_help = true)]
+    Rename(RenameArgs),
+
+    /// Recreate the configs missing from the dotconfigs directory from your local system
+    Repair(FilterArgs),
+
+    /// Copy the configs stored in a dotconfigs directory into another dotconfigs directory
+    #[command(arg_required_else_help = true)]
+    Transfer(TransferArgs),
+
     /// Clean all the config directories from your specified dotconfigs path
     #[clap(short_flag = 'C')]
     Clean,
@@ -59,6 +173,35 @@ pub enum Commands {
     /// Edit the sync-dotfiles config file
     #[clap(short_flag = 'e')]
     Edit,
+
+    /// Prints the JSON Schema of the sync-dotfiles config file format
+    Schema,
+
+    /// Check the sync-dotfiles config file for problems
+    Doctor(DoctorArgs),
+
+    /// Prints how the path of every config is resolved, without syncing
+    DumpResolved,
+
+    /// Prints a sync-dotfiles configuration importing a GNU Stow directory
+    #[command(arg_required_else_help = true)]
+    ImportStow(ImportStowArgs),
+
+    /// Prints the hash of a file or directory, as calculated for a config
+    #[command(arg_required_else_help = true)]
+    Hash(HashArgs),
+
+    /// Prints the configs added, removed or changed in another sync-dotfiles config file
+    #[command(arg_required_else_help = true)]
+    DiffConfigs(DiffConfigsArgs),
+
+    /// Prints a file config from the dotconfigs directory, or lists the files of a directory config
+    #[command(arg_required_else_help = true)]
+    Cat(CatArgs),
+
+    /// Prints the absolute path of a config inside the dotconfigs directory
+    #[command(arg_required_else_help = true)]
+    Resolve(ResolveArgs),
 }
 
 #[derive(Args)]
@@ -69,8 +212,115 @@ pub struct AddArgs {
     /// The path to the config entry
     #[arg(short = 'p', long)]
     pub path: String,
+    /// Update the path of an existing config entry with the same name
+    #[arg(long, alias = "update")]
+    pub overwrite: bool,
+    /// The type of the config entry (file or dir), instead of detecting it from the path
+    #[arg(long = "type", value_name = "TYPE")]
+    pub conf_type: Option<String>,
+}
+
+#[derive(Args)]
+pub struct PrintNewArgs {
+    /// Add a config for every well-known dotfile found in the home directory (e.g. ~/.bashrc, ~/.config/nvim)
+    #[arg(long)]
+    pub autodetect: bool,
+}
+
+#[derive(Args)]
+pub struct RenameArgs {
+    /// The current name of the config entry
+    pub old: String,
+    /// The new name of the config entry
+    pub new: String,
+}
+
+#[derive(Args)]
+pub struct FilterArgs {
+    /// Only sync the config entry with the given name
+    #[arg(long, value_name = "CONFIG")]
+    pub name: Option<String>,
+    /// Skip the config entry with the given name (can be repeated)
+    #[arg(long, value_name = "CONFIG")]
+    pub exclude_config: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct PullArgs {
+    #[command(flatten)]
+    pub filter: FilterArgs,
+    /// Commit the pulled configs and push them to the origin remote of the dotconfigs directory
+    #[arg(long)]
+    pub commit: bool,
+}
+
+#[derive(Args)]
+pub struct TransferArgs {
+    /// The dotconfigs directory the configs are copied from
+    #[arg(long, value_name = "DIR")]
+    pub from: String,
+    /// The dotconfigs directory the configs are copied into
+    #[arg(long, value_name = "DIR")]
+    pub to: String,
+}
+
+#[derive(Args)]
+pub struct ClearMetadataArgs {
+    /// Only clear the metadata of the config entry with the given name
+    #[arg(long, value_name = "CONFIG")]
+    pub name: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DoctorArgs {
+    /// Warn about configs not synced for longer than the given duration (e.g. 45d, 12h)
+    #[arg(long, value_name = "DURATION")]
+    pub stale_after: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ImportStowArgs {
+    /// The path to the stow directory containing the packages
+    pub dir: String,
+}
+
+#[derive(Args)]
+pub struct HashArgs {
+    /// The path to the file or directory to hash
+    pub path: String,
+    /// The hash algorithm to use (default: sha1)
+    #[arg(long, value_name = "ALGO")]
+    pub algo: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DiffConfigsArgs {
+    /// The path to the other config file (e.g. of another machine)
+    pub other: String,
+    /// Print the differences as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct CatArgs {
+    /// The name of the config entry
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct ResolveArgs {
+    /// The name of the config entry
+    pub name: String,
 }
 
 pub fn get_env_args() -> SyncDotfilesArgs {
     SyncDotfilesArgs::parse()
 }
+
+/// Exit with a usage error because no subcommand was given.
+pub fn missing_subcommand() -> ! {
+    SyncDotfilesArgs::command()
+        .error(ErrorKind::MissingSubcommand, "a subcommand is required")
+        .exit()
+}
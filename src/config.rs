@@ -1,14 +1,19 @@
 use crate::{
-    fix_path, hasher,
+    fix_path,
+    hasher::{self, HashAlgorithm},
+    info, options,
+    stub::{self, Stub},
     utils::{self, escape_privilege, FixPath},
 };
 use anyhow::Result;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use sha1::{Digest, Sha1};
 use std::os::unix::fs::PermissionsExt;
 use std::{
+    collections::HashSet,
     fmt, fs, io,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
+    str::FromStr,
 };
 use walkdir::WalkDir;
 
@@ -31,19 +36,248 @@ use walkdir::WalkDir;
 ///     Some(ConfType::File),
 /// );
 /// ```
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
 pub struct Config {
     /// Name of the config (e.g., "vimrc")
     pub name: String,
     /// Path to the config (e.g., "${HOME}/.vimrc")
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub path: String,
-    /// Hash of the config
+    /// Paths of a config synced to multiple destinations, used instead of
+    /// `path` (e.g., ["~/work/.editorconfig", "~/oss/.editorconfig"]).
+    /// The config is pulled from the first path and pushed to all of them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paths: Option<Vec<String>>,
+    /// Hash of the config, prefixed with its algorithm (e.g. "sha1:...")
     /// (used to check if the config has changed since the last sync)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hash: Option<String>,
     /// Config type (file or directory)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub conf_type: Option<ConfType>,
+    /// Custom path of the config inside the dotconfigs directory
+    /// (e.g., "editors/nvim"), relative to the dotconfigs directory.
+    /// Defaults to the config name for directories and the file name for files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo_path: Option<String>,
+    /// Store a directory config as a single zstd compressed tar archive
+    /// (`<name>.tar.zst`) inside the dotconfigs directory (default: false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compress: Option<bool>,
+    /// Push the config by symlinking its local path to the copy inside the
+    /// dotconfigs directory instead of copying it (default: Copy)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sync_mode: Option<SyncMode>,
+    /// Paths of files or directories inside a directory config excluded
+    /// from syncing, relative to the config directory
+    /// (e.g., ["lazy-lock.json"])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_exact: Option<Vec<String>>,
+    /// Glob patterns of files or directories inside a directory config
+    /// excluded from syncing, relative to the config directory
+    /// (e.g., ["target", "**/*.lock"])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore: Option<Vec<String>>,
+    /// Glob patterns of files inside a directory config that are still
+    /// synced but left out of its hash, so changing them doesn't mark the
+    /// config as changed, relative to the config directory
+    /// (e.g., ["version", "**/*.cache"])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash_exclude: Option<Vec<String>>,
+    /// Track a config that is a symlink by the target of the link instead
+    /// of the contents it points to, so repointing the link is detected as
+    /// a change. The link itself is copied on pull and push (default: false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash_link_target: Option<bool>,
+    /// Ignore a single trailing newline of text files when hashing the
+    /// config, so editors adding or removing it don't mark the config as
+    /// changed. Files are still copied unchanged (default: the
+    /// `trim_trailing_newline` of the `DotConfig`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trim_trailing_newline: Option<bool>,
+    /// Owner of the config on push, as a user name or numeric id
+    /// (only applied when running with root privileges)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Group of the config on push, as a group name or numeric id
+    /// (only applied when running with root privileges)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Operating systems the config is synced on, as named by Rust's
+    /// `std::env::consts::OS` (e.g., ["linux", "macos"]).
+    /// The config is synced on every operating system if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os: Option<Vec<String>>,
+    /// Free-form note about the config, kept when the config file is saved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Short human description of what the config tracks, shown when
+    /// printing the config
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Time of the last sync of the config, in seconds since the Unix epoch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_sync: Option<u64>,
+    /// Change detection mode used for the config hash
+    /// (set from the `DotConfig` and not stored in the config file)
+    #[serde(skip)]
+    pub change_detection: ChangeDetection,
+    /// Hash algorithm used for the config hash
+    /// (set from the `DotConfig` and not stored in the config file)
+    #[serde(skip)]
+    pub hash_algorithm: HashAlgorithm,
+    /// Size in bytes above which the files of a directory config are pulled
+    /// as stubs (set from the `DotConfig` and not stored in the config file)
+    #[serde(skip)]
+    pub lfs_threshold: Option<u64>,
+    /// Store the config at its path relative to the home directory inside
+    /// the dotconfigs directory (set from the `DotConfig` and not stored in
+    /// the config file)
+    #[serde(skip)]
+    pub mirror_home: bool,
+    /// Ignore a trailing newline of text files when hashing the config,
+    /// unless overridden by `trim_trailing_newline` (set from the
+    /// `DotConfig` and not stored in the config file)
+    #[serde(skip)]
+    pub default_trim_trailing_newline: bool,
+    /// Paths of the other configs stored inside the stored copy of the
+    /// config, relative to it, which are kept when its stale files are
+    /// removed (set from the `DotConfig` and not stored in the config file)
+    #[serde(skip)]
+    pub nested_repo_paths: Vec<PathBuf>,
+}
+
+/// Enum representing how changes of a configuration are detected.
+///
+/// # Variants
+///
+/// - `ContentHash`: Hash the contents of every file (default).
+/// - `MetadataStamp`: Hash the relative path, length and modification time
+///   of every file. This is a lot faster for large directories, but misses
+///   edits that preserve both the length and the modification time of a
+///   file.
+///
+/// # Examples
+///
+/// ```rust
+/// use sync_dotfiles_rs::config::ChangeDetection;
+///
+/// assert_eq!(ChangeDetection::default(), ChangeDetection::ContentHash);
+/// ```
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChangeDetection {
+    /// Hash the contents of every file.
+    #[default]
+    ContentHash,
+    /// Hash the relative path, length and modification time of every file.
+    MetadataStamp,
+}
+
+impl ChangeDetection {
+    /// Check if this is the default `ContentHash` mode.
+    pub fn is_content_hash(&self) -> bool {
+        matches!(self, ChangeDetection::ContentHash)
+    }
+}
+
+/// Enum representing how a configuration is pushed to the local system.
+///
+/// # Variants
+///
+/// - `Copy`: Copy the configuration from the dotconfigs directory (default).
+/// - `Symlink`: Symlink the local path to the configuration inside the
+///   dotconfigs directory (GNU Stow style), so edits are made to the
+///   dotconfigs directory directly.
+///
+/// # Examples
+///
+/// ```rust
+/// use sync_dotfiles_rs::config::SyncMode;
+///
+/// assert_eq!(SyncMode::default(), SyncMode::Copy);
+/// ```
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SyncMode {
+    /// Copy the configuration.
+    #[default]
+    Copy,
+    /// Symlink the configuration to the dotconfigs directory.
+    Symlink,
+}
+
+/// Summary of the files copied into the dotconfigs directory by
+/// `Config::pull_config`, or to the local system by `Config::push_config`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sync_dotfiles_rs::config::CopyReport;
+///
+/// let mut report = CopyReport::default();
+/// report.add_file(512);
+/// report.merge(CopyReport {
+///     files_copied: 1,
+///     bytes: 512,
+///     files_unchanged: 0,
+///     skipped: vec![],
+/// });
+///
+/// assert_eq!(report.files_copied, 2);
+/// assert_eq!(report.to_string(), "2 files copied (1.0 KB)");
+///
+/// report.files_unchanged = 3;
+/// assert_eq!(report.to_string(), "2 files copied (1.0 KB), 3 unchanged");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CopyReport {
+    /// Number of copied files.
+    pub files_copied: usize,
+    /// Total size of the copied files in bytes.
+    pub bytes: u64,
+    /// Number of files not copied because the destination was already
+    /// identical.
+    pub files_unchanged: usize,
+    /// Files that were not copied, e.g. files stored as stubs or files
+    /// that failed to copy.
+    pub skipped: Vec<PathBuf>,
+}
+
+impl CopyReport {
+    /// Record a copied file of the given size.
+    pub fn add_file(&mut self, bytes: u64) {
+        self.files_copied += 1;
+        self.bytes += bytes;
+    }
+
+    /// Add the files of another report to this report.
+    pub fn merge(&mut self, other: CopyReport) {
+        self.files_copied += other.files_copied;
+        self.bytes += other.bytes;
+        self.files_unchanged += other.files_unchanged;
+        self.skipped.extend(other.skipped);
+    }
+}
+
+/// Display implementation for CopyReport.
+///
+/// The report is displayed as the number and total size of the copied
+/// files, followed by the number of unchanged files if there are any, e.g.
+/// `12 files copied (84.0 KB), 240 unchanged`.
+impl fmt::Display for CopyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} files copied ({})",
+            self.files_copied,
+            utils::format_bytes(self.bytes)
+        )?;
+
+        if self.files_unchanged > 0 {
+            write!(f, ", {} unchanged", self.files_unchanged)?;
+        }
+
+        Ok(())
+    }
 }
 
 /// Enum representing the type of a configuration, which can be either a
@@ -73,7 +307,7 @@ pub struct Config {
 /// assert_eq!(file_type, ConfType::File);
 /// assert_eq!(dir_type, ConfType::Dir);
 /// ```
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub enum ConfType {
     /// Configuration is a file.
     File,
@@ -102,7 +336,7 @@ pub enum ConfType {
 /// ## Implementation Notes
 ///
 /// - Two `ConfType` variants are considered equal if they are of the
-/// same variant (`File` or `Dir`).
+///   same variant (`File` or `Dir`).
 impl PartialEq for ConfType {
     fn eq(&self, other: &Self) -> bool {
         match self {
@@ -133,7 +367,7 @@ impl PartialEq for ConfType {
 /// ## Implementation Notes
 ///
 /// - Two `ConfType` variants are considered equal if they are of the same
-/// variant (`File` or `Dir`).
+///   variant (`File` or `Dir`).
 impl Eq for ConfType {}
 
 impl ConfType {
@@ -148,6 +382,31 @@ impl ConfType {
     }
 }
 
+/// Parse a `ConfType` from `file` or `dir`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sync_dotfiles_rs::config::ConfType;
+///
+/// assert_eq!("file".parse::<ConfType>().unwrap(), ConfType::File);
+/// assert_eq!("dir".parse::<ConfType>().unwrap(), ConfType::Dir);
+/// assert!("link".parse::<ConfType>().is_err());
+/// ```
+impl FromStr for ConfType {
+    type Err = anyhow::Error;
+
+    fn from_str(conf_type: &str) -> Result<Self> {
+        match conf_type {
+            "file" => Ok(ConfType::File),
+            "dir" => Ok(ConfType::Dir),
+            _ => Err(anyhow::anyhow!(
+                "Unknown config type {conf_type:?}, expected file or dir"
+            )),
+        }
+    }
+}
+
 /// Default implementation for `Config`.
 ///
 /// The `Config` struct implements the `Default` trait, allowing you to
@@ -162,21 +421,94 @@ impl ConfType {
 ///
 /// assert_eq!(config.name, String::from("placeholder"));
 /// assert_eq!(config.path, String::from("~/placeholder"));
+/// assert_eq!(config.paths, None);
 /// assert_eq!(config.hash, None);
 /// assert_eq!(config.conf_type, None);
+/// assert_eq!(config.repo_path, None);
+/// assert_eq!(config.compress, None);
+/// assert_eq!(config.sync_mode, None);
+/// assert_eq!(config.exclude_exact, None);
+/// assert_eq!(config.ignore, None);
+/// assert_eq!(config.hash_exclude, None);
+/// assert_eq!(config.hash_link_target, None);
+/// assert_eq!(config.trim_trailing_newline, None);
+/// assert_eq!(config.owner, None);
+/// assert_eq!(config.group, None);
+/// assert_eq!(config.os, None);
+/// assert_eq!(config.note, None);
+/// assert_eq!(config.description, None);
 /// ```
 impl Default for Config {
     fn default() -> Self {
         Config {
             name: String::from("placeholder"),
             path: String::from("~/placeholder"),
+            paths: None,
             hash: None,
             conf_type: None,
+            repo_path: None,
+            compress: None,
+            sync_mode: None,
+            exclude_exact: None,
+            ignore: None,
+            hash_exclude: None,
+            hash_link_target: None,
+            trim_trailing_newline: None,
+            owner: None,
+            group: None,
+            os: None,
+            note: None,
+            description: None,
+            last_sync: None,
+            change_detection: ChangeDetection::default(),
+            hash_algorithm: HashAlgorithm::default(),
+            lfs_threshold: None,
+            mirror_home: false,
+            default_trim_trailing_newline: false,
+            nested_repo_paths: Vec::new(),
         }
     }
 }
 
+/// TryFrom implementation for Config, creating a config named after the
+/// path, see `Config::try_from_path`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sync_dotfiles_rs::config::Config;
+/// use std::path::Path;
+///
+/// let config = Config::try_from(Path::new("~/.config/nvim")).unwrap();
+/// assert_eq!(config.name, "nvim");
+/// ```
+impl TryFrom<&Path> for Config {
+    type Error = anyhow::Error;
+
+    fn try_from(path: &Path) -> Result<Self> {
+        Config::try_from_path(path, None)
+    }
+}
+
 impl Config {
+    /// Check if the config is the placeholder of the default config
+    /// template (see `Config::default`), which doesn't point to a real
+    /// config.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    ///
+    /// assert!(Config::default().is_placeholder());
+    /// assert!(!Config::new_file("vimrc", "~/.vimrc").is_placeholder());
+    /// ```
+    pub fn is_placeholder(&self) -> bool {
+        let placeholder = Config::default();
+
+        self.name == placeholder.name && self.path == placeholder.path && self.hash.is_none()
+    }
+
     /// Create a new `Config` instance with the specified attributes.
     ///
     /// This method allows you to create a new `Config` instance with a
@@ -187,9 +519,9 @@ impl Config {
     /// * `name` - A string representing the name of the configuration.
     /// * `path` - A string representing the path to the configuration.
     /// * `hash` - An optional string representing the hash of the
-    /// configuration (used for change detection).
+    ///   configuration (used for change detection).
     /// * `conf_type` - An optional `ConfType` enum indicating the type of the
-    /// configuration (file or directory).
+    ///   configuration (file or directory).
     ///
     /// # Returns
     ///
@@ -237,9 +569,118 @@ impl Config {
             path,
             hash,
             conf_type,
+            ..Default::default()
         }
     }
 
+    /// Create a new file `Config` instance with the specified name and path.
+    ///
+    /// The `conf_type` is set to `ConfType::File` and the hash is left unset.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::{Config, ConfType};
+    ///
+    /// let config = Config::new_file("vimrc", "~/.vimrc");
+    ///
+    /// assert_eq!(config.name, "vimrc");
+    /// assert_eq!(config.path, "~/.vimrc");
+    /// assert_eq!(config.hash, None);
+    /// assert_eq!(config.conf_type, Some(ConfType::File));
+    /// ```
+    pub fn new_file(name: impl Into<String>, path: impl Into<String>) -> Self {
+        Self::new(name.into(), path.into(), None, Some(ConfType::File))
+    }
+
+    /// Create a new directory `Config` instance with the specified name and
+    /// path.
+    ///
+    /// The `conf_type` is set to `ConfType::Dir` and the hash is left unset.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::{Config, ConfType};
+    ///
+    /// let config = Config::new_dir("nvim", "~/.config/nvim");
+    ///
+    /// assert_eq!(config.name, "nvim");
+    /// assert_eq!(config.path, "~/.config/nvim");
+    /// assert_eq!(config.hash, None);
+    /// assert_eq!(config.conf_type, Some(ConfType::Dir));
+    /// ```
+    pub fn new_dir(name: impl Into<String>, path: impl Into<String>) -> Self {
+        Self::new(name.into(), path.into(), None, Some(ConfType::Dir))
+    }
+
+    /// Create a new `Config` instance for an existing path, e.g. when
+    /// registering a list of paths.
+    ///
+    /// The `conf_type` is detected from the path, and left unset if the
+    /// path doesn't exist. Without a name, the config is named after the
+    /// file stem of the path without its leading dots (e.g. `tmux` for
+    /// `~/.tmux.conf`). The hash is left unset.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the config.
+    /// * `name` - The name of the config, instead of deriving it from the
+    ///   path.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the new `Config`, or an error if no name can be
+    /// derived from the path or the name is invalid (see `validate_name`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::{Config, ConfType};
+    /// use std::path::Path;
+    ///
+    /// let path = format!("{}/examples/config.ron", env!("CARGO_MANIFEST_DIR"));
+    /// let config = Config::try_from_path(Path::new(&path), None).unwrap();
+    ///
+    /// assert_eq!(config.name, "config");
+    /// assert_eq!(config.path, path);
+    /// assert_eq!(config.hash, None);
+    /// assert_eq!(config.conf_type, Some(ConfType::File));
+    ///
+    /// let config = Config::try_from_path(Path::new("/does/not/exist/.tmux.conf"), None).unwrap();
+    /// assert_eq!(config.name, "tmux");
+    /// assert_eq!(config.conf_type, None);
+    ///
+    /// let config = Config::try_from_path(Path::new("examples"), Some(String::from("ex"))).unwrap();
+    /// assert_eq!(config.name, "ex");
+    /// assert_eq!(config.conf_type, Some(ConfType::Dir));
+    ///
+    /// assert!(Config::try_from_path(Path::new("/"), None).is_err());
+    /// ```
+    pub fn try_from_path(path: &Path, name: Option<String>) -> Result<Config> {
+        let name = match name {
+            Some(name) => name,
+            None => path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().trim_start_matches('.').to_string())
+                .filter(|name| !name.is_empty())
+                .ok_or_else(|| anyhow::anyhow!("Cannot derive a config name from {:#?}", path))?,
+        };
+
+        let conf_type = if path.is_dir() {
+            Some(ConfType::Dir)
+        } else if path.is_file() {
+            Some(ConfType::File)
+        } else {
+            None
+        };
+
+        let config = Config::new(name, path.to_string_lossy().to_string(), None, conf_type);
+        config.validate_name()?;
+
+        Ok(config)
+    }
+
     /// Check if the config path exists.
     ///
     /// This method checks whether the file or directory specified by the
@@ -272,158 +713,1391 @@ impl Config {
     /// assert!(existant_config.path_exists());
     /// ```
     pub fn path_exists(&self) -> bool {
-        fix_path!(&self.path).exists()
+        if self.is_glob() {
+            return self.glob_matches().is_ok_and(|matches| !matches.is_empty());
+        }
+
+        let path = fix_path!(self.primary_path());
+
+        // A tracked symlink exists even if its target doesn't
+        path.exists() || self.tracked_link_target(&path).is_some()
     }
 
-    /// Calculate the hash of the metadata for a file or directory.
+    /// Get the path the config is pulled from, which is the first of the
+    /// `paths` if they are set, or the `path` otherwise.
     ///
-    /// This method computes the hash of the metadata
-    /// (e.g., file content or directory structure) for the configuration file
-    /// or directory specified by the `path` field of the `Config` instance.
+    /// # Example
     ///
-    /// # Returns
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
     ///
-    /// A `Result` containing the hash as a string if successful,
-    /// or an error if the operation fails.
+    /// let mut config = Config::new_file("editorconfig", "~/.editorconfig");
+    /// assert_eq!(config.primary_path(), "~/.editorconfig");
+    ///
+    /// config.paths = Some(vec![
+    ///     String::from("~/work/.editorconfig"),
+    ///     String::from("~/oss/.editorconfig"),
+    /// ]);
+    /// assert_eq!(config.primary_path(), "~/work/.editorconfig");
+    /// ```
+    pub fn primary_path(&self) -> &str {
+        match &self.paths {
+            Some(paths) if !paths.is_empty() => &paths[0],
+            _ => &self.path,
+        }
+    }
+
+    /// Get all the paths the config is pushed to.
     ///
     /// # Example
     ///
     /// ```rust
     /// use sync_dotfiles_rs::config::Config;
     ///
-    /// let config = Config::new(
-    ///     String::from("config.ron"),
-    ///     format!("{}/examples/config.ron", env!("CARGO_MANIFEST_DIR")),
-    ///     None,
-    ///     None,
-    /// );
-    ///
-    /// match config.metadata_digest() {
-    ///     Ok(hash) => println!("Metadata digest: {}", hash),
-    ///     Err(err) => eprintln!("Error calculating metadata digest: {:?}", err),
-    /// }
+    /// let config = Config::new_file("editorconfig", "~/.editorconfig");
+    /// assert_eq!(config.destinations(), vec!["~/.editorconfig"]);
     /// ```
-    pub fn metadata_digest(&self) -> Result<String> {
-        let path = fix_path!(&self.path);
-
-        // check if the path exists and return empty string if it doesn't
-        if !self.path_exists() {
-            return Ok(String::new());
+    pub fn destinations(&self) -> Vec<&str> {
+        match &self.paths {
+            Some(paths) if !paths.is_empty() => paths.iter().map(String::as_str).collect(),
+            _ => vec![self.path.as_str()],
         }
+    }
 
-        if path.is_file() {
-            return Ok(hasher::get_file_hash(&path, &mut Sha1::new())?);
-        }
-        if path.is_dir() {
-            return Ok(hasher::get_complete_dir_hash(&path, &mut Sha1::new())?);
+    /// Validate that the config has either a `path` or a list of `paths`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    ///
+    /// let mut config = Config::new_file("editorconfig", "~/.editorconfig");
+    /// assert!(config.validate_paths().is_ok());
+    ///
+    /// config.paths = Some(vec![String::from("~/work/.editorconfig")]);
+    /// assert!(config.validate_paths().is_err());
+    ///
+    /// config.path = String::new();
+    /// assert!(config.validate_paths().is_ok());
+    /// ```
+    pub fn validate_paths(&self) -> Result<()> {
+        match (&self.paths, self.path.is_empty()) {
+            (None, true) => Err(anyhow::anyhow!(
+                "Invalid config {:#?}: either path or paths must be set",
+                self.name
+            )),
+            (Some(paths), _) if paths.is_empty() => Err(anyhow::anyhow!(
+                "Invalid config {:#?}: paths must not be empty",
+                self.name
+            )),
+            (Some(_), false) => Err(anyhow::anyhow!(
+                "Invalid config {:#?}: only one of path and paths may be set",
+                self.name
+            )),
+            _ => Ok(()),
         }
-
-        Err(anyhow::anyhow!("Invalid config type: {:#?}", self.path))
     }
 
-    /// Check if the configuration needs metadata update.
+    /// Validate that the name of the config is safe to use as a file name.
     ///
-    /// This method checks whether the configuration needs an update of its
-    /// metadata, such as hash and configuration type. It is required because
-    /// the hash and configuration type are not stored in the dotconfig file
-    /// and are used to determine if the configuration has changed since the
-    /// last sync.
+    /// The name is used as the name of the config inside the dotconfigs
+    /// directory, so it may not contain path separators or characters
+    /// reserved on Windows, be `.` or `..`, start with a dot (which hides it)
+    /// or be a name reserved on Windows (e.g. `CON` or `LPT1`).
     ///
     /// # Returns
     ///
-    /// `true` if metadata update is required, `false` otherwise.
+    /// A `Result` indicating whether the name is valid, with an error
+    /// listing the offending characters otherwise.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use sync_dotfiles_rs::config::{Config, ConfType};
-    ///
-    /// let mut config = Config::new(
-    ///     String::from("config.ron"),
-    ///     format!("{}/examples/config.ron", env!("CARGO_MANIFEST_DIR")),
-    ///     None,
-    ///     Some(ConfType::File),
-    /// );
+    /// use sync_dotfiles_rs::config::Config;
     ///
-    /// assert!(config.check_update_metadata_required());
+    /// assert!(Config::new_dir("nvim", "~/.config/nvim").validate_name().is_ok());
+    /// assert!(Config::new_dir("../escape", "~/.config/nvim").validate_name().is_err());
+    /// assert!(Config::new_file("CON", "~/.vimrc").validate_name().is_err());
+    /// assert!(Config::new_file(".vimrc", "~/.vimrc").validate_name().is_err());
     /// ```
-    pub fn check_update_metadata_required(&self) -> bool {
-        match self.hash.as_ref() {
-            Some(hash) => {
-                let digest = self
-                    .metadata_digest()
-                    .expect("Failed to get metadata digest");
+    pub fn validate_name(&self) -> Result<()> {
+        let name = &self.name;
+        if name.is_empty() {
+            return Err(anyhow::anyhow!("Invalid config name: the name is empty"));
+        }
 
-                // If hash hash doesn't match, then we require metadata update
-                if hash.ne(&digest) {
-                    true
-                } else {
-                    // If config tye is not preset, then we require metadata update
-                    self.conf_type.is_none()
+        let mut reserved = Vec::new();
+        name.chars()
+            .filter(|c| RESERVED_NAME_CHARS.contains(c) || c.is_control())
+            .for_each(|c| {
+                if !reserved.contains(&c) {
+                    reserved.push(c);
                 }
-            }
-            // If hash is not set, then we require metadata update
-            None => true,
+            });
+        if !reserved.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Invalid config name {:#?}: it contains the reserved characters {}",
+                name,
+                reserved
+                    .iter()
+                    .map(|c| format!("{c:?}"))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
         }
-    }
 
-    /// Update the hash of the configuration's metadata.
-    ///
-    /// This method calculates the new hash of the configuration's metadata
-    /// and updates the `hash` field of the `Config` instance.
-    ///
-    /// # Returns
-    ///
-    /// A `Result` indicating success or failure of the operation.
-    pub fn update_config_hash(&mut self) -> Result<()> {
-        // calculate the new hash of the config
-        let new_hash = self
-            .metadata_digest()
-            .expect("Failed to get metadata digest");
+        if name.starts_with('.') {
+            return Err(anyhow::anyhow!(
+                "Invalid config name {:#?}: it must not start with a dot",
+                name
+            ));
+        }
+
+        // Reserved names are reserved with any extension too (e.g. `nul.txt`)
+        let stem = name.split('.').next().unwrap_or_default().to_uppercase();
+        if RESERVED_NAMES.contains(&stem.as_str())
+            || ["COM", "LPT"]
+                .iter()
+                .any(|prefix| stem.strip_prefix(prefix).is_some_and(is_port_number))
+        {
+            return Err(anyhow::anyhow!(
+                "Invalid config name {:#?}: {:#?} is a reserved name on Windows",
+                name,
+                stem
+            ));
+        }
 
-        self.hash = Some(new_hash);
         Ok(())
     }
 
-    /// Update the configuration type of the `Config`.
-    ///
-    /// This method checks whether the configuration specified by the
-    /// `Config` instance is a file or a directory.
-    /// If the `conf_type` field is already set, it skips the check.
-    /// Otherwise, it determines the type based on the path.
+    /// Validate the custom `repo_path` of the config.
     ///
-    /// If the path does not exist, it prints an error message and
-    /// returns without modifying the `Config`.
+    /// The `repo_path` must be a non-empty relative path that stays inside the
+    /// dotconfigs directory, i.e. it may not be absolute, start with `~` or
+    /// contain any `..` components. It must also name a directory or file
+    /// inside the dotconfigs directory, not the dotconfigs directory itself.
     ///
-    /// # Errors
+    /// # Returns
     ///
-    /// This method may return an error if it encounters issues accessing the
-    /// file system or determining the config type.
+    /// A `Result` indicating whether the `repo_path` is valid. A config
+    /// without a `repo_path` is always valid.
     ///
-    /// # Examples
+    /// # Example
     ///
     /// ```rust
-    /// use sync_dotfiles_rs::config::{Config, ConfType};
+    /// use sync_dotfiles_rs::config::Config;
     ///
     /// let mut config = Config::new(
-    ///     String::from("config.ron"),
-    ///     format!("{}/examples/config.ron", env!("CARGO_MANIFEST_DIR")),
+    ///     String::from("nvim"),
+    ///     String::from("~/.config/nvim"),
     ///     None,
     ///     None,
     /// );
     ///
-    /// // Update the configuration type.
-    /// config.update_config_type().expect("Failed to update config type");
+    /// config.repo_path = Some(String::from("editors/nvim"));
+    /// assert!(config.validate_repo_path().is_ok());
     ///
-    /// assert_eq!(config.conf_type, Some(ConfType::File));
-    pub fn update_config_type(&mut self) -> Result<()> {
-        let path = fix_path!(&self.path);
+    /// config.repo_path = Some(String::from("../outside"));
+    /// assert!(config.validate_repo_path().is_err());
+    ///
+    /// config.repo_path = Some(String::from("."));
+    /// assert!(config.validate_repo_path().is_err());
+    /// ```
+    pub fn validate_repo_path(&self) -> Result<()> {
+        let Some(repo_path) = &self.repo_path else {
+            return Ok(());
+        };
+
+        let path = Path::new(repo_path);
+        if repo_path.is_empty() || repo_path.starts_with('~') || path.has_root() {
+            return Err(anyhow::anyhow!(
+                "Invalid repo_path for {:#?}: {:#?} must be a relative path",
+                self.name,
+                repo_path
+            ));
+        }
+
+        if path
+            .components()
+            .any(|component| matches!(component, Component::ParentDir))
+        {
+            return Err(anyhow::anyhow!(
+                "Invalid repo_path for {:#?}: {:#?} escapes the dotconfigs directory",
+                self.name,
+                repo_path
+            ));
+        }
+
+        if !path
+            .components()
+            .any(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(anyhow::anyhow!(
+                "Invalid repo_path for {:#?}: {:#?} is the dotconfigs directory itself",
+                self.name,
+                repo_path
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Validate the `ignore` patterns of the config.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success, or an error naming the first pattern
+    /// that isn't a valid glob pattern.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    ///
+    /// let mut config = Config::new_dir("app", "~/.config/app");
+    /// config.ignore = Some(vec![String::from("target"), String::from("*.lock")]);
+    /// assert!(config.validate_ignore().is_ok());
+    ///
+    /// config.ignore = Some(vec![String::from("[cache")]);
+    /// assert!(config.validate_ignore().is_err());
+    /// ```
+    pub fn validate_ignore(&self) -> Result<()> {
+        self.ignore.iter().flatten().try_for_each(|pattern| {
+            glob::Pattern::new(&normalize_relative_path(pattern))
+                .map(|_| ())
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Invalid ignore pattern for {:#?}: {:#?}: {e}",
+                        self.name,
+                        pattern
+                    )
+                })
+        })
+    }
+
+    /// Validate the `hash_exclude` patterns of the config.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success, or an error naming the first pattern
+    /// that isn't a valid glob pattern.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    ///
+    /// let mut config = Config::new_dir("app", "~/.config/app");
+    /// config.hash_exclude = Some(vec![String::from("version"), String::from("**/*.cache")]);
+    /// assert!(config.validate_hash_exclude().is_ok());
+    ///
+    /// config.hash_exclude = Some(vec![String::from("[cache")]);
+    /// assert!(config.validate_hash_exclude().is_err());
+    /// ```
+    pub fn validate_hash_exclude(&self) -> Result<()> {
+        self.hash_exclude.iter().flatten().try_for_each(|pattern| {
+            glob::Pattern::new(&normalize_relative_path(pattern))
+                .map(|_| ())
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Invalid hash_exclude pattern for {:#?}: {:#?}: {e}",
+                        self.name,
+                        pattern
+                    )
+                })
+        })
+    }
+
+    /// Find the files of a directory config whose names differ only in case.
+    ///
+    /// The entries are walked the same way they are copied on pull, so git
+    /// entries are skipped. Files and configs that don't exist have no
+    /// collisions.
+    ///
+    /// # Returns
+    ///
+    /// A vector of the colliding pairs of paths, relative to the config path.
+    pub fn case_collisions(&self) -> Vec<(PathBuf, PathBuf)> {
+        let path = fix_path!(self.primary_path());
+        if !path.is_dir() {
+            return Vec::new();
+        }
+
+        let entries = WalkDir::new(&path)
+            .follow_links(false)
+            .min_depth(1)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.path().strip_prefix(&path).ok().map(Path::to_path_buf))
+            .filter(|relative_path| !utils::is_git_path(relative_path))
+            .collect::<Vec<PathBuf>>();
+
+        utils::case_collisions(&entries)
+    }
+
+    /// Check if the config is stored as a compressed archive.
+    ///
+    /// Only directory configs with `compress` enabled are compressed, glob
+    /// configs (see `is_glob`) never are.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    ///
+    /// let mut config = Config::new_dir("nvim", "~/.config/nvim");
+    /// assert!(!config.is_compressed());
+    ///
+    /// config.compress = Some(true);
+    /// assert!(config.is_compressed());
+    /// ```
+    pub fn is_compressed(&self) -> bool {
+        self.compress.unwrap_or(false)
+            && self.conf_type.as_ref().is_some_and(ConfType::is_dir)
+            && !self.is_glob()
+    }
+
+    /// Check if the config is pushed as a symlink to its copy inside the
+    /// dotconfigs directory.
+    ///
+    /// Glob configs (see `is_glob`) and compressed configs are always
+    /// copied.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::{Config, SyncMode};
+    ///
+    /// let mut config = Config::new_dir("nvim", "~/.config/nvim");
+    /// assert!(!config.is_symlinked());
+    ///
+    /// config.sync_mode = Some(SyncMode::Symlink);
+    /// assert!(config.is_symlinked());
+    ///
+    /// config.compress = Some(true);
+    /// assert!(!config.is_symlinked());
+    /// ```
+    pub fn is_symlinked(&self) -> bool {
+        self.sync_mode == Some(SyncMode::Symlink) && !self.is_glob() && !self.is_compressed()
+    }
+
+    /// Check if a config pushed as a symlink (see `is_symlinked`) still has
+    /// to be linked, because one of its destinations isn't a symlink to its
+    /// copy inside the dotconfigs directory yet.
+    pub fn needs_link(&self, repo_config_path: &Path) -> bool {
+        if !self.is_symlinked() {
+            return false;
+        }
+
+        let source = fs::canonicalize(repo_config_path).ok();
+        self.destinations()
+            .iter()
+            .any(|destination| fs::read_link(fix_path!(destination)).ok() != source)
+    }
+
+    /// Check if the config is synced on the given operating system.
+    ///
+    /// Configs without an `os` list are synced on every operating system.
+    ///
+    /// # Arguments
+    ///
+    /// * `os` - The name of the operating system, as in
+    ///   `std::env::consts::OS`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    ///
+    /// let mut config = Config::new_file("skhd", "~/.skhdrc");
+    /// assert!(config.applies_to_os("linux"));
+    ///
+    /// config.os = Some(vec![String::from("macos")]);
+    /// assert!(config.applies_to_os("macos"));
+    /// assert!(!config.applies_to_os("linux"));
+    /// ```
+    pub fn applies_to_os(&self, os: &str) -> bool {
+        self.os
+            .as_ref()
+            .is_none_or(|systems| systems.iter().any(|system| system == os))
+    }
+
+    /// Check if the config is synced on the current operating system, see
+    /// `applies_to_os`.
+    pub fn applies_to_current_os(&self) -> bool {
+        self.applies_to_os(std::env::consts::OS)
+    }
+
+    /// Get the target of a symlink of the config, if the config is tracked
+    /// by the target of its symlink (see `hash_link_target`).
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The local path of the config, or its path inside the
+    ///   dotconfigs directory.
+    ///
+    /// # Returns
+    ///
+    /// The target of the symlink, or `None` if the path isn't a symlink or
+    /// the config is tracked by its contents.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use std::{fs, path::Path};
+    ///
+    /// let path = std::env::temp_dir().join("sync-dotfiles-doctest-link-target");
+    /// let _ = fs::remove_file(&path);
+    /// std::os::unix::fs::symlink("config.ron", &path).unwrap();
+    ///
+    /// let mut config = Config::new_file("link", path.display().to_string());
+    /// assert_eq!(config.tracked_link_target(&path), None);
+    ///
+    /// config.hash_link_target = Some(true);
+    /// assert_eq!(config.tracked_link_target(&path).unwrap(), Path::new("config.ron"));
+    ///
+    /// fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn tracked_link_target(&self, path: &Path) -> Option<PathBuf> {
+        if !self.hash_link_target.unwrap_or(false) {
+            return None;
+        }
+
+        fs::read_link(path).ok()
+    }
+
+    /// Check if a trailing newline of the text files of the config is
+    /// ignored when hashing it, see `trim_trailing_newline`.
+    ///
+    /// Compressed configs are hashed over the files inside their archive
+    /// and are never trimmed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    ///
+    /// let mut config = Config::new_file("vimrc", "~/.vimrc");
+    /// assert!(!config.trims_trailing_newline());
+    ///
+    /// config.default_trim_trailing_newline = true;
+    /// assert!(config.trims_trailing_newline());
+    ///
+    /// config.trim_trailing_newline = Some(false);
+    /// assert!(!config.trims_trailing_newline());
+    /// ```
+    pub fn trims_trailing_newline(&self) -> bool {
+        !self.is_compressed()
+            && self
+                .trim_trailing_newline
+                .unwrap_or(self.default_trim_trailing_newline)
+    }
+
+    /// Check if the path of the config is a glob pattern expanded at sync
+    /// time, i.e. the `expand_globs_in_config` option is set and the path
+    /// contains a wildcard (`*`, `?` or `[`).
+    ///
+    /// A glob config is stored as a directory inside the dotconfigs
+    /// directory, containing the matched files at their path relative to
+    /// the directory of the pattern (see `glob_base`).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use sync_dotfiles_rs::options::{self, SyncOptions};
+    ///
+    /// let config = Config::new_file("units", "~/.config/systemd/user/*.service");
+    /// assert!(!config.is_glob());
+    ///
+    /// options::set(SyncOptions {
+    ///     expand_globs_in_config: true,
+    ///     ..Default::default()
+    /// });
+    /// assert!(config.is_glob());
+    /// assert!(!Config::new_file("vimrc", "~/.vimrc").is_glob());
+    /// ```
+    pub fn is_glob(&self) -> bool {
+        options::get().expand_globs_in_config && is_glob_pattern(self.primary_path())
+    }
+
+    /// Get the directory of a glob pattern, i.e. its path up to the first
+    /// component containing a wildcard.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use std::path::PathBuf;
+    ///
+    /// assert_eq!(
+    ///     Config::glob_base("/etc/systemd/user/*/override.conf"),
+    ///     PathBuf::from("/etc/systemd/user")
+    /// );
+    /// ```
+    pub fn glob_base(pattern: &str) -> PathBuf {
+        fix_path!(pattern)
+            .components()
+            .take_while(|component| !is_glob_pattern(&component.as_os_str().to_string_lossy()))
+            .collect()
+    }
+
+    /// Expand the glob pattern of the config into the files it matches,
+    /// leaving out the excluded files (see `is_excluded`).
+    ///
+    /// Directories matching the pattern are not included.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the matched files sorted by their path, or an
+    /// error if the pattern is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use sync_dotfiles_rs::options::{self, SyncOptions};
+    /// use std::fs;
+    ///
+    /// let path = std::env::temp_dir().join("sync-dotfiles-doctest-glob-matches");
+    /// let _ = fs::remove_dir_all(&path);
+    /// fs::create_dir_all(path.join("timers.target.wants")).unwrap();
+    /// fs::write(path.join("sync.service"), "").unwrap();
+    /// fs::write(path.join("backup.service"), "").unwrap();
+    /// fs::write(path.join("backup.timer"), "").unwrap();
+    ///
+    /// options::set(SyncOptions {
+    ///     expand_globs_in_config: true,
+    ///     ..Default::default()
+    /// });
+    ///
+    /// let config = Config::new_file("units", format!("{}/*.service", path.display()));
+    /// assert_eq!(
+    ///     config.glob_matches().unwrap(),
+    ///     vec![path.join("backup.service"), path.join("sync.service")]
+    /// );
+    ///
+    /// fs::remove_dir_all(&path).unwrap();
+    /// ```
+    pub fn glob_matches(&self) -> Result<Vec<PathBuf>> {
+        let pattern = fix_path!(self.primary_path());
+        let base = Config::glob_base(self.primary_path());
+        let exclusions = self.exclusions();
+
+        let mut matches = glob::glob(&pattern.to_string_lossy())
+            .map_err(|e| anyhow::anyhow!("Invalid glob pattern {:#?}: {e}", self.primary_path()))?
+            .filter_map(|entry| entry.ok())
+            .filter(|path| path.is_file())
+            .filter(|path| !is_excluded_by(&exclusions, path.strip_prefix(&base).unwrap_or(path)))
+            .collect::<Vec<PathBuf>>();
+        matches.sort();
+
+        Ok(matches)
+    }
+
+    /// Calculate the hash of the files matched by the glob pattern of the
+    /// config, see `glob_matches`.
+    fn glob_digest(&self, change_detection: ChangeDetection) -> Result<String> {
+        let base = Config::glob_base(self.primary_path());
+        let hash_exclusions = self.hash_exclusions()?;
+        let matches = self
+            .glob_matches()?
+            .into_iter()
+            .filter(|path| {
+                !matches_patterns(&hash_exclusions, path.strip_prefix(&base).unwrap_or(path))
+            })
+            .collect::<Vec<PathBuf>>();
+        if change_detection == ChangeDetection::MetadataStamp {
+            return Ok(hasher::get_files_metadata_stamp_hash(
+                &base,
+                &matches,
+                &mut self.hash_algorithm.hasher(),
+            )?);
+        }
+
+        self.files_content_hash(&matches)
+    }
+
+    /// Copy the files matched by the glob pattern of the config into the
+    /// dotconfigs directory, removing the files of earlier matches.
+    fn pull_glob_matches(&self, repo_config_path: &Path) -> Result<CopyReport> {
+        let base = Config::glob_base(self.primary_path());
+        let matches = self.glob_matches()?;
+        let relative_matches = matches
+            .iter()
+            .map(|path| path.strip_prefix(&base).unwrap_or(path).to_path_buf())
+            .collect::<HashSet<PathBuf>>();
+
+        if repo_config_path.is_file() {
+            fs::remove_file(repo_config_path)?;
+        }
+        if repo_config_path.is_dir() {
+            hasher::list_dir_files(repo_config_path)?
+                .into_iter()
+                .filter(|path| {
+                    !relative_matches.contains(path.strip_prefix(repo_config_path).unwrap_or(path))
+                })
+                .try_for_each(fs::remove_file)?;
+        }
+
+        let mut report = CopyReport::default();
+        for path in &matches {
+            let repo_path = repo_config_path.join(path.strip_prefix(&base).unwrap_or(path));
+            if let Some(parent) = repo_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            report.add_file(fs::copy(path, &repo_path)?);
+        }
+
+        if options::get().verify_after
+            && self.repo_digest(repo_config_path)?
+                != Some(self.glob_digest(ChangeDetection::ContentHash)?)
+        {
+            return Err(anyhow::anyhow!(
+                "Failed to verify {:#?}: {:#?} does not match {:#?}",
+                self.name,
+                repo_config_path,
+                self.primary_path()
+            ));
+        }
+
+        Ok(report)
+    }
+
+    /// Copy the files of a glob config from the dotconfigs directory into
+    /// the directory of every destination pattern.
+    fn push_glob_matches(&self, from_dotconfigs_path: &Path) -> Result<CopyReport> {
+        let files = hasher::list_dir_files(from_dotconfigs_path)?;
+
+        let mut report = CopyReport::default();
+        for pattern in self.destinations() {
+            let base = Config::glob_base(pattern);
+            for file in &files {
+                let to_path = base.join(file.strip_prefix(from_dotconfigs_path).unwrap_or(file));
+                if let Some(parent) = to_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                report.add_file(fs::copy(file, &to_path)?);
+
+                self.restore_ownership(&to_path)?;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Calculate the content hash of a file of the config.
+    fn file_content_hash(&self, path: &Path) -> Result<String> {
+        if self.trims_trailing_newline() {
+            return Ok(hasher::get_trimmed_file_hash(
+                path,
+                &mut self.hash_algorithm.hasher(),
+            )?);
+        }
+
+        Ok(hasher::get_file_hash(
+            path,
+            &mut self.hash_algorithm.hasher(),
+        )?)
+    }
+
+    /// Calculate the combined content hash of files of the config.
+    fn files_content_hash(&self, files: &[PathBuf]) -> Result<String> {
+        if self.trims_trailing_newline() {
+            return Ok(hasher::get_trimmed_files_hash(
+                files,
+                &mut self.hash_algorithm.hasher(),
+            )?);
+        }
+
+        Ok(hasher::get_files_hash(
+            files,
+            &mut self.hash_algorithm.hasher(),
+        )?)
+    }
+
+    /// Check if a path inside a directory config is excluded by its
+    /// `exclude_exact` paths or its `ignore` patterns.
+    ///
+    /// A path is excluded if it, or one of the directories containing it,
+    /// matches an excluded path exactly or one of the patterns. The
+    /// separators of the paths are normalized, so `nvim\lazy-lock.json` and
+    /// `./nvim/lazy-lock.json` both match `nvim/lazy-lock.json`. Compressed
+    /// configs are always stored whole, so nothing is excluded from them.
+    ///
+    /// # Arguments
+    ///
+    /// * `relative_path` - The path relative to the config directory.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use std::path::Path;
+    ///
+    /// let mut config = Config::new_dir("nvim", "~/.config/nvim");
+    /// config.exclude_exact = Some(vec![
+    ///     String::from("lazy-lock.json"),
+    ///     String::from("spell\\"),
+    /// ]);
+    ///
+    /// assert!(config.is_excluded(Path::new("lazy-lock.json")));
+    /// assert!(config.is_excluded(Path::new("spell/en.utf-8.add")));
+    /// assert!(!config.is_excluded(Path::new("lua/lazy-lock.json")));
+    /// assert!(!config.is_excluded(Path::new("init.lua")));
+    ///
+    /// config.ignore = Some(vec![String::from("target"), String::from("**/*.log")]);
+    /// assert!(config.is_excluded(Path::new("target/debug/app")));
+    /// assert!(config.is_excluded(Path::new("lua/plugins/update.log")));
+    /// assert!(!config.is_excluded(Path::new("lua/target.lua")));
+    /// ```
+    pub fn is_excluded(&self, relative_path: &Path) -> bool {
+        is_excluded_by(&self.exclusions(), relative_path)
+    }
+
+    /// Get the normalized `exclude_exact` paths and the compiled `ignore`
+    /// patterns of the config, see `is_excluded`.
+    ///
+    /// Invalid patterns are rejected when the config file is parsed (see
+    /// `validate_ignore`), so they are left out here.
+    fn exclusions(&self) -> Exclusions {
+        if self.is_compressed() {
+            return Exclusions::default();
+        }
+
+        let exact = self
+            .exclude_exact
+            .iter()
+            .flatten()
+            .map(|path| normalize_relative_path(path))
+            .filter(|path| !path.is_empty())
+            .collect();
+        let patterns = self
+            .ignore
+            .iter()
+            .flatten()
+            .filter_map(|pattern| glob::Pattern::new(&normalize_relative_path(pattern)).ok())
+            .collect();
+
+        Exclusions { exact, patterns }
+    }
+
+    /// List the files of a directory config that are not excluded, sorted
+    /// by their path.
+    fn included_files(&self, path: &Path, exclusions: &Exclusions) -> Result<Vec<PathBuf>> {
+        let mut files = hasher::list_dir_files(path)?
+            .into_iter()
+            .filter(|file| !is_excluded_by(exclusions, file.strip_prefix(path).unwrap_or(file)))
+            .collect::<Vec<PathBuf>>();
+        files.sort();
+
+        Ok(files)
+    }
+
+    /// Check if a file inside a directory config is left out of its hash by
+    /// its `hash_exclude` patterns, while still being synced.
+    ///
+    /// A file is left out if its path, or the path of one of the
+    /// directories containing it, matches one of the patterns. Compressed
+    /// configs are always hashed whole, so nothing is left out of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `relative_path` - The path relative to the config directory.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing whether the file is left out of the hash, or an
+    /// error if a pattern is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use std::path::Path;
+    ///
+    /// let mut config = Config::new_dir("foo", "~/.config/foo");
+    /// config.hash_exclude = Some(vec![String::from("version"), String::from("cache/*.bin")]);
+    ///
+    /// assert!(config.is_hash_excluded(Path::new("version")).unwrap());
+    /// assert!(config.is_hash_excluded(Path::new("cache/index.bin")).unwrap());
+    /// assert!(!config.is_hash_excluded(Path::new("lua/version")).unwrap());
+    /// assert!(!config.is_hash_excluded(Path::new("foo.toml")).unwrap());
+    /// ```
+    pub fn is_hash_excluded(&self, relative_path: &Path) -> Result<bool> {
+        Ok(matches_patterns(&self.hash_exclusions()?, relative_path))
+    }
+
+    /// Get the compiled `hash_exclude` patterns of the config, see
+    /// `is_hash_excluded`.
+    fn hash_exclusions(&self) -> Result<Vec<glob::Pattern>> {
+        match &self.hash_exclude {
+            Some(patterns) if !self.is_compressed() => patterns
+                .iter()
+                .map(|pattern| {
+                    glob::Pattern::new(&normalize_relative_path(pattern)).map_err(|e| {
+                        anyhow::anyhow!("Invalid hash_exclude pattern {pattern:#?}: {e}")
+                    })
+                })
+                .collect(),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// List the files of a directory config that are hashed, i.e. neither
+    /// excluded from syncing nor left out of the hash, sorted by their path.
+    fn hashed_files(&self, path: &Path) -> Result<Vec<PathBuf>> {
+        let hash_exclusions = self.hash_exclusions()?;
+
+        Ok(self
+            .included_files(path, &self.exclusions())?
+            .into_iter()
+            .filter(|file| {
+                !matches_patterns(&hash_exclusions, file.strip_prefix(path).unwrap_or(file))
+            })
+            .collect())
+    }
+
+    /// Calculate the content hash of a directory of the config, leaving out
+    /// the excluded files and the files left out of the hash.
+    fn dir_content_hash(&self, path: &Path) -> Result<String> {
+        if self.exclusions().is_empty()
+            && self.hash_exclusions()?.is_empty()
+            && !self.trims_trailing_newline()
+        {
+            return Ok(hasher::get_complete_dir_hash(
+                path,
+                &mut self.hash_algorithm.hasher(),
+            )?);
+        }
+
+        let files = self.hashed_files(path)?;
+        self.files_content_hash(&files)
+    }
+
+    /// Resolve the path of the config inside the dotconfigs directory.
+    ///
+    /// If the config has a custom `repo_path`, it is used relative to the
+    /// dotconfigs directory. Otherwise directories are stored by the config
+    /// name and files directly inside the home directory by their file name,
+    /// preferring an already existing entry named after the config. Other
+    /// files are stored by their file name inside a directory named after
+    /// the config (e.g. `app/config.yml`), unless an older version already
+    /// stored them by their file name.
+    ///
+    /// With `mirror_home`, configs inside the home directory are stored at
+    /// their path relative to it instead (e.g. `.config/nvim`).
+    ///
+    /// Compressed configs are stored with a `.tar.zst` extension appended to
+    /// that path.
+    ///
+    /// # Arguments
+    ///
+    /// * `dotconfigs_path` - The resolved path to the dotconfigs directory.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the path of the config inside the dotconfigs
+    /// directory, or an error if the `repo_path` is invalid or the config
+    /// path has no file name.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::{Config, ConfType};
+    /// use std::path::{Path, PathBuf};
+    ///
+    /// let mut config = Config::new(
+    ///     String::from("vimrc"),
+    ///     String::from("~/.vimrc"),
+    ///     None,
+    ///     Some(ConfType::File),
+    /// );
+    ///
+    /// let dotconfigs = Path::new("/tmp/dotconfigs");
+    /// assert_eq!(
+    ///     config.repo_path_in(dotconfigs).unwrap(),
+    ///     PathBuf::from("/tmp/dotconfigs/.vimrc")
+    /// );
+    ///
+    /// config.repo_path = Some(String::from("editors/vimrc"));
+    /// assert_eq!(
+    ///     config.repo_path_in(dotconfigs).unwrap(),
+    ///     PathBuf::from("/tmp/dotconfigs/editors/vimrc")
+    /// );
+    ///
+    /// let config = Config::new(
+    ///     String::from("app"),
+    ///     String::from("~/.config/app/config.yml"),
+    ///     None,
+    ///     Some(ConfType::File),
+    /// );
+    /// assert_eq!(
+    ///     config.repo_path_in(dotconfigs).unwrap(),
+    ///     PathBuf::from("/tmp/dotconfigs/app/config.yml")
+    /// );
+    /// ```
+    pub fn repo_path_in(&self, dotconfigs_path: &Path) -> Result<PathBuf> {
+        let path = self.uncompressed_repo_path_in(dotconfigs_path)?;
+
+        if self.is_compressed() {
+            let mut archive_path = path.into_os_string();
+            archive_path.push(".tar.zst");
+            return Ok(archive_path.into());
+        }
+
+        Ok(path)
+    }
+
+    /// Resolve the path of the config inside the dotconfigs directory,
+    /// ignoring the compression of the config.
+    fn uncompressed_repo_path_in(&self, dotconfigs_path: &Path) -> Result<PathBuf> {
+        self.validate_repo_path()?;
+
+        if let Some(repo_path) = &self.repo_path {
+            return Ok(dotconfigs_path.join(repo_path));
+        }
+
+        // Glob configs are stored as the directory of their pattern
+        let local_path = match self.is_glob() {
+            true => Config::glob_base(self.primary_path()),
+            false => fix_path!(self.primary_path()),
+        };
+        if self.mirror_home {
+            if let Ok(relative_path) = local_path.strip_prefix(utils::home_dir()?) {
+                if relative_path.components().next().is_some() {
+                    return Ok(dotconfigs_path.join(relative_path));
+                }
+            }
+        }
+
+        let path = dotconfigs_path.join(&self.name);
+        if self.conf_type.as_ref().is_some_and(ConfType::is_dir) || self.is_glob() {
+            return Ok(path);
+        }
+
+        let Some(file_name) = local_path.file_name() else {
+            return Err(anyhow::anyhow!(
+                "Invalid config path for {:#?}: {:#?}",
+                self.name,
+                self.primary_path()
+            ));
+        };
+
+        // Files outside of the home directory root (e.g.
+        // `~/.config/app/config.yml`) are stored inside a directory named
+        // after the config, so that files with the same name don't collide
+        let nested_path = path.join(file_name);
+        let flat_path = dotconfigs_path.join(file_name);
+        let is_file = self.conf_type.as_ref().is_some_and(ConfType::is_file)
+            || local_path.is_file()
+            || nested_path.is_file();
+        if is_file && local_path.parent() != Some(utils::home_dir()?.as_path()) {
+            // Keep using a file stored flat by an older version
+            if path.is_file() {
+                return Ok(path);
+            } else if !nested_path.exists() && flat_path.is_file() {
+                return Ok(flat_path);
+            }
+
+            return Ok(nested_path);
+        }
+
+        if path.exists() {
+            return Ok(path);
+        }
+
+        Ok(flat_path)
+    }
+
+    /// Calculate the hash of the metadata for a file or directory.
+    ///
+    /// This method computes the hash of the metadata
+    /// (e.g., file content or directory structure) for the configuration file
+    /// or directory specified by the `path` field of the `Config` instance,
+    /// using the `change_detection` mode of the config.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the hash as a string if successful,
+    /// or an error if the operation fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    ///
+    /// let config = Config::new(
+    ///     String::from("config.ron"),
+    ///     format!("{}/examples/config.ron", env!("CARGO_MANIFEST_DIR")),
+    ///     None,
+    ///     None,
+    /// );
+    ///
+    /// match config.metadata_digest() {
+    ///     Ok(hash) => println!("Metadata digest: {}", hash),
+    ///     Err(err) => eprintln!("Error calculating metadata digest: {:?}", err),
+    /// }
+    /// ```
+    pub fn metadata_digest(&self) -> Result<String> {
+        self.metadata_digest_with(self.change_detection)
+    }
+
+    /// Calculate the hash of the metadata for a file or directory using the
+    /// given change detection mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `change_detection` - The change detection mode to be used.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the hash as a string if successful,
+    /// or an error if the operation fails.
+    ///
+    /// A missing path yields an empty hash, unless the `strict_hash` option
+    /// is set, in which case it is reported as an error.
+    pub fn metadata_digest_with(&self, change_detection: ChangeDetection) -> Result<String> {
+        let path = fix_path!(self.primary_path());
+
+        // check if the path exists and return empty string if it doesn't
+        if !self.path_exists() {
+            if options::get().strict_hash {
+                return Err(anyhow::anyhow!(
+                    "Cannot hash {:#?}: {:#?} does not exist",
+                    self.name,
+                    path
+                ));
+            }
+            return Ok(String::new());
+        }
+
+        if self.is_glob() {
+            return self.glob_digest(change_detection);
+        }
+        if self.tracked_link_target(&path).is_some() {
+            return Ok(hasher::get_link_target_hash(
+                &path,
+                &mut self.hash_algorithm.hasher(),
+            )?);
+        }
+
+        if change_detection == ChangeDetection::MetadataStamp {
+            if path.is_dir()
+                && !(self.exclusions().is_empty() && self.hash_exclusions()?.is_empty())
+            {
+                let files = self.hashed_files(&path)?;
+                return Ok(hasher::get_files_metadata_stamp_hash(
+                    &path,
+                    &files,
+                    &mut self.hash_algorithm.hasher(),
+                )?);
+            }
+
+            return Ok(hasher::get_metadata_stamp_hash(
+                &path,
+                &mut self.hash_algorithm.hasher(),
+            )?);
+        }
+
+        if path.is_file() {
+            return self.file_content_hash(&path);
+        }
+        if path.is_dir() {
+            return self.dir_content_hash(&path);
+        }
+
+        Err(anyhow::anyhow!(
+            "Invalid config type: {:#?}",
+            self.primary_path()
+        ))
+    }
+
+    /// Calculate the content hash of the config stored in the dotconfigs
+    /// directory.
+    ///
+    /// Compressed configs are hashed over the files inside the archive, so
+    /// the result can be compared with the content hash of the local config.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo_config_path` - The path of the config inside the dotconfigs
+    ///   directory, as resolved by `repo_path_in`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the hash, `None` if the config doesn't exist in
+    /// the dotconfigs directory, or an error if the hashing fails.
+    pub fn repo_digest(&self, repo_config_path: &Path) -> Result<Option<String>> {
+        if self.tracked_link_target(repo_config_path).is_some() {
+            return Ok(Some(hasher::get_link_target_hash(
+                repo_config_path,
+                &mut self.hash_algorithm.hasher(),
+            )?));
+        }
+        if self.is_compressed() && repo_config_path.is_file() {
+            return Ok(Some(hasher::get_archive_hash(
+                repo_config_path,
+                &mut self.hash_algorithm.hasher(),
+            )?));
+        }
+        if repo_config_path.is_file() {
+            return Ok(Some(self.file_content_hash(repo_config_path)?));
+        }
+        if repo_config_path.is_dir() {
+            return Ok(Some(self.dir_content_hash(repo_config_path)?));
+        }
+
+        Ok(None)
+    }
+
+    /// Check if the local configuration and its copy in the dotconfigs
+    /// directory diverged in type, i.e. one of them is a file and the other
+    /// one a directory.
+    ///
+    /// The copy of a compressed configuration is an archive file, which
+    /// stands for a directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo_config_path` - The path of the config inside the dotconfigs
+    ///   directory, as resolved by `repo_path_in`.
+    ///
+    /// # Returns
+    ///
+    /// Whether the types differ, which is `false` if either side doesn't
+    /// exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use std::path::Path;
+    ///
+    /// let config = Config::new_file("examples", "./examples");
+    ///
+    /// assert!(config.type_changed(Path::new("./examples/config.ron")));
+    /// assert!(!config.type_changed(Path::new("./examples/local_configs_dir")));
+    /// assert!(!config.type_changed(Path::new("./examples/does-not-exist")));
+    /// ```
+    pub fn type_changed(&self, repo_config_path: &Path) -> bool {
+        let local_path = fix_path!(self.primary_path());
+
+        // A tracked symlink replaces whatever is on the other side
+        if self.tracked_link_target(&local_path).is_some()
+            || self.tracked_link_target(repo_config_path).is_some()
+        {
+            return false;
+        }
+        let (Ok(local), Ok(repo)) = (fs::metadata(local_path), fs::metadata(repo_config_path))
+        else {
+            return false;
+        };
+
+        let repo_is_dir = repo.is_dir() || self.is_compressed() && repo.is_file();
+        local.is_dir() != repo_is_dir
+    }
+
+    /// Check if the configuration needs metadata update.
+    ///
+    /// This method checks whether the configuration needs an update of its
+    /// metadata, such as hash and configuration type. It is required because
+    /// the hash and configuration type are not stored in the dotconfig file
+    /// and are used to determine if the configuration has changed since the
+    /// last sync.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing `true` if metadata update is required, `false`
+    /// otherwise, or an error if the config can't be hashed (e.g. if its
+    /// directory contains a cycle).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::{Config, ConfType};
+    ///
+    /// let mut config = Config::new(
+    ///     String::from("config.ron"),
+    ///     format!("{}/examples/config.ron", env!("CARGO_MANIFEST_DIR")),
+    ///     None,
+    ///     Some(ConfType::File),
+    /// );
+    ///
+    /// assert!(config.check_update_metadata_required().unwrap());
+    /// ```
+    pub fn check_update_metadata_required(&self) -> Result<bool> {
+        // A missing config never matches the stored hash, even an empty one
+        if !self.path_exists() {
+            return Ok(true);
+        }
+
+        match self.hash.as_ref() {
+            Some(_) => {
+                let digest = self.metadata_digest()?;
+
+                // If hash hash doesn't match, then we require metadata update
+                if !self.hash_matches(&digest) {
+                    Ok(true)
+                } else {
+                    // If config tye is not preset, then we require metadata update
+                    Ok(self.conf_type.is_none())
+                }
+            }
+            // If hash is not set, then we require metadata update
+            None => Ok(true),
+        }
+    }
+
+    /// Check if the stored hash matches a digest of the current algorithm.
+    ///
+    /// The stored hash only matches if it was produced by the same
+    /// algorithm, so a change of the algorithm triggers a rehash. Unprefixed
+    /// hashes are treated as SHA-1 hashes. An empty digest, as produced for
+    /// a missing path, never matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `digest` - The unprefixed digest to compare the stored hash with.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    ///
+    /// let mut config = Config::new_file("vimrc", "~/.vimrc");
+    /// assert!(!config.hash_matches("abcd"));
+    ///
+    /// config.hash = Some(String::from("sha1:abcd"));
+    /// assert!(config.hash_matches("abcd"));
+    ///
+    /// config.hash = Some(String::from("abcd"));
+    /// assert!(config.hash_matches("abcd"));
+    ///
+    /// config.hash = Some(String::from("blake3:abcd"));
+    /// assert!(!config.hash_matches("abcd"));
+    ///
+    /// config.hash = Some(String::from("sha1:"));
+    /// assert!(!config.hash_matches(""));
+    /// ```
+    pub fn hash_matches(&self, digest: &str) -> bool {
+        let Some(hash) = self.hash.as_ref() else {
+            return false;
+        };
+
+        if digest.is_empty() {
+            return false;
+        }
+
+        let (algorithm, stored_digest) = hasher::parse_hash(hash);
+        algorithm == Some(self.hash_algorithm) && stored_digest == digest
+    }
+
+    /// Update the hash of the configuration's metadata.
+    ///
+    /// This method calculates the new hash of the configuration's metadata
+    /// and updates the `hash` field of the `Config` instance, prefixed with
+    /// the algorithm that produced it.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure of the operation.
+    pub fn update_config_hash(&mut self) -> Result<()> {
+        // calculate the new hash of the config
+        let new_hash = self.metadata_digest()?;
+
+        self.hash = Some(hasher::format_hash(self.hash_algorithm, &new_hash));
+        Ok(())
+    }
 
-        if !path.exists() {
-            println!("Config does not exist: {:#?}", self.path);
+    /// Check if the stored `conf_type` of a config that doesn't exist yet
+    /// disagrees with its path, i.e. the config is stored as a directory
+    /// but its path has a file extension (e.g. `~/.config/app/config.toml`).
+    ///
+    /// The type of an existing config is detected from the path, so only
+    /// configs whose path doesn't exist are checked. Extensions used for
+    /// directories (`.d`) and glob configs are ignored. This is only a
+    /// heuristic, e.g. for configs registered before their path exists.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    ///
+    /// assert!(Config::new_dir("app", "/does/not/exist/config.toml").conf_type_mismatch());
+    /// assert!(!Config::new_file("app", "/does/not/exist/config.toml").conf_type_mismatch());
+    /// assert!(!Config::new_dir("fish", "/does/not/exist/conf.d").conf_type_mismatch());
+    /// assert!(!Config::new_dir("nvim", "/does/not/exist/nvim").conf_type_mismatch());
+    /// ```
+    pub fn conf_type_mismatch(&self) -> bool {
+        if self.conf_type != Some(ConfType::Dir) || self.is_glob() || self.path_exists() {
+            return false;
+        }
+
+        Path::new(self.primary_path())
+            .extension()
+            .is_some_and(|extension| extension != "d")
+    }
+
+    /// Update the configuration type of the `Config`.
+    ///
+    /// This method checks whether the configuration specified by the
+    /// `Config` instance is a file or a directory.
+    /// If the `conf_type` field is already set, it skips the check.
+    /// Otherwise, it determines the type based on the path.
+    ///
+    /// If the path does not exist, it prints an error message and
+    /// returns without modifying the `Config`, warning if the stored type
+    /// looks wrong for the path (see `conf_type_mismatch`).
+    ///
+    /// # Errors
+    ///
+    /// This method may return an error if it encounters issues accessing the
+    /// file system or determining the config type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::{Config, ConfType};
+    ///
+    /// let mut config = Config::new(
+    ///     String::from("config.ron"),
+    ///     format!("{}/examples/config.ron", env!("CARGO_MANIFEST_DIR")),
+    ///     None,
+    ///     None,
+    /// );
+    ///
+    /// // Update the configuration type.
+    /// config.update_config_type().expect("Failed to update config type");
+    ///
+    /// assert_eq!(config.conf_type, Some(ConfType::File));
+    pub fn update_config_type(&mut self) -> Result<()> {
+        // The matches of a glob config are stored as a directory
+        if self.is_glob() {
+            self.conf_type.get_or_insert(ConfType::Dir);
             return Ok(());
         }
 
+        // The type is detected from the first destination that exists
+        let Some(path) = self
+            .destinations()
+            .into_iter()
+            .map(|path| fix_path!(path))
+            .find(|path| path.exists())
+        else {
+            info!("Config does not exist: {:#?}", self.primary_path());
+            if self.conf_type_mismatch() {
+                println!(
+                    "Warning: {:#?} is stored as a directory, but its path {:#?} looks like a file",
+                    self.name,
+                    self.primary_path()
+                );
+            }
+            return Ok(());
+        };
+
         // If the config type is not set, then update it
         if self.conf_type.is_none() {
             if path.is_file() {
@@ -431,7 +2105,7 @@ impl Config {
             } else if path.is_dir() {
                 self.conf_type = Some(ConfType::Dir);
             } else {
-                println!("Invalid config type: {:#?}", self.path);
+                println!("Invalid config type: {:#?}", path);
                 return Err(anyhow::anyhow!("Invalid config type"));
             }
         }
@@ -439,6 +2113,51 @@ impl Config {
         Ok(())
     }
 
+    /// Change the path of the config.
+    ///
+    /// The metadata of the config belongs to its old path, so the `hash`
+    /// and last sync time are cleared and the `conf_type` is detected again
+    /// from the new path (it is `None` if the new path doesn't exist). A
+    /// config synced to multiple `paths` is changed to the single new path.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_path` - The new path of the config.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the type of the new path can't be
+    /// detected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::{Config, ConfType};
+    ///
+    /// let mut config = Config::new(
+    ///     String::from("examples"),
+    ///     format!("{}/examples/config.ron", env!("CARGO_MANIFEST_DIR")),
+    ///     Some(String::from("sha1:0a")),
+    ///     Some(ConfType::File),
+    /// );
+    ///
+    /// config
+    ///     .set_path(format!("{}/examples", env!("CARGO_MANIFEST_DIR")))
+    ///     .expect("Failed to set the config path");
+    ///
+    /// assert_eq!(config.conf_type, Some(ConfType::Dir));
+    /// assert_eq!(config.hash, None);
+    /// ```
+    pub fn set_path(&mut self, new_path: impl Into<String>) -> Result<()> {
+        self.path = new_path.into();
+        self.paths = None;
+        self.hash = None;
+        self.last_sync = None;
+        self.conf_type = None;
+
+        self.update_config_type()
+    }
+
     /// Update the metadata of the `Config`.
     ///
     /// This method updates the hash of the configuration and its type by
@@ -467,12 +2186,13 @@ impl Config {
     ///
     /// ## Implementation Notes
     ///
-    /// - This method updates the `hash` and `conf_type` fields in the `Config`
-    /// instance.
+    /// - This method updates the `hash`, `conf_type` and `last_sync` fields in
+    ///   the `Config` instance.
     /// - It relies on the `update_config_hash` and `update_config_type` methods.
     pub fn update_metadata(&mut self) -> Result<()> {
         self.update_config_hash()?;
         self.update_config_type()?;
+        self.last_sync = Some(utils::unix_now());
 
         Ok(())
     }
@@ -490,13 +2210,18 @@ impl Config {
     /// # Arguments
     ///
     /// - `path`: A string specifying the destination path where the
-    /// configuration should be synced.
+    ///   configuration should be synced.
     ///
     /// # Errors
     ///
     /// This method may return errors if it encounters issues during the
     /// file copying process.
     ///
+    /// # Returns
+    ///
+    /// A `CopyReport` of the copied files, with the files that were not
+    /// copied (e.g. files stored as stubs) as skipped.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -510,26 +2235,30 @@ impl Config {
     /// );
     ///
     /// // Sync the configuration to the specified path.
-    /// config.pull_config(&format!("{}/examples", env!("CARGO_MANIFEST_DIR")))
-    ///         .expect("Failed to pull config");
+    /// let report = config
+    ///     .pull_config(&format!("{}/examples", env!("CARGO_MANIFEST_DIR")))
+    ///     .expect("Failed to pull config");
+    ///
+    /// // The type of the config is unknown, so nothing is copied
+    /// assert_eq!(report.files_copied, 0);
     /// ```
     ///
     /// ## Implementation Notes
     ///
     /// - This method determines whether to copy a file or a directory based
-    /// on the `conf_type` field.
+    ///   on the `conf_type` field.
     /// - It relies on the `copy_config_directory` method for directory
-    /// copying.
-    pub fn pull_config(&self, path: &String) -> Result<()> {
+    ///   copying.
+    pub fn pull_config(&self, path: &String) -> Result<CopyReport> {
         let dotconfigs_path = fix_path!(path);
 
-        let selfpath = fix_path!(&self.path);
+        let selfpath = fix_path!(self.primary_path());
 
         let config_path = dotconfigs_path.join(selfpath);
 
         // If dotconfigs_path doesn't exist, create it
-        if !dotconfigs_path.exists() {
-            println!(
+        if !dotconfigs_path.exists() && !options::get().dry_run {
+            info!(
                 "Creating dotconfigs directory: {:#?}",
                 dotconfigs_path.display()
             );
@@ -547,55 +2276,174 @@ impl Config {
             }
         }
 
+        if self.is_glob() {
+            let repo_config_path = self.repo_path_in(&dotconfigs_path)?;
+            if options::get().dry_run {
+                println!(
+                    "Would copy {:#?} -> {:#?}",
+                    self.primary_path(),
+                    repo_config_path
+                );
+                return Ok(CopyReport::default());
+            }
+            return self.pull_glob_matches(&repo_config_path);
+        }
+
         // If the config path doesn't exist, skip it
-        if !config_path.exists() {
-            println!("Path does not exists! skipping: {:#?}", config_path);
-            return Ok(());
+        if !config_path.exists() && self.tracked_link_target(&config_path).is_none() {
+            info!("Path does not exists! skipping: {:#?}", config_path);
+            return Ok(CopyReport {
+                skipped: vec![config_path],
+                ..Default::default()
+            });
+        }
+
+        // Resolve where the config is stored inside the dotconfigs directory
+        let repo_config_path = self.repo_path_in(&dotconfigs_path)?;
+
+        // A local path linked to the stored copy has nothing to pull
+        if is_same_path(&config_path, &repo_config_path) {
+            return Ok(CopyReport {
+                files_unchanged: 1,
+                ..Default::default()
+            });
+        }
+
+        if options::get().dry_run {
+            println!("Would copy {:#?} -> {:#?}", config_path, repo_config_path);
+            if self.conf_type.as_ref().is_some_and(ConfType::is_dir)
+                && !self.is_compressed()
+                && repo_config_path.is_dir()
+            {
+                self.remove_stale_files(&config_path, &repo_config_path)?;
+            }
+            return Ok(CopyReport::default());
+        }
+
+        let report =
+            self.copy_to_dotconfigs_path(&dotconfigs_path, &config_path, &repo_config_path)?;
+        self.verify_after_copy(&config_path, &repo_config_path)?;
+
+        Ok(report)
+    }
+
+    /// Copies the configuration from its local path into the dotconfigs
+    /// directory.
+    ///
+    /// This function is used by the pull_config function to perform the
+    /// actual copy operation, based on the `conf_type` field.
+    fn copy_to_dotconfigs_path(
+        &self,
+        dotconfigs_path: &Path,
+        config_path: &Path,
+        repo_config_path: &Path,
+    ) -> Result<CopyReport> {
+        let mut report = CopyReport::default();
+
+        // A tracked symlink is copied as a symlink
+        if let Some(target) = self.tracked_link_target(config_path) {
+            utils::replace_with_symlink(target, repo_config_path)?;
+            report.add_file(0);
+
+            return Ok(report);
         }
 
         // if the config path is just a file, then directly copy it
         if let Some(conf_type) = &self.conf_type {
             if conf_type.is_file() {
-                if let Err(e) = fs::copy(
-                    &config_path,
-                    dotconfigs_path.join(config_path.file_name().unwrap()),
-                ) {
-                    match e.kind() {
+                // Create the parent directories of a custom repo_path
+                if let Some(parent) = repo_config_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+
+                let bytes = match fs::copy(config_path, repo_config_path) {
+                    Ok(bytes) => bytes,
+                    Err(e) => match e.kind() {
                         io::ErrorKind::PermissionDenied => {
                             escape_privilege().expect("Failed to escape privilege");
-                            fs::copy(
-                                &config_path,
-                                dotconfigs_path.join(config_path.file_name().unwrap()),
-                            )
-                            .expect("Failed to copy file");
+                            fs::copy(config_path, repo_config_path).expect("Failed to copy file")
                         }
 
                         _ => {
                             return Err(anyhow::anyhow!("Failed to copy file: {:#?}", config_path))
                         }
-                    }
-                }
-                return Ok(());
+                    },
+                };
+                report.add_file(bytes);
+
+                return Ok(report);
             } else if conf_type.is_dir() {
                 // Check if directory exists
                 if !dotconfigs_path.exists() {
-                    println!("Creating dotconfigs directory: {:#?}", dotconfigs_path);
-                    fs::create_dir_all(&dotconfigs_path)?;
+                    info!("Creating dotconfigs directory: {:#?}", dotconfigs_path);
+                    fs::create_dir_all(dotconfigs_path)?;
+                }
+
+                // Warn about files that would overwrite each other on
+                // case-insensitive filesystems
+                self.case_collisions().iter().for_each(|(a, b)| {
+                    println!(
+                        "Warning: {:#?} and {:#?} in {:#?} differ only in case and collide on case-insensitive filesystems",
+                        a, b, self.name
+                    );
+                });
+
+                // Store compressed configs as a single archive
+                if self.is_compressed() {
+                    if let Some(parent) = repo_config_path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+
+                    utils::compress_dir(config_path, repo_config_path)?;
+                    report.add_file(fs::metadata(repo_config_path)?.len());
+
+                    return Ok(report);
                 }
                 // if the config path is a directory, then copy the directory contents
+                let exclusions = self.exclusions();
                 WalkDir::new(config_path)
+                    .follow_links(false)
                     .into_iter()
                     .filter_map(|e| e.ok())
                     .for_each(|entry| {
+                        let path = entry.path();
                         // ignore git directory
-                        if entry.path().to_str().unwrap().contains(".git") {
+                        if utils::is_git_path(path.strip_prefix(config_path).unwrap_or(path)) {
+                            return;
+                        }
+                        if is_excluded_by(
+                            &exclusions,
+                            path.strip_prefix(config_path).unwrap_or(path),
+                        ) {
+                            return;
+                        }
+                        let new_path = repo_config_path
+                            .join(path.strip_prefix(fix_path!(self.primary_path())).unwrap());
+
+                        // Symlinks inside the config are recreated with the
+                        // same target instead of copying what they point to
+                        if entry.depth() > 0 && entry.path_is_symlink() {
+                            let Ok(target) = fs::read_link(path) else {
+                                report.skipped.push(path.to_path_buf());
+                                return;
+                            };
+                            if fs::read_link(&new_path).is_ok_and(|t| t == target) {
+                                report.files_unchanged += 1;
+                                return;
+                            }
+                            if new_path.is_dir() && !new_path.is_symlink() {
+                                let _ = fs::remove_dir_all(&new_path);
+                            }
+
+                            match utils::replace_with_symlink(&target, &new_path) {
+                                Ok(()) => report.add_file(0),
+                                Err(e) => {
+                                    println!("Failed to copy the symlink {:#?}: {:#?}", path, e);
+                                    report.skipped.push(path.to_path_buf());
+                                }
+                            }
                             return;
                         }
-                        let path = entry.path();
-                        let new_path = dotconfigs_path.join(
-                            PathBuf::from(&self.name)
-                                .join(path.strip_prefix(fix_path!(&self.path)).unwrap()),
-                        );
 
                         if path.is_dir() {
                             if let Err(e) = fs::create_dir_all(&new_path) {
@@ -612,118 +2460,356 @@ impl Config {
                                     }
                                 }
                             }
-                        } else if let Err(e) = fs::copy(path, &new_path) {
-                            match e.kind() {
-                                io::ErrorKind::AlreadyExists => {}
-                                io::ErrorKind::PermissionDenied => {
-                                    let mut permissions = fs::metadata(path).unwrap().permissions();
-                                    permissions.set_mode(0o644);
-                                    fs::set_permissions(path, permissions).unwrap();
-
-                                    // Now attempt to copy again
-                                    if let Err(copy_error) = fs::copy(path, &new_path) {
-                                        // Handle any error that may occur during the second copy attempt
-                                        match copy_error.kind() {
-                                            io::ErrorKind::AlreadyExists => {}
-                                            _ => {
-                                                println!("Failed to copy file: {:#?}", copy_error);
+                        } else if self.lfs_threshold.is_some_and(|threshold| {
+                            entry.metadata().is_ok_and(|m| m.len() > threshold)
+                        }) {
+                            // Store large files as stubs pointing to them
+                            if let Err(e) = Stub::from_file(path, self.hash_algorithm)
+                                .and_then(|stub| stub.write(&new_path))
+                            {
+                                println!("Failed to store the stub of {:#?}: {:#?}", path, e);
+                            }
+                            report.skipped.push(path.to_path_buf());
+                        } else if utils::same_file_contents(path, &new_path).unwrap_or(false) {
+                            // Identical files are left as they are
+                            report.files_unchanged += 1;
+                        } else {
+                            match fs::copy(path, &new_path) {
+                                Ok(bytes) => report.add_file(bytes),
+                                Err(e) => match e.kind() {
+                                    io::ErrorKind::AlreadyExists => {
+                                        report.skipped.push(path.to_path_buf())
+                                    }
+                                    io::ErrorKind::PermissionDenied => {
+                                        let mut permissions =
+                                            fs::metadata(path).unwrap().permissions();
+                                        permissions.set_mode(0o644);
+                                        fs::set_permissions(path, permissions).unwrap();
+
+                                        // Now attempt to copy again
+                                        match fs::copy(path, &new_path) {
+                                            Ok(bytes) => report.add_file(bytes),
+                                            // Handle any error that may occur during the second copy attempt
+                                            Err(copy_error) => {
+                                                if copy_error.kind() != io::ErrorKind::AlreadyExists
+                                                {
+                                                    println!(
+                                                        "Failed to copy file: {:#?}",
+                                                        copy_error
+                                                    );
+                                                }
+                                                report.skipped.push(path.to_path_buf());
                                             }
                                         }
                                     }
-                                }
-                                _ => {
-                                    println!("Failed to copy file: {:#?}", e);
-                                }
+                                    _ => {
+                                        println!("Failed to copy file: {:#?}", e);
+                                        report.skipped.push(path.to_path_buf());
+                                    }
+                                },
                             }
                         }
                     });
+
+                // Set the modes of the directories last, so read-only
+                // directories can still be filled
+                for entry in WalkDir::new(config_path)
+                    .contents_first(true)
+                    .follow_links(false)
+                    .into_iter()
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.file_type().is_dir())
+                {
+                    let relative_path = entry.path().strip_prefix(config_path)?;
+                    let new_path = repo_config_path.join(relative_path);
+                    if utils::is_git_path(relative_path)
+                        || is_excluded_by(&exclusions, relative_path)
+                        || !new_path.is_dir()
+                    {
+                        continue;
+                    }
+
+                    utils::copy_permissions(entry.path(), &new_path)?;
+                }
+
+                self.remove_stale_files(config_path, repo_config_path)?;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Remove the files of a pulled directory that no longer exist in its
+    /// source.
+    ///
+    /// Git entries are ignored the same way as when the directory is copied,
+    /// so they are never removed. Excluded files are removed, since they are
+    /// no longer synced. The copies of other configs stored inside the
+    /// directory, see `nested_repo_paths`, are kept. With the `dry_run`
+    /// option, the files are printed instead of being removed.
+    fn remove_stale_files(&self, config_path: &Path, repo_config_path: &Path) -> Result<()> {
+        let exclusions = self.exclusions();
+        for entry in WalkDir::new(repo_config_path)
+            .min_depth(1)
+            .contents_first(true)
+            .follow_links(false)
+        {
+            let entry = entry?;
+            let relative_path = entry.path().strip_prefix(repo_config_path)?;
+            if utils::is_git_path(relative_path)
+                || self
+                    .nested_repo_paths
+                    .iter()
+                    .any(|nested_path| relative_path.starts_with(nested_path))
+                || !is_excluded_by(&exclusions, relative_path)
+                    && fs::symlink_metadata(config_path.join(relative_path)).is_ok()
+            {
+                continue;
+            }
+
+            if options::get().dry_run {
+                // Directories are only removed once their files are
+                if !entry.file_type().is_dir() {
+                    println!("Would remove {:#?}", entry.path());
+                }
+                continue;
+            }
+
+            info!(
+                "Removing {:#?} from {:#?}, it no longer exists in {:#?}",
+                relative_path, self.name, config_path
+            );
+            if entry.file_type().is_dir() {
+                // Directories still containing git entries are kept
+                if let Err(e) = fs::remove_dir(entry.path()) {
+                    if e.kind() != io::ErrorKind::DirectoryNotEmpty {
+                        return Err(e.into());
+                    }
+                }
+            } else {
+                fs::remove_file(entry.path())?;
             }
         }
 
         Ok(())
     }
 
+    /// Verify that a copy of the configuration matches its source.
+    ///
+    /// Both paths are hashed by their contents, the same way as the
+    /// directory is copied on pull, so git entries are ignored. A compressed
+    /// archive is compared by the files inside it.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The path the configuration was copied from.
+    /// * `dst` - The path the configuration was copied to.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing whether the copy matches its source, or an error
+    /// if either path can't be hashed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use std::path::Path;
+    ///
+    /// let config = Config::new_file("config.ron", "~/config.ron");
+    /// let path = format!("{}/examples/config.ron", env!("CARGO_MANIFEST_DIR"));
+    ///
+    /// assert!(config.verify_copy(Path::new(&path), Path::new(&path)).unwrap());
+    /// ```
+    pub fn verify_copy(&self, src: &Path, dst: &Path) -> Result<bool> {
+        Ok(self.copy_digest(src)? == self.copy_digest(dst)?)
+    }
+
+    /// Calculate the content hash of a copy of the config, see `verify_copy`.
+    fn copy_digest(&self, path: &Path) -> Result<Option<String>> {
+        if self.tracked_link_target(path).is_some() {
+            return Ok(Some(hasher::get_link_target_hash(
+                path,
+                &mut self.hash_algorithm.hasher(),
+            )?));
+        }
+        if self.is_compressed() && path.is_file() {
+            return Ok(Some(hasher::get_archive_hash(
+                path,
+                &mut self.hash_algorithm.hasher(),
+            )?));
+        }
+        if path.is_file() {
+            return Ok(Some(self.file_content_hash(path)?));
+        }
+        if !path.is_dir() {
+            return Ok(None);
+        }
+
+        let exclusions = self.exclusions();
+        let files = self
+            .included_files(path, &exclusions)?
+            .into_iter()
+            .filter(|file| !utils::is_git_path(file.strip_prefix(path).unwrap_or(file)))
+            .collect::<Vec<PathBuf>>();
+
+        Ok(Some(self.files_content_hash(&files)?))
+    }
+
+    /// Verify a copy of the configuration if the `verify_after` option is
+    /// set, and report a mismatch as an error.
+    fn verify_after_copy(&self, src: &Path, dst: &Path) -> Result<()> {
+        if options::get().verify_after && !self.verify_copy(src, dst)? {
+            return Err(anyhow::anyhow!(
+                "Failed to verify {:#?}: {:#?} does not match {:#?}",
+                self.name,
+                dst,
+                src
+            ));
+        }
+
+        Ok(())
+    }
+
     /// Copies the contents of a configuration directory from the dotconfig
     /// directory to the home directory.
     ///
     /// This function is used by the push_config function to perform the
     /// actual copy operation.
     ///
+    /// The directory is first copied into a temporary sibling directory,
+    /// which is only swapped into place once the whole copy succeeds. If the
+    /// copy fails, the temporary directory is removed and the original
+    /// directory is left intact. The leftovers of an interrupted push are
+    /// cleaned up (or the original directory is recovered) on the next push.
+    ///
     /// # Arguments
     ///
     /// * `to_config_path`: The path to the configuration directory in the
-    /// home directory.
+    ///   home directory.
     /// * `from_dotconfigs_path`: The path to the dotconfig directory.
     ///
     /// # Returns
     ///
     /// Returns a Result indicating success or an error if the copy operation
     /// fails.
-    fn copy_config_directory(to_config_path: &PathBuf, from_dotconfigs_path: &Path) -> Result<()> {
-        if !to_config_path.exists() {
-            if let Err(e) = fs::create_dir_all(to_config_path) {
+    fn copy_config_directory(
+        to_config_path: &PathBuf,
+        from_dotconfigs_path: &Path,
+        exclusions: &Exclusions,
+    ) -> Result<CopyReport> {
+        let Some(file_name) = to_config_path.file_name() else {
+            return Err(anyhow::anyhow!(
+                "Invalid config path: {:#?}",
+                to_config_path
+            ));
+        };
+        let file_name = file_name.to_string_lossy();
+        let staging_path = to_config_path.with_file_name(format!(".{file_name}.sync-dotfiles-new"));
+        let backup_path = to_config_path.with_file_name(format!(".{file_name}.sync-dotfiles-old"));
+
+        // Create the parent directory of the config if it doesn't exist
+        if let Some(parent) = to_config_path.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
                 match e.kind() {
                     io::ErrorKind::PermissionDenied => {
                         escape_privilege().expect("Failed to escape privilege");
-                        fs::create_dir_all(to_config_path).expect("Failed to create directory");
+                        fs::create_dir_all(parent).expect("Failed to create directory");
                     }
                     _ => {
-                        return Err(anyhow::anyhow!(
-                            "Failed to create directory: {:#?}",
-                            to_config_path
-                        ));
+                        return Err(anyhow::anyhow!("Failed to create directory: {:#?}", parent));
                     }
                 }
             }
-        } else {
-            // Delete all the files in the to_config_path directory
-            // Use match for Ignoring the NotFound error as it is not a problem
-            if let Err(e) = fs::remove_dir_all(to_config_path) {
-                match e.kind() {
-                    io::ErrorKind::NotFound => {}
-                    io::ErrorKind::PermissionDenied => {
-                        escape_privilege().expect("Failed to escape privilege");
-                        fs::remove_dir_all(to_config_path).expect("Failed to remove directory");
-                    }
-                    _ => {
-                        return Err(anyhow::anyhow!(
-                            "Failed to delete directory: {:#?}",
-                            to_config_path
-                        ));
-                    }
-                }
+        }
+
+        // Clean up the leftovers of an interrupted push, recovering the
+        // original directory if it was already moved out of the way
+        if backup_path.exists() {
+            if to_config_path.exists() {
+                fs::remove_dir_all(&backup_path)?;
+            } else {
+                fs::rename(&backup_path, to_config_path)?;
             }
+        }
+        if staging_path.exists() {
+            fs::remove_dir_all(&staging_path)?;
+        }
 
-            // Create the to_config_path directory again
-            if let Err(e) = fs::create_dir_all(to_config_path) {
-                match e.kind() {
-                    io::ErrorKind::PermissionDenied => {
-                        escape_privilege().expect("Failed to escape privilege");
-                        fs::create_dir_all(to_config_path).expect("Failed to create directory");
-                    }
-                    _ => {
-                        return Err(anyhow::anyhow!(
-                            "Failed to create directory: {:#?}",
-                            to_config_path
-                        ))
-                    }
-                }
+        // Copy the config into the staging directory
+        if let Err(e) = utils::copy_dir(from_dotconfigs_path, staging_path.as_path()) {
+            let _ = fs::remove_dir_all(&staging_path);
+
+            return Err(anyhow::anyhow!(
+                "Failed to copy {:#?} to {:#?}: {e}",
+                from_dotconfigs_path,
+                to_config_path
+            ));
+        }
+
+        // Pushing a stub would replace the original file with it, so the
+        // stubs are replaced with the original files first
+        if let Err(e) = stub::restore_stubs(&staging_path, to_config_path) {
+            let _ = fs::remove_dir_all(&staging_path);
+
+            return Err(anyhow::anyhow!(
+                "Failed to push {:#?} to {:#?}: {e}",
+                from_dotconfigs_path,
+                to_config_path
+            ));
+        }
+
+        let report = match copied_files_report(&staging_path) {
+            Ok(report) => report,
+            Err(e) => {
+                let _ = fs::remove_dir_all(&staging_path);
+                return Err(e);
             }
+        };
+
+        // The excluded paths aren't synced, so they are kept as they are
+        if let Err(e) = keep_excluded_paths(to_config_path, &staging_path, exclusions) {
+            let _ = fs::remove_dir_all(&staging_path);
+
+            return Err(anyhow::anyhow!(
+                "Failed to keep the excluded paths of {:#?}: {e}",
+                to_config_path
+            ));
         }
 
-        // copy config from from_dotconfigs_path directory to to_config_path directory
-        WalkDir::new(to_config_path).into_iter().for_each(|entry| {
-            if entry.is_err() {
-                println!("Failed to read directory: {:#?}", entry);
-                return;
+        // Swap the staging directory into place
+        if to_config_path.exists() {
+            if let Err(e) = fs::rename(to_config_path, &backup_path) {
+                let _ = fs::remove_dir_all(&staging_path);
+
+                return Err(anyhow::anyhow!(
+                    "Failed to replace directory: {:#?} ({e})",
+                    to_config_path
+                ));
             }
+        }
 
-            let entry = entry.ok().unwrap();
+        if let Err(e) = fs::rename(&staging_path, to_config_path) {
+            if backup_path.exists() {
+                let _ = fs::rename(&backup_path, to_config_path);
+            }
+            let _ = fs::remove_dir_all(&staging_path);
 
-            utils::copy_dir(from_dotconfigs_path, entry.path()).expect("Failed to copy directory");
-        });
+            return Err(anyhow::anyhow!(
+                "Failed to replace directory: {:#?} ({e})",
+                to_config_path
+            ));
+        }
 
-        Ok(())
+        // Use match for Ignoring the NotFound error as it is not a problem
+        if let Err(e) = fs::remove_dir_all(&backup_path) {
+            match e.kind() {
+                io::ErrorKind::NotFound => {}
+                _ => println!("Failed to remove directory: {:#?} ({e})", backup_path),
+            }
+        }
+
+        Ok(report)
     }
 
     /// Push the configuration from the home directory or expected
@@ -739,7 +2825,7 @@ impl Config {
     /// # Arguments
     ///
     /// - `path`: A string specifying the destination path in the dotconfig
-    /// directory where the configuration should be pushed.
+    ///   directory where the configuration should be pushed.
     ///
     /// # Errors
     ///
@@ -781,33 +2867,244 @@ impl Config {
     /// file.write_all(content.as_bytes()).expect("Failed to write file");
     /// ```
     ///
-    /// ## Implementation Notes
+    /// ## Implementation Notes
+    ///
+    /// - This method determines whether to copy a file or a directory based on
+    ///   the `conf_type` field.
+    /// - It relies on the `copy_config_directory` method for directory
+    ///   copying.
+    pub fn push_config(&self, path: &PathBuf) -> Result<CopyReport> {
+        let from_dotconfigs_path = fix_path!(path);
+
+        // If dotconfigs_path doesn't exist, then return
+        if !from_dotconfigs_path.exists()
+            && self.tracked_link_target(&from_dotconfigs_path).is_none()
+        {
+            return Err(anyhow::anyhow!(
+                "{:#?} does not exist!",
+                from_dotconfigs_path
+            ));
+        }
+
+        if self.is_glob() {
+            if options::get().dry_run {
+                println!(
+                    "Would copy {:#?} -> {:#?}",
+                    from_dotconfigs_path,
+                    self.primary_path()
+                );
+                return Ok(CopyReport::default());
+            }
+            return self.push_glob_matches(&from_dotconfigs_path);
+        }
+
+        let mut report = CopyReport::default();
+        for to_config_path in self.destinations() {
+            if options::get().dry_run {
+                println!(
+                    "Would {} {:#?} -> {:#?}",
+                    if self.is_symlinked() { "link" } else { "copy" },
+                    from_dotconfigs_path,
+                    fix_path!(to_config_path)
+                );
+                continue;
+            }
+            report.merge(
+                self.push_to_config_path(&from_dotconfigs_path, &fix_path!(to_config_path))?,
+            );
+        }
+
+        Ok(report)
+    }
+
+    /// Push the configuration from the dotconfig directory to a single
+    /// destination of the config.
+    fn push_to_config_path(
+        &self,
+        from_dotconfigs_path: &Path,
+        to_config_path: &PathBuf,
+    ) -> Result<CopyReport> {
+        // A tracked symlink is pushed as a symlink
+        if let Some(target) = self.tracked_link_target(from_dotconfigs_path) {
+            utils::replace_with_symlink(target, to_config_path)?;
+            self.verify_after_copy(from_dotconfigs_path, to_config_path)?;
+            return Ok(CopyReport::default());
+        }
+
+        if self.is_symlinked() {
+            return self.link_to_config_path(from_dotconfigs_path, to_config_path);
+        }
+
+        self.check_push_types(from_dotconfigs_path, to_config_path)?;
+
+        // Read-only destinations are only overwritten when explicitly allowed
+        let readonly = utils::readonly_entries(to_config_path);
+        let saved_permissions = if readonly.is_empty() {
+            Vec::new()
+        } else if options::get().make_writable {
+            utils::make_writable(&readonly)?
+        } else {
+            return Err(anyhow::anyhow!(
+                "Cannot push {:#?}: {:#?} is read-only, rerun with --make-writable to overwrite it",
+                self.name,
+                readonly[0]
+            ));
+        };
+
+        let result = self.copy_to_config_path(from_dotconfigs_path, to_config_path);
+
+        utils::restore_permissions(saved_permissions);
+
+        let report = result?;
+        self.verify_after_copy(from_dotconfigs_path, to_config_path)?;
+        self.restore_ownership(to_config_path)?;
+
+        Ok(report)
+    }
+
+    /// Symlink a destination of the config to its copy inside the dotconfigs
+    /// directory.
+    ///
+    /// A destination already linked to the copy is left as is. An existing
+    /// file or directory is moved to a `.sync-dotfiles.bak` backup next to
+    /// it first, and the push fails if that backup already exists.
+    fn link_to_config_path(
+        &self,
+        from_dotconfigs_path: &Path,
+        to_config_path: &Path,
+    ) -> Result<CopyReport> {
+        let mut report = CopyReport::default();
+        let source = fs::canonicalize(from_dotconfigs_path)?;
+
+        if fs::read_link(to_config_path).is_ok_and(|target| target == source) {
+            report.files_unchanged += 1;
+            return Ok(report);
+        }
+
+        let is_real_path = fs::symlink_metadata(to_config_path).is_ok_and(|m| !m.is_symlink());
+        if is_real_path {
+            let Some(file_name) = to_config_path.file_name() else {
+                return Err(anyhow::anyhow!(
+                    "Invalid config path: {:#?}",
+                    to_config_path
+                ));
+            };
+            let backup_path = to_config_path
+                .with_file_name(format!("{}.sync-dotfiles.bak", file_name.to_string_lossy()));
+            if fs::symlink_metadata(&backup_path).is_ok() {
+                return Err(anyhow::anyhow!(
+                    "Cannot link {:#?}: {:#?} already exists and so does its backup {:#?}",
+                    self.name,
+                    to_config_path,
+                    backup_path
+                ));
+            }
+
+            fs::rename(to_config_path, &backup_path)?;
+            info!("Moved {:#?} to {:#?}", to_config_path, backup_path);
+        }
+
+        utils::replace_with_symlink(&source, to_config_path)?;
+        report.add_file(0);
+
+        Ok(report)
+    }
+
+    /// Check that the type of the config matches the types of the pushed
+    /// path and its destination.
     ///
-    /// - This method determines whether to copy a file or a directory based on
-    /// the `conf_type` field.
-    /// - It relies on the `copy_config_directory` method for directory
-    /// copying.
-    pub fn push_config(&self, path: &PathBuf) -> Result<()> {
-        let from_dotconfigs_path = fix_path!(path);
-        let to_config_path = fix_path!(&self.path);
+    /// A file can't be pushed onto a directory and vice versa, so a mismatch
+    /// between the `conf_type` of the config, its copy in the dotconfigs
+    /// directory and its existing destination is reported as an error,
+    /// instead of the OS error of the failed copy.
+    fn check_push_types(&self, from_dotconfigs_path: &Path, to_config_path: &Path) -> Result<()> {
+        fn kind(is_dir: bool) -> &'static str {
+            if is_dir {
+                "directory"
+            } else {
+                "file"
+            }
+        }
 
-        // If dotconfigs_path doesn't exist, then return
-        if !from_dotconfigs_path.exists() {
+        // A compressed directory is stored as an archive file
+        let (source_is_dir, expected) = match &self.conf_type {
+            Some(conf_type) if self.is_compressed() => (false, Some(conf_type.is_dir())),
+            Some(conf_type) => (conf_type.is_dir(), Some(conf_type.is_dir())),
+            None => (from_dotconfigs_path.is_dir(), None),
+        };
+
+        if expected.is_some() && from_dotconfigs_path.is_dir() != source_is_dir {
             return Err(anyhow::anyhow!(
-                "{:#?} does not exist!",
-                from_dotconfigs_path
+                "Cannot push {:#?}: it is a {}, but {:#?} in the dotconfigs directory is a {}. \
+                 Fix its conf_type, or clear the metadata of the configs with `clear-metadata` and pull it again",
+                self.name,
+                kind(source_is_dir),
+                from_dotconfigs_path,
+                kind(from_dotconfigs_path.is_dir())
+            ));
+        }
+
+        let is_dir = expected.unwrap_or(source_is_dir);
+        let destination_exists = fs::symlink_metadata(to_config_path).is_ok();
+        if destination_exists && to_config_path.is_dir() != is_dir {
+            return Err(anyhow::anyhow!(
+                "Cannot push {:#?}: it is a {}, but its destination {:#?} is a {}. \
+                 Remove the destination, or fix the path of the config",
+                self.name,
+                kind(is_dir),
+                to_config_path,
+                kind(to_config_path.is_dir())
             ));
         }
 
+        Ok(())
+    }
+
+    /// Apply the `owner` and `group` of the config to the pushed path.
+    ///
+    /// Changing the ownership requires root privileges, so without them a
+    /// warning is printed and the ownership is left unchanged.
+    fn restore_ownership(&self, to_config_path: &Path) -> Result<()> {
+        if self.owner.is_none() && self.group.is_none() {
+            return Ok(());
+        }
+
+        if !utils::is_privileged() {
+            eprintln!(
+                "Warning: Skipping the ownership of {:#?}, changing it requires root privileges",
+                self.name
+            );
+            return Ok(());
+        }
+
+        utils::chown_recursive(to_config_path, self.owner.as_deref(), self.group.as_deref())
+    }
+
+    /// Copies the configuration from the dotconfig directory to its
+    /// destination.
+    ///
+    /// This function is used by the push_config function to perform the
+    /// actual copy operation, based on the `conf_type` field or the type of
+    /// the destination path if the `conf_type` is not set.
+    fn copy_to_config_path(
+        &self,
+        from_dotconfigs_path: &Path,
+        to_config_path: &PathBuf,
+    ) -> Result<CopyReport> {
+        let mut report = CopyReport::default();
+
         // If the to_config_path is a file, then just copy it
         if let Some(conf_type) = &self.conf_type {
             if conf_type.is_file() {
-                if let Err(e) = fs::copy(&from_dotconfigs_path, &to_config_path) {
-                    match e.kind() {
+                match fs::copy(from_dotconfigs_path, to_config_path) {
+                    Ok(bytes) => report.add_file(bytes),
+                    Err(e) => match e.kind() {
                         io::ErrorKind::PermissionDenied => {
-                            escape_privilege().expect("Failed to escape privilege");
-                            fs::copy(&from_dotconfigs_path, &to_config_path)
-                                .expect("Failed to copy");
+                            return Err(anyhow::anyhow!(
+                                "Cannot push {:#?}: {:#?} is not writable, rerun with --make-writable to overwrite it",
+                                self.name,
+                                to_config_path
+                            ))
                         }
                         _ => {
                             return Err(anyhow::anyhow!(
@@ -817,17 +3114,24 @@ impl Config {
                                 e
                             ))
                         }
-                    }
+                    },
                 }
+            } else if self.is_compressed() {
+                utils::extract_archive(from_dotconfigs_path, to_config_path)?;
+                report = copied_files_report(to_config_path)?;
             } else if conf_type.is_dir() {
-                Self::copy_config_directory(&to_config_path, &from_dotconfigs_path).unwrap()
+                report = Self::copy_config_directory(
+                    to_config_path,
+                    from_dotconfigs_path,
+                    &self.exclusions(),
+                )?;
             } else {
                 return Err(anyhow::anyhow!("Invalid config type!"));
             }
         } else {
             // check if the to_config_path is a file
             if to_config_path.is_file() {
-                fs::copy(&from_dotconfigs_path, &to_config_path)
+                let bytes = fs::copy(from_dotconfigs_path, to_config_path)
                     .map_err(|e| {
                         eprintln!(
                             "Failed to copy config: {} to {}: {:#?}",
@@ -837,24 +3141,168 @@ impl Config {
                         )
                     })
                     .unwrap();
+                report.add_file(bytes);
             } else if to_config_path.is_dir() {
-                Self::copy_config_directory(&to_config_path, &from_dotconfigs_path)
-                    .map_err(|e| {
-                        eprintln!(
-                            "Failed to copy config dir: {} to {}: {:#?}",
-                            from_dotconfigs_path.display(),
-                            to_config_path.display(),
-                            e
-                        )
-                    })
-                    .unwrap();
+                report = Self::copy_config_directory(
+                    to_config_path,
+                    from_dotconfigs_path,
+                    &self.exclusions(),
+                )
+                .map_err(|e| {
+                    eprintln!(
+                        "Failed to copy config dir: {} to {}: {:#?}",
+                        from_dotconfigs_path.display(),
+                        to_config_path.display(),
+                        e
+                    )
+                })
+                .unwrap();
             } else {
                 return Err(anyhow::anyhow!("Invalid config path!"));
             }
         }
 
-        Ok(())
+        Ok(report)
+    }
+}
+
+/// Characters that can't be used in config names: path separators and
+/// the characters reserved on Windows.
+const RESERVED_NAME_CHARS: [char; 9] = ['/', '\\', '<', '>', ':', '"', '|', '?', '*'];
+
+/// Device names reserved on Windows, besides `COM1`-`COM9` and
+/// `LPT1`-`LPT9`.
+const RESERVED_NAMES: [&str; 4] = ["CON", "PRN", "AUX", "NUL"];
+
+/// Build the report of the files written to a pushed directory, see
+/// `CopyReport`.
+fn copied_files_report(path: &Path) -> Result<CopyReport> {
+    let mut report = CopyReport::default();
+    for file in hasher::list_dir_files(path)? {
+        report.add_file(fs::metadata(file)?.len());
+    }
+
+    Ok(report)
+}
+
+/// Check if two paths resolve to the same file or directory, e.g. a local
+/// config symlinked to its copy inside the dotconfigs directory.
+fn is_same_path(path: &Path, other: &Path) -> bool {
+    match (fs::canonicalize(path), fs::canonicalize(other)) {
+        (Ok(path), Ok(other)) => path == other,
+        _ => false,
+    }
+}
+
+/// Check if a path contains a glob wildcard (`*`, `?` or `[`).
+fn is_glob_pattern(path: &str) -> bool {
+    path.contains(['*', '?', '['])
+}
+
+/// Check if the suffix of a reserved `COM` or `LPT` name is a port number.
+fn is_port_number(suffix: &str) -> bool {
+    matches!(suffix, "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9")
+}
+
+/// Normalize a relative path to `/` separators without empty or `.`
+/// components, e.g. `.\\nvim\\lazy-lock.json` to `nvim/lazy-lock.json`.
+fn normalize_relative_path(path: &str) -> String {
+    path.split(['/', '\\'])
+        .filter(|component| !component.is_empty() && *component != ".")
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
+/// Paths excluded from syncing a directory config, see
+/// `Config::is_excluded`.
+#[derive(Default)]
+struct Exclusions {
+    /// The normalized `exclude_exact` paths.
+    exact: HashSet<String>,
+    /// The compiled `ignore` patterns.
+    patterns: Vec<glob::Pattern>,
+}
+
+impl Exclusions {
+    /// Check if nothing is excluded.
+    fn is_empty(&self) -> bool {
+        self.exact.is_empty() && self.patterns.is_empty()
+    }
+}
+
+/// Check if a relative path, or one of its parent directories, is one of
+/// the normalized excluded paths or matches one of the ignore patterns.
+fn is_excluded_by(exclusions: &Exclusions, relative_path: &Path) -> bool {
+    !exclusions.exact.is_empty()
+        && relative_path.ancestors().any(|path| {
+            exclusions
+                .exact
+                .contains(&normalize_relative_path(&path.to_string_lossy()))
+        })
+        || matches_patterns(&exclusions.patterns, relative_path)
+}
+
+/// Check if a relative path, or one of its parent directories, matches one
+/// of the given patterns (e.g. the `hash_exclude` patterns).
+fn matches_patterns(patterns: &[glob::Pattern], relative_path: &Path) -> bool {
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..Default::default()
+    };
+
+    !patterns.is_empty()
+        && relative_path
+            .ancestors()
+            .map(|path| normalize_relative_path(&path.to_string_lossy()))
+            .filter(|path| !path.is_empty())
+            .any(|path| {
+                patterns
+                    .iter()
+                    .any(|pattern| pattern.matches_with(&path, options))
+            })
+}
+
+/// Copy the excluded paths of a directory config into the directory
+/// replacing it on push, so they are kept.
+fn keep_excluded_paths(
+    config_path: &Path,
+    staging_path: &Path,
+    exclusions: &Exclusions,
+) -> Result<()> {
+    let mut kept = exclusions.exact.iter().cloned().collect::<Vec<String>>();
+
+    // Keep the topmost entries matching the ignore patterns, along with
+    // their contents
+    if !exclusions.patterns.is_empty() && config_path.is_dir() {
+        let mut entries = WalkDir::new(config_path)
+            .min_depth(1)
+            .follow_links(false)
+            .into_iter();
+        while let Some(entry) = entries.next() {
+            let entry = entry?;
+            let relative_path = entry.path().strip_prefix(config_path)?;
+            if matches_patterns(&exclusions.patterns, relative_path) {
+                kept.push(normalize_relative_path(&relative_path.to_string_lossy()));
+                if entry.file_type().is_dir() {
+                    entries.skip_current_dir();
+                }
+            }
+        }
+    }
+
+    for excluded in kept {
+        let (from, to) = (config_path.join(&excluded), staging_path.join(&excluded));
+        if from.is_dir() {
+            utils::copy_dir(&from, &to)?;
+        } else if from.is_file() {
+            if let Some(parent) = to.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&from, &to)?;
+        }
     }
+
+    Ok(())
 }
 
 /// Implements the Display trait for the Config struct.
@@ -887,13 +3335,782 @@ impl fmt::Display for Config {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{{ ")?;
         write!(f, "name: {}, ", self.name)?;
-        write!(f, "path: {}, ", self.path)?;
+        if let Some(paths) = &self.paths {
+            write!(f, "paths: {paths:?}, ")?;
+        } else {
+            write!(f, "path: {}, ", self.path)?;
+        }
 
         if let Some(conf_type) = &self.conf_type {
             write!(f, "conf_type: {conf_type:?} ")?;
         } else {
             write!(f, "conf_type: None ")?;
         }
+
+        if let Some(repo_path) = &self.repo_path {
+            write!(f, "repo_path: {repo_path} ")?;
+        }
+
+        if let Some(compress) = &self.compress {
+            write!(f, "compress: {compress} ")?;
+        }
+
+        if let Some(sync_mode) = &self.sync_mode {
+            write!(f, "sync_mode: {sync_mode:?} ")?;
+        }
+
+        if let Some(exclude_exact) = &self.exclude_exact {
+            write!(f, "exclude_exact: {exclude_exact:?} ")?;
+        }
+
+        if let Some(ignore) = &self.ignore {
+            write!(f, "ignore: {ignore:?} ")?;
+        }
+
+        if let Some(hash_exclude) = &self.hash_exclude {
+            write!(f, "hash_exclude: {hash_exclude:?} ")?;
+        }
+
+        if let Some(hash_link_target) = &self.hash_link_target {
+            write!(f, "hash_link_target: {hash_link_target} ")?;
+        }
+        if let Some(trim_trailing_newline) = &self.trim_trailing_newline {
+            write!(f, "trim_trailing_newline: {trim_trailing_newline} ")?;
+        }
+
+        if let Some(owner) = &self.owner {
+            write!(f, "owner: {owner} ")?;
+        }
+
+        if let Some(group) = &self.group {
+            write!(f, "group: {group} ")?;
+        }
+
+        if let Some(os) = &self.os {
+            write!(f, "os: {os:?} ")?;
+        }
+
+        if let Some(note) = &self.note {
+            write!(f, "note: {note:?} ")?;
+        }
+
+        if let Some(description) = &self.description {
+            write!(f, "description: {description:?} ")?;
+        }
         write!(f, "}}")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn example_config(hash: Option<String>) -> Config {
+        Config::new(
+            String::from("config.ron"),
+            format!("{}/examples/config.ron", env!("CARGO_MANIFEST_DIR")),
+            hash,
+            Some(ConfType::File),
+        )
+    }
+
+    #[test]
+    fn test_mixed_hash_formats() {
+        let digest = example_config(None)
+            .metadata_digest()
+            .expect("Failed to get metadata digest");
+
+        // Prefixed and legacy unprefixed SHA-1 hashes are both up-to-date
+        let prefixed = example_config(Some(format!("sha1:{digest}")));
+        let unprefixed = example_config(Some(digest.clone()));
+        assert!(!prefixed.check_update_metadata_required().unwrap());
+        assert!(!unprefixed.check_update_metadata_required().unwrap());
+
+        // A hash of another algorithm is never compared and requires a rehash
+        let other_algorithm = example_config(Some(format!("blake3:{digest}")));
+        assert!(other_algorithm.check_update_metadata_required().unwrap());
+
+        // Rehashing stores the prefixed hash
+        let mut config = other_algorithm;
+        config
+            .update_config_hash()
+            .expect("Failed to update config hash");
+        assert_eq!(config.hash, Some(format!("sha1:{digest}")));
+    }
+
+    #[test]
+    fn test_push_to_multiple_paths() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-paths");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("work")).unwrap();
+        fs::create_dir_all(path.join("oss")).unwrap();
+        fs::write(path.join(".editorconfig"), "root = true").unwrap();
+
+        let config: Config = ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(&format!(
+            r#"(name: "editorconfig", paths: ["{0}/work/.editorconfig", "{0}/oss/.editorconfig"], conf_type: File)"#,
+            path.display()
+        ))
+        .expect("Failed to parse config with multiple paths");
+        config.validate_paths().expect("Failed to validate paths");
+
+        let report = config
+            .push_config(&path.join(".editorconfig"))
+            .expect("Failed to push config");
+        assert_eq!(report.files_copied, 2);
+        assert_eq!(report.bytes, 2 * "root = true".len() as u64);
+
+        for dest in ["work", "oss"] {
+            let pushed = fs::read_to_string(path.join(dest).join(".editorconfig")).unwrap();
+            assert_eq!(pushed, "root = true");
+        }
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_permissions_preserved() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-permissions");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("dotconfigs/tools/private")).unwrap();
+        fs::create_dir_all(path.join("dotconfigs/tools/bin")).unwrap();
+        fs::write(path.join("dotconfigs/tools/private/token"), "secret").unwrap();
+        fs::write(path.join("dotconfigs/tools/bin/run.sh"), "#!/bin/sh").unwrap();
+
+        let set_mode = |relative_path: &str, mode: u32| {
+            fs::set_permissions(path.join(relative_path), fs::Permissions::from_mode(mode))
+                .unwrap();
+        };
+        let mode = |relative_path: &str| {
+            fs::metadata(path.join(relative_path))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777
+        };
+        set_mode("dotconfigs/tools/private", 0o700);
+        set_mode("dotconfigs/tools/bin/run.sh", 0o755);
+
+        let config = Config::new_dir("tools", path.join("local/tools").display().to_string());
+        config
+            .push_config(&path.join("dotconfigs/tools"))
+            .expect("Failed to push config");
+        assert_eq!(mode("local/tools/private"), 0o700);
+        assert_eq!(mode("local/tools/bin/run.sh"), 0o755);
+
+        fs::remove_dir_all(path.join("dotconfigs")).unwrap();
+        config
+            .pull_config(&path.join("dotconfigs").display().to_string())
+            .expect("Failed to pull config");
+        assert_eq!(mode("dotconfigs/tools/private"), 0o700);
+        assert_eq!(mode("dotconfigs/tools/bin/run.sh"), 0o755);
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_symlinks_recreated() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-symlinks");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("local/shell/themes")).unwrap();
+        fs::write(path.join("local/shell/themes/dark.sh"), "dark").unwrap();
+        std::os::unix::fs::symlink("themes/dark.sh", path.join("local/shell/theme.sh")).unwrap();
+        std::os::unix::fs::symlink("..", path.join("local/shell/themes/parent")).unwrap();
+
+        let config = Config::new_dir("shell", path.join("local/shell").display().to_string());
+        config
+            .pull_config(&path.join("dotconfigs").display().to_string())
+            .expect("Failed to pull config");
+        assert_eq!(
+            fs::read_link(path.join("dotconfigs/shell/theme.sh")).unwrap(),
+            PathBuf::from("themes/dark.sh")
+        );
+        assert_eq!(
+            fs::read_link(path.join("dotconfigs/shell/themes/parent")).unwrap(),
+            PathBuf::from("..")
+        );
+
+        fs::remove_dir_all(path.join("local")).unwrap();
+        config
+            .push_config(&path.join("dotconfigs/shell"))
+            .expect("Failed to push config");
+        assert_eq!(
+            fs::read_link(path.join("local/shell/theme.sh")).unwrap(),
+            PathBuf::from("themes/dark.sh")
+        );
+        assert_eq!(
+            fs::read_to_string(path.join("local/shell/theme.sh")).unwrap(),
+            "dark"
+        );
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_push_readonly_file() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-push-readonly");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("dotconfigs")).unwrap();
+        fs::create_dir_all(path.join("local")).unwrap();
+        fs::write(path.join("dotconfigs/.vimrc"), "set nu").unwrap();
+        fs::write(path.join("local/.vimrc"), "set nonu").unwrap();
+        fs::set_permissions(path.join("local/.vimrc"), fs::Permissions::from_mode(0o444)).unwrap();
+
+        let config = Config::new_file("vimrc", path.join("local/.vimrc").display().to_string());
+
+        let err = config
+            .push_config(&path.join("dotconfigs/.vimrc"))
+            .unwrap_err();
+        assert!(err.to_string().contains("--make-writable"));
+        assert_eq!(
+            fs::read_to_string(path.join("local/.vimrc")).unwrap(),
+            "set nonu"
+        );
+
+        options::set(options::SyncOptions {
+            make_writable: true,
+            ..Default::default()
+        });
+        let result = config.push_config(&path.join("dotconfigs/.vimrc"));
+        options::set(options::SyncOptions::default());
+        result.expect("Failed to push config");
+
+        // The file is overwritten and stays read-only
+        assert_eq!(
+            fs::read_to_string(path.join("local/.vimrc")).unwrap(),
+            "set nu"
+        );
+        let mode = fs::metadata(path.join("local/.vimrc"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o444);
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_symlink_sync_mode() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-sync-mode");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("dotconfigs/app")).unwrap();
+        fs::create_dir_all(path.join("local")).unwrap();
+        fs::write(path.join("dotconfigs/app/config.toml"), "stored").unwrap();
+        fs::write(path.join("local/config.toml"), "local").unwrap();
+
+        let mut config =
+            Config::new_file("app", path.join("local/config.toml").display().to_string());
+        config.sync_mode = Some(SyncMode::Symlink);
+        let repo_copy = path.join("dotconfigs/app/config.toml");
+
+        assert!(config.needs_link(&repo_copy));
+
+        // The existing file is backed up and replaced by a link
+        let report = config
+            .push_config(&repo_copy)
+            .expect("Failed to push config");
+        assert_eq!(report.files_copied, 1);
+        assert_eq!(
+            fs::read_link(path.join("local/config.toml")).unwrap(),
+            fs::canonicalize(&repo_copy).unwrap()
+        );
+        assert_eq!(
+            fs::read_to_string(path.join("local/config.toml.sync-dotfiles.bak")).unwrap(),
+            "local"
+        );
+
+        // Edits of the local path go straight to the dotconfigs directory
+        fs::write(path.join("local/config.toml"), "edited").unwrap();
+        assert_eq!(fs::read_to_string(&repo_copy).unwrap(), "edited");
+
+        assert!(!config.needs_link(&repo_copy));
+
+        // An existing correct link is left as is on push and pull
+        let report = config
+            .push_config(&repo_copy)
+            .expect("Failed to push config");
+        assert_eq!((report.files_copied, report.files_unchanged), (0, 1));
+        let report = config
+            .pull_config(&path.join("dotconfigs").display().to_string())
+            .expect("Failed to pull config");
+        assert_eq!((report.files_copied, report.files_unchanged), (0, 1));
+        assert_eq!(fs::read_to_string(&repo_copy).unwrap(), "edited");
+
+        // A second backup would overwrite the first one
+        fs::remove_file(path.join("local/config.toml")).unwrap();
+        fs::write(path.join("local/config.toml"), "local").unwrap();
+        assert!(config.push_config(&repo_copy).is_err());
+        assert!(!fs::symlink_metadata(path.join("local/config.toml"))
+            .unwrap()
+            .is_symlink());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_compressed_config_git_entries() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-compress-git");
+        let _ = fs::remove_dir_all(&path);
+        let local = path.join("foo.github-cfg/nvim");
+        fs::create_dir_all(local.join(".git")).unwrap();
+        fs::create_dir_all(local.join(".github")).unwrap();
+        fs::write(local.join("init.lua"), "vim.o.number = true").unwrap();
+        fs::write(local.join(".gitconfig"), "[user]").unwrap();
+        fs::write(local.join(".github/ci.yml"), "on: push").unwrap();
+        fs::write(local.join(".git/HEAD"), "ref: refs/heads/main").unwrap();
+
+        let mut config = Config::new_dir("nvim", local.display().to_string());
+        config.compress = Some(true);
+        let dotconfigs_path = path.join("dotconfigs");
+        config
+            .pull_config(&dotconfigs_path.display().to_string())
+            .expect("Failed to pull config");
+
+        // Only the git directory is left out of the archive
+        fs::remove_dir_all(&local).unwrap();
+        let repo_config_path = config.repo_path_in(&dotconfigs_path).unwrap();
+        config
+            .push_config(&repo_config_path)
+            .expect("Failed to push config");
+        assert!(local.join("init.lua").exists());
+        assert!(local.join(".gitconfig").exists());
+        assert!(local.join(".github/ci.yml").exists());
+        assert!(!local.join(".git").exists());
+        assert_eq!(
+            config.repo_digest(&repo_config_path).unwrap(),
+            Some(config.metadata_digest().unwrap())
+        );
+
+        // Uncompressed copies leave out the same entries
+        fs::create_dir_all(local.join(".git")).unwrap();
+        fs::write(local.join(".git/HEAD"), "ref: refs/heads/main").unwrap();
+        config.compress = None;
+        let plain_path = path.join("plain");
+        config
+            .pull_config(&plain_path.display().to_string())
+            .expect("Failed to pull config");
+        assert!(plain_path.join("nvim/init.lua").exists());
+        assert!(plain_path.join("nvim/.gitconfig").exists());
+        assert!(plain_path.join("nvim/.github/ci.yml").exists());
+        assert!(!plain_path.join("nvim/.git").exists());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_large_files_stored_as_stubs() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-stubs");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("local/wallpapers")).unwrap();
+        fs::write(path.join("local/wallpapers/small.txt"), "small").unwrap();
+        fs::write(path.join("local/wallpapers/large.png"), vec![7u8; 2048]).unwrap();
+
+        let mut config = Config::new_dir(
+            "wallpapers",
+            path.join("local/wallpapers").display().to_string(),
+        );
+        config.lfs_threshold = Some(1024);
+
+        let dotconfigs_path = path.join("dotconfigs").display().to_string();
+        let report = config
+            .pull_config(&dotconfigs_path)
+            .expect("Failed to pull config");
+        assert_eq!(report.files_copied, 1);
+        assert_eq!(
+            report.skipped,
+            vec![path.join("local/wallpapers/large.png")]
+        );
+
+        // Only the large file is stubbed, and the stubbed copy still matches
+        let repo_path = path.join("dotconfigs/wallpapers");
+        assert!(Stub::read(&repo_path.join("large.png")).unwrap().is_some());
+        assert!(Stub::read(&repo_path.join("small.txt")).unwrap().is_none());
+        assert_eq!(
+            config.repo_digest(&repo_path).unwrap(),
+            Some(config.metadata_digest().unwrap())
+        );
+
+        // The stub is replaced with the original file on push
+        config
+            .push_config(&repo_path)
+            .expect("Failed to push config");
+        let large = fs::read(path.join("local/wallpapers/large.png")).unwrap();
+        assert_eq!(large, vec![7u8; 2048]);
+
+        // Without the original file, the stub can't be pushed
+        fs::remove_file(path.join("local/wallpapers/large.png")).unwrap();
+        assert!(config.push_config(&repo_path).is_err());
+        assert!(path.join("local/wallpapers/small.txt").exists());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_pull_removes_deleted_files() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-stale-files");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("local/nvim/lua")).unwrap();
+        fs::write(path.join("local/nvim/init.lua"), "init").unwrap();
+        fs::write(path.join("local/nvim/lua/plugins.lua"), "plugins").unwrap();
+
+        let config = Config::new_dir("nvim", path.join("local/nvim").display().to_string());
+        let dotconfigs_path = path.join("dotconfigs").display().to_string();
+        let report = config
+            .pull_config(&dotconfigs_path)
+            .expect("Failed to pull config");
+        assert_eq!(report.files_copied, 2);
+        assert_eq!(report.bytes, 11);
+
+        let repo_path = path.join("dotconfigs/nvim");
+        fs::create_dir_all(repo_path.join(".git")).unwrap();
+        fs::write(repo_path.join(".git/HEAD"), "").unwrap();
+        assert!(repo_path.join("lua/plugins.lua").exists());
+
+        // Deleted files are removed from the repo, git entries are kept
+        fs::remove_dir_all(path.join("local/nvim/lua")).unwrap();
+        config
+            .pull_config(&dotconfigs_path)
+            .expect("Failed to pull config");
+
+        assert!(!repo_path.join("lua").exists());
+        assert!(repo_path.join("init.lua").exists());
+        assert!(repo_path.join(".git/HEAD").exists());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_exclude_exact_paths() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-exclude-exact");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("local/nvim/spell")).unwrap();
+        fs::write(path.join("local/nvim/init.lua"), "init").unwrap();
+        fs::write(path.join("local/nvim/lazy-lock.json"), "{}").unwrap();
+        fs::write(path.join("local/nvim/spell/en.add"), "nvim").unwrap();
+
+        let mut config = Config::new_dir("nvim", path.join("local/nvim").display().to_string());
+        let dotconfigs_path = path.join("dotconfigs").display().to_string();
+        config
+            .pull_config(&dotconfigs_path)
+            .expect("Failed to pull config");
+
+        // Excluded paths are removed from the repo on the next pull
+        config.exclude_exact = Some(vec![
+            String::from("./lazy-lock.json"),
+            String::from("spell\\"),
+        ]);
+        let report = config
+            .pull_config(&dotconfigs_path)
+            .expect("Failed to pull config");
+        assert_eq!((report.files_copied, report.files_unchanged), (0, 1));
+
+        let repo_path = path.join("dotconfigs/nvim");
+        assert!(repo_path.join("init.lua").exists());
+        assert!(!repo_path.join("lazy-lock.json").exists());
+        assert!(!repo_path.join("spell").exists());
+
+        // Changes of excluded files don't change the hash
+        let digest = config.metadata_digest().unwrap();
+        fs::write(path.join("local/nvim/lazy-lock.json"), "{ }").unwrap();
+        assert_eq!(config.metadata_digest().unwrap(), digest);
+        assert_eq!(config.repo_digest(&repo_path).unwrap(), Some(digest));
+
+        // Excluded paths are kept on push
+        fs::write(repo_path.join("init.lua"), "pushed").unwrap();
+        config
+            .push_config(&repo_path)
+            .expect("Failed to push config");
+        assert_eq!(
+            fs::read_to_string(path.join("local/nvim/init.lua")).unwrap(),
+            "pushed"
+        );
+        assert!(path.join("local/nvim/lazy-lock.json").exists());
+        assert!(path.join("local/nvim/spell/en.add").exists());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_ignore_patterns() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-ignore");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("local/app/target/debug")).unwrap();
+        fs::create_dir_all(path.join("local/app/src")).unwrap();
+        fs::write(path.join("local/app/config.toml"), "config").unwrap();
+        fs::write(path.join("local/app/src/main.rs"), "main").unwrap();
+        fs::write(path.join("local/app/src/build.log"), "log").unwrap();
+        fs::write(path.join("local/app/target/debug/app"), "binary").unwrap();
+
+        let mut config = Config::new_dir("app", path.join("local/app").display().to_string());
+        config.ignore = Some(vec![String::from("target"), String::from("**/*.log")]);
+        let dotconfigs_path = path.join("dotconfigs").display().to_string();
+        let report = config
+            .pull_config(&dotconfigs_path)
+            .expect("Failed to pull config");
+        assert_eq!(report.files_copied, 2);
+
+        let repo_path = path.join("dotconfigs/app");
+        assert!(repo_path.join("config.toml").exists());
+        assert!(repo_path.join("src/main.rs").exists());
+        assert!(!repo_path.join("target").exists());
+        assert!(!repo_path.join("src/build.log").exists());
+
+        // Changes of ignored files don't change the hash
+        let digest = config.metadata_digest().unwrap();
+        fs::write(path.join("local/app/target/debug/app"), "rebuilt").unwrap();
+        assert_eq!(config.metadata_digest().unwrap(), digest);
+        assert_eq!(config.repo_digest(&repo_path).unwrap(), Some(digest));
+
+        // Ignored paths are kept on push
+        fs::write(repo_path.join("config.toml"), "pushed").unwrap();
+        config
+            .push_config(&repo_path)
+            .expect("Failed to push config");
+        assert_eq!(
+            fs::read_to_string(path.join("local/app/config.toml")).unwrap(),
+            "pushed"
+        );
+        assert_eq!(
+            fs::read_to_string(path.join("local/app/target/debug/app")).unwrap(),
+            "rebuilt"
+        );
+        assert!(path.join("local/app/src/build.log").exists());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_pull_skips_identical_files() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-identical-files");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("local/nvim/lua")).unwrap();
+        fs::write(path.join("local/nvim/init.lua"), "init").unwrap();
+        fs::write(path.join("local/nvim/lua/plugins.lua"), "plugins").unwrap();
+
+        let config = Config::new_dir("nvim", path.join("local/nvim").display().to_string());
+        let dotconfigs_path = path.join("dotconfigs").display().to_string();
+        let report = config
+            .pull_config(&dotconfigs_path)
+            .expect("Failed to pull config");
+        assert_eq!((report.files_copied, report.files_unchanged), (2, 0));
+
+        // Mark the copies, so a rewrite would change their modification time
+        let repo_path = path.join("dotconfigs/nvim");
+        let marked = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1);
+        for file in ["init.lua", "lua/plugins.lua"] {
+            fs::File::options()
+                .write(true)
+                .open(repo_path.join(file))
+                .unwrap()
+                .set_modified(marked)
+                .unwrap();
+        }
+
+        fs::write(path.join("local/nvim/init.lua"), "edited").unwrap();
+        let report = config
+            .pull_config(&dotconfigs_path)
+            .expect("Failed to pull config");
+        assert_eq!((report.files_copied, report.files_unchanged), (1, 1));
+
+        let modified = |file: &str| {
+            fs::metadata(repo_path.join(file))
+                .unwrap()
+                .modified()
+                .unwrap()
+        };
+        assert_eq!(modified("lua/plugins.lua"), marked);
+        assert_ne!(modified("init.lua"), marked);
+        assert_eq!(
+            fs::read_to_string(repo_path.join("init.lua")).unwrap(),
+            "edited"
+        );
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_hash_exclude_patterns() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-hash-exclude");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("local/foo/cache")).unwrap();
+        fs::write(path.join("local/foo/foo.toml"), "foo").unwrap();
+        fs::write(path.join("local/foo/version"), "1").unwrap();
+        fs::write(path.join("local/foo/cache/index.bin"), "1").unwrap();
+
+        let mut config = Config::new_dir("foo", path.join("local/foo").display().to_string());
+        config.hash_exclude = Some(vec![String::from("version"), String::from("cache")]);
+        let dotconfigs_path = path.join("dotconfigs").display().to_string();
+        config
+            .pull_config(&dotconfigs_path)
+            .expect("Failed to pull config");
+        config.hash = Some(config.metadata_digest().unwrap());
+
+        // Files left out of the hash are still copied
+        let repo_path = path.join("dotconfigs/foo");
+        assert_eq!(fs::read_to_string(repo_path.join("version")).unwrap(), "1");
+        assert!(repo_path.join("cache/index.bin").exists());
+
+        // Changes of files left out of the hash keep the config clean
+        fs::write(path.join("local/foo/version"), "2").unwrap();
+        fs::write(path.join("local/foo/cache/index.bin"), "2").unwrap();
+        assert_eq!(config.hash, Some(config.metadata_digest().unwrap()));
+        assert_eq!(config.repo_digest(&repo_path).unwrap(), config.hash);
+        for change_detection in [ChangeDetection::ContentHash, ChangeDetection::MetadataStamp] {
+            let digest = config.metadata_digest_with(change_detection).unwrap();
+            fs::write(path.join("local/foo/version"), "3").unwrap();
+            assert_eq!(
+                config.metadata_digest_with(change_detection).unwrap(),
+                digest
+            );
+        }
+
+        // Other changes are still detected
+        fs::write(path.join("local/foo/foo.toml"), "bar").unwrap();
+        assert_ne!(config.hash, Some(config.metadata_digest().unwrap()));
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_push_type_mismatch() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-type-mismatch");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("dotconfigs/nvim")).unwrap();
+        fs::create_dir_all(path.join("local/.vimrc")).unwrap();
+        fs::write(path.join("dotconfigs/.vimrc"), "set nu").unwrap();
+        fs::write(path.join("local/nvim"), "").unwrap();
+
+        // A file config pushed onto a directory
+        let vimrc = Config::new_file("vimrc", path.join("local/.vimrc").display().to_string());
+        let error = vimrc
+            .push_config(&path.join("dotconfigs/.vimrc"))
+            .unwrap_err();
+        assert!(error.to_string().contains("its destination"));
+
+        // A directory config pushed onto a file
+        let nvim = Config::new_dir("nvim", path.join("local/nvim").display().to_string());
+        let error = nvim.push_config(&path.join("dotconfigs/nvim")).unwrap_err();
+        assert!(error.to_string().contains("its destination"));
+
+        // A file config stored as a directory in the dotconfigs directory
+        let nvim = Config::new_file("nvim", path.join("local/new").display().to_string());
+        let error = nvim.push_config(&path.join("dotconfigs/nvim")).unwrap_err();
+        assert!(error.to_string().contains("in the dotconfigs directory"));
+        assert!(!path.join("local/new").exists());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_hash_link_target() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-link-target");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("local")).unwrap();
+        fs::write(path.join("local/light.toml"), "light").unwrap();
+        fs::write(path.join("local/dark.toml"), "dark").unwrap();
+        let link = path.join("local/theme.toml");
+        std::os::unix::fs::symlink("light.toml", &link).unwrap();
+
+        let mut config = Config::new_file("theme", link.display().to_string());
+        config.hash_link_target = Some(true);
+        let digest = config.metadata_digest().unwrap();
+
+        // Only repointing the link changes the hash
+        fs::write(path.join("local/light.toml"), "lighter").unwrap();
+        assert_eq!(config.metadata_digest().unwrap(), digest);
+
+        fs::remove_file(&link).unwrap();
+        std::os::unix::fs::symlink("dark.toml", &link).unwrap();
+        let repointed_digest = config.metadata_digest().unwrap();
+        assert_ne!(repointed_digest, digest);
+
+        // The link is pulled as a link
+        let dotconfigs = path.join("dotconfigs");
+        config
+            .pull_config(&dotconfigs.display().to_string())
+            .expect("Failed to pull the link");
+        let repo_link = config.repo_path_in(&dotconfigs).unwrap();
+        assert_eq!(fs::read_link(&repo_link).unwrap(), Path::new("dark.toml"));
+        assert_eq!(
+            config.repo_digest(&repo_link).unwrap(),
+            Some(repointed_digest)
+        );
+
+        // Repointing the pulled link and pushing it repoints the local link
+        fs::remove_file(&repo_link).unwrap();
+        std::os::unix::fs::symlink("light.toml", &repo_link).unwrap();
+        config
+            .push_config(&repo_link)
+            .expect("Failed to push the link");
+        assert_eq!(fs::read_link(&link).unwrap(), Path::new("light.toml"));
+        assert_eq!(config.metadata_digest().unwrap(), digest);
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_trim_trailing_newline() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-trailing-newline");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("nvim")).unwrap();
+        fs::write(path.join("vimrc"), "set number").unwrap();
+        fs::write(path.join("nvim/init.lua"), "vim.o.number = true").unwrap();
+        fs::write(path.join("nvim/spell.bin"), b"\x00\x01").unwrap();
+
+        let mut file = Config::new_file("vimrc", path.join("vimrc").display().to_string());
+        let mut dir = Config::new_dir("nvim", path.join("nvim").display().to_string());
+        file.trim_trailing_newline = Some(true);
+        dir.default_trim_trailing_newline = true;
+        let file_digest = file.metadata_digest().unwrap();
+        let dir_digest = dir.metadata_digest().unwrap();
+
+        // Adding a trailing newline to a text file doesn't change the hash
+        fs::write(path.join("vimrc"), "set number\n").unwrap();
+        fs::write(path.join("nvim/init.lua"), "vim.o.number = true\r\n").unwrap();
+        assert_eq!(file.metadata_digest().unwrap(), file_digest);
+        assert_eq!(dir.metadata_digest().unwrap(), dir_digest);
+
+        // The files are still copied with the newline
+        let dotconfigs = path.join("dotconfigs");
+        file.pull_config(&dotconfigs.display().to_string()).unwrap();
+        let repo_file = file.repo_path_in(&dotconfigs).unwrap();
+        assert_eq!(fs::read_to_string(&repo_file).unwrap(), "set number\n");
+        assert_eq!(
+            file.repo_digest(&repo_file).unwrap(),
+            Some(file_digest.clone())
+        );
+
+        // Binary files are hashed unchanged
+        fs::write(path.join("nvim/spell.bin"), b"\x00\x01\n").unwrap();
+        assert_ne!(dir.metadata_digest().unwrap(), dir_digest);
+
+        // Without the option, the newline is a change
+        file.trim_trailing_newline = None;
+        assert_ne!(file.metadata_digest().unwrap(), file_digest);
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_hash_dir_with_symlink_loop() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-symlink-loop");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("nested")).unwrap();
+        fs::write(path.join("nested/init.lua"), "").unwrap();
+
+        let config = Config::new_dir("nvim", path.display().to_string());
+        let digest = config
+            .metadata_digest()
+            .expect("Failed to get metadata digest");
+
+        // A link back to the config directory is neither followed nor hashed
+        std::os::unix::fs::symlink(&path, path.join("nested/loop")).unwrap();
+        let looped_digest = config
+            .metadata_digest()
+            .expect("Failed to hash the directory containing a symlink loop");
+        assert_eq!(looped_digest, digest);
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+}
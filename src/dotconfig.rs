@@ -1,17 +1,31 @@
 use crate::{
-    config::ConfType,
-    config::Config,
-    fix_path, hasher,
-    utils::{get_ron_formatter, FixPath},
+    config::{ChangeDetection, ConfType, Config, CopyReport},
+    fix_path,
+    hasher::{self, HashAlgorithm},
+    info, options,
+    utils::{self, get_ron_formatter, FileLock, FixPath},
 };
 
 use anyhow::{Context, Result};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use lazy_static::lazy_static;
 use rayon::prelude::*;
 use ron::{extensions::Extensions, ser::to_string_pretty, Options};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
-use std::{fmt, fs, io::Write, path::PathBuf, process, sync::Mutex};
+use similar::TextDiff;
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap},
+    ffi::OsStr,
+    fmt, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process,
+    str::FromStr,
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
 
 /// Struct to store configuration data, including the path to the dotconfig
 /// directory and a list of configuration files.
@@ -20,13 +34,51 @@ use std::{fmt, fs, io::Write, path::PathBuf, process, sync::Mutex};
 /// syncing dotfiles.
 /// It includes the path to the dotconfig directory and a list of individual
 /// `Config` structs, each representing a configuration file.
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
 pub struct DotConfig {
     /// Enum representing the path to the dotconfig directory.
     pub dotconfigs_path: DotconfigPath,
     /// A vector of `Config` structs, each representing an individual
     /// configuration file.
     pub configs: Vec<Config>,
+    /// How changes of the configs are detected (default: `ContentHash`).
+    #[serde(default, skip_serializing_if = "ChangeDetection::is_content_hash")]
+    pub change_detection: ChangeDetection,
+    /// Hash algorithm of the config hashes (default: `Sha1`).
+    #[serde(default, skip_serializing_if = "HashAlgorithm::is_sha1")]
+    pub hash_algorithm: HashAlgorithm,
+    /// Named sets of configs (e.g. "laptop" or "desktop"), mapping the
+    /// profile name to the names of its configs.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Vec<String>>,
+    /// Size in bytes above which the files of directory configs are pulled
+    /// as stubs recording their hash, size and source, instead of their
+    /// contents (default: no limit).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lfs_threshold: Option<u64>,
+    /// Store the configs inside the home directory at their path relative
+    /// to it (e.g. `.config/nvim`), so the dotconfigs directory mirrors the
+    /// layout of the home directory (default: false).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mirror_home: bool,
+    /// Ignore a single trailing newline of text files when hashing the
+    /// configs, unless overridden by a config (default: false).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub trim_trailing_newline: bool,
+    /// Save the hashes and sync times of the configs in a state file of the
+    /// current host (`state/<hostname>.ron` next to the config file) instead
+    /// of the config file, so that a config file shared between machines
+    /// with git doesn't conflict on every sync (default: false).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub host_state: bool,
+    /// Dotconfigs path of the config file, kept while it is overridden from
+    /// the command line so that the override isn't saved.
+    #[serde(skip)]
+    saved_dotconfigs_path: Option<DotconfigPath>,
+    /// Fragments of the config directory the config was merged from, so
+    /// every config is saved back to its fragment.
+    #[serde(skip)]
+    fragments: Option<ConfigFragments>,
 }
 
 /// Enum representing the path to the dotconfig directory.
@@ -42,57 +94,158 @@ pub struct DotConfig {
 /// let dotconfig_github = DotconfigPath::Github("https://github.com/user/repo".to_string());
 /// let dotconfig_local = DotconfigPath::Local(String::from("~/dotfiles"));
 /// ```
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
 pub enum DotconfigPath {
+    /// URL of a GitHub repository containing the dotconfigs.
     Github(String),
+    /// Path to a local dotconfigs directory.
     Local(String),
 }
 
+/// Directories shared between GNU Stow packages (relative to the home
+/// directory), whose entries are imported as separate configs.
+const STOW_SHARED_DIRS: [&str; 5] = [
+    ".config",
+    ".local",
+    ".local/bin",
+    ".local/share",
+    ".local/state",
+];
+
+/// Entries of GNU Stow packages that are not installed into the home
+/// directory.
+const STOW_IGNORED_ENTRIES: [&str; 4] = [".git", ".stow-local-ignore", ".stowrc", ".gitignore"];
+
+/// Well-known dotfiles (name and path relative to the home directory)
+/// looked up by `DotConfig::autodetect`.
+const AUTODETECT_DOTFILES: [(&str, &str); 18] = [
+    ("bashrc", ".bashrc"),
+    ("bash_profile", ".bash_profile"),
+    ("profile", ".profile"),
+    ("zshrc", ".zshrc"),
+    ("zprofile", ".zprofile"),
+    ("inputrc", ".inputrc"),
+    ("vimrc", ".vimrc"),
+    ("gitconfig", ".gitconfig"),
+    ("tmux", ".tmux.conf"),
+    ("nvim", ".config/nvim"),
+    ("git", ".config/git"),
+    ("fish", ".config/fish"),
+    ("tmux-config", ".config/tmux"),
+    ("alacritty", ".config/alacritty"),
+    ("kitty", ".config/kitty"),
+    ("wezterm", ".config/wezterm"),
+    ("i3", ".config/i3"),
+    ("starship", ".config/starship.toml"),
+];
+
 lazy_static! {
     /// Mutex-protected global configuration file path.
     ///
     /// This static variable stores the path to the configuration file and
     /// allows it to be accessed and modified safely from multiple threads.
     static ref CONFIG_PATH: Mutex<PathBuf> = Mutex::new(get_default_config_path());
+
+    /// Mutex-protected cache of the parsed configuration files.
+    ///
+    /// This static variable maps the path of every configuration file parsed
+    /// by `DotConfig::parse_dotconfig_cached` to its modification time, size
+    /// and parsed configuration.
+    static ref PARSED_CONFIGS: Mutex<HashMap<PathBuf, (SystemTime, u64, DotConfig)>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Function to list the locations searched for the configuration file.
+///
+/// The candidates are, in order of precedence: `${HOME}/.sync-dotfiles.ron`,
+/// `${HOME}/.config/sync-dotfiles/config.ron` and `config.ron` in the
+/// current directory, each followed by its gzip compressed version, and
+/// finally the `${HOME}/.config/sync-dotfiles/conf.d` config directory.
+/// The home directory can be overridden with the `--home` option, and only
+/// the current directory is searched if there is no home directory.
+fn config_path_candidates() -> Vec<PathBuf> {
+    let home_dir = utils::home_dir().ok();
+    let home_path = |path: &str| home_dir.as_ref().map(|home_dir| home_dir.join(path));
+
+    [
+        home_path(".sync-dotfiles.ron"),
+        home_path(".config/sync-dotfiles/config.ron"),
+        Some(PathBuf::from("config.ron")),
+    ]
+    .into_iter()
+    .flatten()
+    .flat_map(|path| [path.clone(), gzip_path(&path)])
+    .chain(home_path(".config/sync-dotfiles/conf.d"))
+    .collect()
 }
 
 /// Function to determine the default configuration file path.
 ///
-/// This function determines the default configuration file path based on
-/// the operating system.
-/// If the config file is not found in the ${HOME}/.config/sync-dotfiles
-/// directory, it will try to find the config file in the current directory.
+/// This function returns the first of the `config_path_candidates` that
+/// can be opened.
 /// Otherwise, it will return an empty path.
 fn get_default_config_path() -> PathBuf {
-    let home_dir = PathBuf::from(env!("HOME"));
-    // Try to find the config file in the ${HOME}/.sync-dotfiles.ron
-    let path = home_dir.join(".sync-dotfiles.ron");
-    if fs::File::open(&path).is_ok() {
-        println!(
-            "Found config file: {}/.sync-dotfiles.ron",
-            home_dir.display()
-        );
-        return path;
-    }
-    // Try to find the config file in the ${HOME}/.config/sync-dotfiles directory
-    let path = home_dir.join(".config/sync-dotfiles/config.ron");
-    if fs::File::open(&path).is_ok() {
-        println!("Found config file at {}", path.display());
-        return path;
-    }
+    let Some(path) = config_path_candidates()
+        .into_iter()
+        .find(|path| fs::File::open(path).is_ok())
+    else {
+        // Return an empty path if no config file is found
+        return PathBuf::new();
+    };
 
-    // If the config file is not found in the $HOME/.config/sync-dotfiles directory
-    // Try to find the config file in the current directory
-    let local_config_path = PathBuf::from("config.ron");
-    if fs::File::open(&local_config_path).is_ok() {
-        println!("Found config file in current directory");
-        return local_config_path;
+    info!("Found config file at {}", path.display());
+    path
+}
+
+/// Error returned when the config file to be used doesn't exist, as opposed
+/// to a config file that fails to be read or parsed.
+#[derive(Debug)]
+pub struct ConfigNotFound {
+    /// The locations searched for the config file.
+    pub searched: Vec<PathBuf>,
+    /// The location to create a new config file at: the config path given by
+    /// the user, otherwise `${HOME}/.config/sync-dotfiles/config.ron`.
+    pub path: PathBuf,
+}
+
+impl ConfigNotFound {
+    /// Build the error for the config file given by the user, or for the
+    /// default search locations if none was given.
+    fn new(filepath: Option<&Path>) -> Self {
+        let absolute = |path: PathBuf| std::path::absolute(&path).unwrap_or(path);
+
+        match filepath {
+            Some(path) => ConfigNotFound {
+                searched: vec![absolute(path.to_path_buf())],
+                path: absolute(path.to_path_buf()),
+            },
+            None => ConfigNotFound {
+                searched: config_path_candidates().into_iter().map(absolute).collect(),
+                path: utils::home_dir().map_or_else(
+                    |_| absolute(PathBuf::from("config.ron")),
+                    |home_dir| home_dir.join(".config/sync-dotfiles/config.ron"),
+                ),
+            },
+        }
     }
+}
 
-    // Return an empty path if no config file is found
-    PathBuf::new()
+/// Display implementation for ConfigNotFound, listing every location that
+/// was searched.
+impl fmt::Display for ConfigNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "No config file found, use --config-path to select one. Searched:"
+        )?;
+        self.searched
+            .iter()
+            .try_for_each(|path| write!(f, "\n  {}", path.display()))
+    }
 }
 
+impl std::error::Error for ConfigNotFound {}
+
 impl DotConfig {
     /// Parses the dotconfig file and returns a `DotConfig` structure.
     ///
@@ -114,528 +267,5290 @@ impl DotConfig {
     /// # Arguments
     ///
     /// * `filepath` - An optional reference to a String representing the path
-    /// to the config file specified by the user.
+    ///   to the config file specified by the user.
     ///
     /// # Returns
     ///
     /// A Result containing a `DotConfig` struct if the parsing is successful,
     /// or an error if parsing fails.
     pub fn parse_dotconfig(filepath: &Option<String>) -> Result<Self> {
-        // If the user has specified a config file path
-        if let Some(path) = filepath {
-            *CONFIG_PATH.lock().unwrap() = fix_path!(path);
-        }
-
-        let file = fs::File::open(CONFIG_PATH.lock().unwrap().as_path())
-            .context("Failed to open config file from the current directory")?;
-
-        let config = Options::default()
-            .with_default_extension(Extensions::IMPLICIT_SOME)
-            .from_reader(file)
-            .context("Failed to parse config file")?;
-
-        Ok(config)
+        Self::parse_dotconfig_file(&Self::select_existing_config_path(filepath)?)
     }
 
-    /// Fix the config file path if it is a relative path.
-    /// Also fix the wrong username in the config file path if it is present.
+    /// Parses the dotconfig file like `parse_dotconfig`, reusing the result
+    /// of a previous parse of the same file within the current process.
     ///
-    /// This function adjusts the configuration file paths to make them
-    /// valid and usable.
-    /// It ensures that relative paths are converted to absolute paths
-    /// and handles potential issues related to usernames in file paths.
+    /// The file is parsed again if its modification time or size changed
+    /// since it was cached. This is meant for repeatedly loading the config,
+    /// e.g. in a sync loop, and isn't persisted between runs.
+    ///
+    /// # Arguments
+    ///
+    /// * `filepath` - An optional reference to a String representing the path
+    ///   to the config file specified by the user.
     ///
     /// # Returns
     ///
-    /// A Result indicating success or an error if any path adjustments fail.
-    pub fn fixup_config(&mut self) -> Result<()> {
-        self.configs.iter_mut().for_each(|config| {
-            config.path = fix_path!(&config.path).to_string_lossy().to_string();
-        });
-
-        Ok(())
-    }
-
-    /// Save the current configuration to a local file.
+    /// A Result containing a `DotConfig` struct if the parsing is successful,
+    /// or an error if parsing fails.
     ///
-    /// This method serializes the `DotConfig` structure into a human-readable
-    /// RON (Rust Object Notation) format and writes it to the configuration
-    /// file specified in the `CONFIG_PATH` mutex.
+    /// # Example
     ///
-    /// The configuration file contains information about the dotconfig
-    /// directory and the list of configuration files to sync.
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
     ///
-    /// # Returns
+    /// let path = Some(format!("{}/examples/config.ron", env!("CARGO_MANIFEST_DIR")));
     ///
-    /// A Result indicating success or an error if any file operations fail.
-    pub fn save_configs(&self) -> Result<()> {
-        let ron_pretty = get_ron_formatter();
+    /// let first = DotConfig::parse_dotconfig_cached(&path).expect("Failed to parse config");
+    /// let second = DotConfig::parse_dotconfig_cached(&path).expect("Failed to parse config");
+    /// assert_eq!(first.configs.len(), second.configs.len());
+    /// ```
+    pub fn parse_dotconfig_cached(filepath: &Option<String>) -> Result<Self> {
+        let config_path = Self::select_existing_config_path(filepath)?;
 
-        let config = to_string_pretty(self, ron_pretty).context("Failed to serialize config")?;
+        // Edits of the fragments of a config directory don't change its
+        // modification time
+        if config_path.is_dir() {
+            return Self::parse_dotconfig_file(&config_path);
+        }
 
-        let config_path = CONFIG_PATH.lock().unwrap();
-        println!("Saving config file to {:#?}", config_path.display());
+        let metadata = fs::metadata(&config_path)
+            .context("Failed to open config file from the current directory")?;
+        let modified = metadata.modified()?;
 
-        let mut file =
-            fs::File::create(config_path.as_path()).context("Failed to create config file")?;
+        let mut parsed_configs = PARSED_CONFIGS.lock().unwrap();
+        if let Some((cached_modified, cached_len, config)) = parsed_configs.get(&config_path) {
+            if *cached_modified == modified && *cached_len == metadata.len() {
+                config.update_dotfiles_dir();
+                return Ok(config.clone());
+            }
+        }
 
-        file.write_all(config.as_bytes())
-            .context("Failed to write to config file")?;
+        let config = Self::parse_dotconfig_file(&config_path)?;
+        parsed_configs.insert(config_path, (modified, metadata.len(), config.clone()));
 
-        Ok(())
+        Ok(config)
     }
 
-    /// Pull all configured files based on their metadata.
+    /// Parses a dotconfig file other than the config file in use, e.g. the
+    /// config file of another machine.
     ///
-    /// This method iterates through the list of configured files and checks
-    /// whether each file needs to be updated based on its metadata.
+    /// Unlike `parse_dotconfig`, the file is not selected as the config file
+    /// that is saved, and `$DOTFILES` keeps expanding to the dotconfigs path
+    /// of the config file in use.
     ///
-    /// If a configuration file requires an update, it updates the metadata
-    /// in the config file and replaces the file with the latest version from
-    /// the source specified in the `DotConfig` structure.
+    /// # Arguments
+    ///
+    /// * `path` - The path to the config file.
     ///
     /// # Returns
     ///
-    /// A Result indicating success or an error if any synchronization operations fail.
-    pub fn pull_updated_configs(&mut self) -> Result<()> {
-        // iterate through all the configs
-        self.configs.iter_mut().for_each(|dir| {
-            // check if the config dir exists
-            if !dir.path_exists() {
-                // if the config dir does not exist, exit safely
-                println!("Skipping {:#?} does not exist.", dir.name);
-                return;
-            }
-
-            // check if the config needs to be updated
-            if dir.check_update_metadata_required() {
-                println!("Updating {:#?}.", dir.name);
-
-                // update the metadata in the config file
-                dir.update_metadata().expect("Failed to update config hash");
-
-                if let DotconfigPath::Local(local_dotconfigs_path) = &self.dotconfigs_path {
-                    // Replace the config file with the latest version
-                    dir.pull_config(local_dotconfigs_path)
-                        .expect("Failed to pull config");
-                } else {
-                    println!("Skipping dotconfigs_path does not exist.");
-                }
-            } else {
-                // if the config does not need to be updated, skip the config
-                println!("Skipping {:#?} already up-to date.", dir.name);
-            }
-        });
+    /// A Result containing a `DotConfig` struct if the parsing is successful,
+    /// or an error if parsing fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
+    ///
+    /// let path = format!("{}/examples/config.ron", env!("CARGO_MANIFEST_DIR"));
+    ///
+    /// let dotconfig = DotConfig::from_file(&path).expect("Failed to parse config");
+    /// assert!(!dotconfig.configs.is_empty());
+    /// ```
+    pub fn from_file<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let dotfiles_dir = utils::dotfiles_dir();
+        let config = Self::parse_dotconfig_file(&fix_path!(path.as_ref().to_path_buf()));
+        utils::set_dotfiles_dir(dotfiles_dir);
 
-        Ok(())
+        config
     }
 
-    /// Push Updatable configs back to their local destination in the system
+    /// Get the absolute path of the config file that is used, without
+    /// loading it.
     ///
-    /// The `push_updated_configs` method is responsible for pushing all the
-    /// updated configuration files to their specified destination paths.
+    /// The path is selected the same way as by `parse_dotconfig`: the path
+    /// given by the user, otherwise the first config file found in the search
+    /// locations (which depend on the home directory).
     ///
-    /// It iterates through the list of configured files and, for each
-    /// configuration, checks if it needs to be updated based on its metadata.
-    /// If an update is required, it pushes the updated configuration to its
-    /// specified destination.
+    /// # Arguments
     ///
-    /// This operation is essential when you want to synchronize your local
-    /// configurations with a remote repository or directory specified in the
-    /// `DotConfig` structure's `dotconfigs_path` field.
+    /// * `filepath` - An optional reference to a String representing the path
+    ///   to the config file specified by the user.
     ///
-    /// # Errors
+    /// # Returns
     ///
-    /// This method may return an error if any file operations or the pushing
-    /// process fail. Errors could include issues with file I/O, authentication,
-    /// or network connectivity.
+    /// A Result containing the absolute path of the config file, or an error
+    /// listing the searched locations if no config file is found.
     ///
-    /// # Note
+    /// # Example
     ///
-    /// Before using this method, ensure that you have configured the
-    /// `dotconfigs_path` field in your `DotConfig` instance to specify where
-    /// you want to push the updated configurations. The method will use this
-    /// destination to push the changes.
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
     ///
-    /// Additionally, this method does not perform metadata checks and
-    /// forcefully pushes all updated configurations, overwriting existing files
-    /// if necessary.
+    /// let path = format!("{}/examples/config.ron", env!("CARGO_MANIFEST_DIR"));
     ///
-    /// This method is often used in conjunction with `pull_updated_configs` to
-    /// maintain a consistent state between local and remote configurations.
+    /// let config_path = DotConfig::config_file_path(&Some(path.clone())).unwrap();
+    /// assert_eq!(config_path.display().to_string(), path);
+    /// ```
     ///
-    /// If the `dotconfigs_path` field specifies a local directory, the method
-    /// copies the updated configurations from your local system to the
-    /// specified directory. If it specifies a remote repository URL, the
-    /// method may use Git or another version control system to push changes to
-    /// the remote repository.
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
+    /// use sync_dotfiles_rs::options::{self, SyncOptions};
     ///
-    /// For security reasons, be cautious when using this method in automated
-    /// scripts, as it may overwrite existing files in the destination
-    /// directory.
-    pub fn push_updated_configs(&mut self) -> Result<()> {
-        self.configs.par_iter().for_each(|dir| {
-            if let DotconfigPath::Local(local_dotconfigs_path) = &self.dotconfigs_path {
-                let dotconfigs_config_path = {
-                    let mut path = fix_path!(local_dotconfigs_path).join(&dir.name);
-
-                    if !path.exists() {
-                        path = fix_path!(local_dotconfigs_path);
-                        path.push(PathBuf::from(&dir.path).file_name().unwrap());
-                    }
+    /// let home = std::env::temp_dir().join("sync-dotfiles-doctest-no-config");
+    /// std::fs::create_dir_all(&home).unwrap();
+    ///
+    /// options::set(SyncOptions {
+    ///     home: Some(home.clone()),
+    ///     ..Default::default()
+    /// });
+    ///
+    /// let err = DotConfig::config_file_path(&None).unwrap_err().to_string();
+    /// assert!(err.contains(&home.join(".sync-dotfiles.ron").display().to_string()));
+    /// assert!(err.contains(&home.join(".config/sync-dotfiles/config.ron").display().to_string()));
+    /// ```
+    pub fn config_file_path(filepath: &Option<String>) -> Result<PathBuf> {
+        let config_path = Self::select_existing_config_path(filepath)?;
 
-                    path
-                };
+        Ok(std::path::absolute(config_path)?)
+    }
 
-                let local_config_hash = dir
-                    .metadata_digest()
-                    .expect("Failed to get metadata digest");
-
-                let mut dotconfigs_hash: Option<String> = None;
-                if dotconfigs_config_path.is_file() {
-                    dotconfigs_hash =
-                        hasher::get_file_hash(&dotconfigs_config_path, &mut Sha1::new())
-                            .unwrap()
-                            .into();
-                } else if dotconfigs_config_path.is_dir() {
-                    dotconfigs_hash =
-                        hasher::get_complete_dir_hash(&dotconfigs_config_path, &mut Sha1::new())
-                            .unwrap()
-                            .into();
-                }
+    /// Select the config file to be used, storing it as the path the config
+    /// is saved to.
+    fn select_config_path(filepath: &Option<String>) -> PathBuf {
+        let mut config_path = CONFIG_PATH.lock().unwrap();
 
-                if dotconfigs_hash.is_none() {
-                    println!("Skipping {:#?} does not exist.", dotconfigs_config_path);
-                    return;
-                }
+        // If the user has specified a config file path
+        if let Some(path) = filepath {
+            *config_path = fix_path!(path);
+        }
 
-                if dotconfigs_hash.unwrap().ne(&local_config_hash) {
-                    println!("Updating {:#?}.", dir.name);
+        config_path.clone()
+    }
 
-                    dir.push_config(&dotconfigs_config_path)
-                        .expect("Failed to push the config");
-                } else {
-                    println!("Skipping {:#?} already up-to date.", dir.name);
-                }
-            }
-        });
+    /// Select the config file to be used like `select_config_path`, failing
+    /// with the searched locations if no config file is found.
+    fn select_existing_config_path(filepath: &Option<String>) -> Result<PathBuf> {
+        let config_path = Self::select_config_path(filepath);
+        if config_path.as_os_str().is_empty() {
+            return Err(ConfigNotFound::new(None).into());
+        }
+        if filepath.is_some() && !config_path.exists() {
+            return Err(ConfigNotFound::new(Some(&config_path)).into());
+        }
 
-        Ok(())
+        Ok(config_path)
     }
 
-    /// Forcefully pull the latest versions of all configured files from the
-    /// source.
-    ///
-    /// This method iterates through the list of configured files and pulls
-    /// the latest versions of each file from the source specified in the
-    /// `DotConfig` structure.
-    ///
-    /// It does not perform metadata checks and forcefully updates all
-    /// configured files.
-    ///
-    /// # Returns
-    ///
-    /// A Result indicating success or an error if any file operations
-    /// fail during the pull operation.
-    pub fn force_pull_configs(&self) -> Result<()> {
-        self.configs.par_iter().for_each(|dir| {
-            if let DotconfigPath::Local(local_dotconfigs_path) = &self.dotconfigs_path {
-                println!("Force pulling {:#?}.", dir.name);
+    /// Parse and validate the given dotconfig file, or merge the fragments
+    /// of the given config directory (see `parse_config_fragments`).
+    fn parse_dotconfig_file(config_path: &Path) -> Result<Self> {
+        let mut config = match config_path.is_dir() {
+            true => Self::parse_config_fragments(config_path)?,
+            false => {
+                let content = read_config_file(config_path)
+                    .context("Failed to open config file from the current directory")?;
 
-                dir.pull_config(local_dotconfigs_path)
-                    .expect("Failed to force pull the config");
-            } else {
-                println!("Skipping dotconfigs_path does not exist.");
+                let format = ConfigFormat::of_file(config_path);
+                format
+                    .parse(&content)
+                    .with_context(|| format!("Failed to parse config file as {format}"))?
             }
-        });
+        };
 
-        Ok(())
+        config.update_dotfiles_dir();
+
+        if config.host_state {
+            config.load_host_state(config_path)?;
+        }
+
+        config.propagate_settings();
+
+        config
+            .configs
+            .iter()
+            .try_for_each(|config| {
+                config.validate_paths()?;
+                config.validate_repo_path()?;
+                config.validate_ignore()?;
+                config.validate_hash_exclude()
+            })
+            .and_then(|_| config.validate_repo_path_nesting())
+            .context("Failed to validate config file")?;
+
+        Ok(config)
     }
 
-    /// Forcefully push all the configured files to their specified destinations.
+    /// Merge the `*.ron` fragments of a config directory (e.g.
+    /// `~/.config/sync-dotfiles/conf.d`) into a single config.
     ///
-    /// This method iterates through the list of configured files and
-    /// forcefully pushes each file to its specified destination path as
-    /// defined in the `DotConfig` structure.
+    /// The fragments are read in the order of their file names. The first
+    /// one is the base fragment (e.g. `00-base.ron`), a complete config
+    /// setting the dotconfigs path and the other options, while the other
+    /// fragments only list `configs`, which are appended to the configs of
+    /// the base fragment. A config name defined in more than one fragment is
+    /// reported as an error.
     ///
-    /// It does not perform metadata checks and forcefully updates all
-    /// configured files, overwriting existing files if necessary.
+    /// # Arguments
+    ///
+    /// * `config_dir` - The path to the config directory.
     ///
     /// # Returns
     ///
-    /// A Result indicating success or an error if any file operations fail
-    /// during the push operation.
-    pub fn force_push_configs(&self) -> Result<()> {
-        self.configs.par_iter().for_each(|dir| {
-            if let DotconfigPath::Local(local_dotconfigs_path) = &self.dotconfigs_path {
-                let dotconfigs_config_path = {
-                    let mut path = fix_path!(local_dotconfigs_path).join(&dir.name);
+    /// A Result containing the merged `DotConfig`, or an error if a fragment
+    /// fails to parse or the fragments define the same config.
+    fn parse_config_fragments(config_dir: &Path) -> Result<Self> {
+        let mut paths = fs::read_dir(config_dir)
+            .with_context(|| format!("Failed to read the config directory {config_dir:#?}"))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<PathBuf>>>()?
+            .into_iter()
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "ron"))
+            .collect::<Vec<PathBuf>>();
+        paths.sort();
 
-                    if !path.exists() {
-                        path = fix_path!(local_dotconfigs_path);
-                        path.push(PathBuf::from(&dir.path).file_name().unwrap());
-                    }
+        let Some(base) = paths.first().cloned() else {
+            return Err(anyhow::anyhow!(
+                "No config fragments (*.ron) found in {config_dir:#?}"
+            ));
+        };
 
-                    path
-                };
+        let content = fs::read_to_string(&base)
+            .with_context(|| format!("Failed to read the config fragment {base:#?}"))?;
+        let mut config = ConfigFormat::Ron
+            .parse(&content)
+            .with_context(|| format!("Failed to parse the base config fragment {base:#?}"))?;
 
-                println!("Force pushing {:#?}.", dir.name);
+        let mut fragments = ConfigFragments {
+            base: base.clone(),
+            paths: paths[1..].to_vec(),
+            sources: HashMap::new(),
+        };
+        let mut defined_in = config
+            .configs
+            .iter()
+            .map(|dir| (dir.name.clone(), base.clone()))
+            .collect::<HashMap<String, PathBuf>>();
 
-                dir.push_config(&dotconfigs_config_path)
-                    .expect("Failed to force push the config");
-            } else {
-                println!("Skipping dotconfigs path does not exist.");
+        for path in &fragments.paths {
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read the config fragment {path:#?}"))?;
+            let fragment: ConfigFragment = Options::default()
+                .with_default_extension(Extensions::IMPLICIT_SOME)
+                .from_str(&content)
+                .with_context(|| format!("Failed to parse the config fragment {path:#?}"))?;
+
+            for dir in fragment.configs {
+                if let Some(other) = defined_in.insert(dir.name.clone(), path.clone()) {
+                    return Err(anyhow::anyhow!(
+                        "Config {:#?} is defined in both {other:#?} and {path:#?}",
+                        dir.name
+                    ));
+                }
+
+                fragments.sources.insert(dir.name.clone(), path.clone());
+                config.configs.push(dir);
             }
-        });
+        }
 
-        Ok(())
+        config.fragments = Some(fragments);
+
+        Ok(config)
     }
 
-    /// Remove metadata from all configured files within the `DotConfig` structure.
-    ///
-    /// This method iterates through the list of configured files and removes
-    /// the metadata associated with each file. Specifically, it clears the
-    /// hash and configuration type information.
+    /// Fix the config file path if it is a relative path.
+    /// Also fix the wrong username in the config file path if it is present.
     ///
-    /// This operation is useful when the user wants to update the
-    /// configuration files with the latest versions without updating
-    /// their hashes or types.
+    /// This function adjusts the configuration file paths to make them
+    /// valid and usable.
+    /// It ensures that relative paths are converted to absolute paths
+    /// and handles potential issues related to usernames in file paths.
     ///
     /// # Returns
     ///
-    /// A Result indicating success or an error if clearing the metadata fails
-    /// for any configured file.
-    pub fn clean_metadata_from_configs(&mut self) -> Result<()> {
-        self.configs.iter_mut().for_each(|dir| {
-            dir.hash = None;
-            dir.conf_type = None;
+    /// A Result indicating success or an error if any path adjustments fail.
+    pub fn fixup_config(&mut self) -> Result<()> {
+        self.configs.iter_mut().for_each(|config| {
+            if !config.path.is_empty() {
+                config.path = fix_path!(&config.path).to_string_lossy().to_string();
+            }
+
+            config.paths.iter_mut().flatten().for_each(|path| {
+                *path = fix_path!(&*path).to_string_lossy().to_string();
+            });
         });
 
-        println!("Metadata removed from the config file.");
         Ok(())
     }
 
-    /// Clean all files and directories in the dotconfig directory except the
-    /// .git folder.
+    /// Override the dotconfigs path for the current run.
     ///
-    /// This method recursively iterates over all files and directories within
-    /// the dotconfig directory (specified in `dotconfigs_path`). It deletes
-    /// all files and directories except for the `.git` folder,
-    /// which is typically used for version control.
+    /// The override is used by all the sync operations, but the dotconfigs
+    /// path of the config file is kept when the config is saved.
     ///
-    /// This operation is useful when the user wants to perform maintenance or
-    /// replace existing configurations in the dotconfig directory.
+    /// # Arguments
     ///
-    /// # Returns
+    /// * `dotconfigs_path` - The dotconfigs path to be used instead.
     ///
-    /// A Result indicating success or an error if any file or directory
-    /// removal fails.
-    pub fn clean_dotconfigs_dir(&self) -> Result<()> {
-        let mut path: Option<PathBuf> = None;
-        if let DotconfigPath::Local(local_dotconfigs_path) = &self.dotconfigs_path {
-            path = Some(fix_path!(local_dotconfigs_path));
-        }
-        println!("Cleaning all the configs inside {path:#?}");
-
-        // iterate over all the files and directories inside the dotconfigs folder
-        walkdir::WalkDir::new(path.as_ref().unwrap())
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .for_each(|e| {
-                // skip the path itself and the .git folder
-                if e.path().eq(path.as_ref().unwrap())
-                    || e.path().to_string_lossy().contains(".git")
-                {
-                    return;
-                }
-
-                // remove the file or directory depending on the type
-                if e.file_type().is_dir() {
-                    std::fs::remove_dir_all(e.path()).expect("Failed to remove directory");
-                } else {
-                    std::fs::remove_file(e.path()).expect("Failed to remove file");
-                }
-            });
-
-        Ok(())
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::{DotConfig, DotconfigPath};
+    ///
+    /// let mut dotconfig = DotConfig::new();
+    /// dotconfig.override_dotconfigs_path(DotconfigPath::Local(String::from("/tmp/dotfiles")));
+    ///
+    /// assert_eq!(dotconfig.dotconfigs_path.to_string(), "/tmp/dotfiles");
+    /// ```
+    pub fn override_dotconfigs_path(&mut self, dotconfigs_path: DotconfigPath) {
+        let original = std::mem::replace(&mut self.dotconfigs_path, dotconfigs_path);
+        self.saved_dotconfigs_path.get_or_insert(original);
+        self.update_dotfiles_dir();
     }
 
-    /// Add a new configuration to the `DotConfig` structure.
+    /// Switch the hash algorithm of the config hashes.
     ///
-    /// This method adds a new configuration to the `DotConfig` structure.
-    /// It creates a new `Config` struct with the specified name and path and
-    /// appends it to the list of configurations. It also checks if a
-    /// configuration with the same name already exists to prevent duplicates.
+    /// The algorithm is saved with the config file. The hashes stored with
+    /// another algorithm never match, so every config is treated as changed
+    /// once, and its hash is replaced when it is synced.
     ///
     /// # Arguments
     ///
-    /// * `name` - A reference to a String representing the name of the
-    /// new configuration.
-    /// * `path` - A reference to a Path representing the path of the
-    /// new configuration.
+    /// * `hash_algorithm` - The hash algorithm to be used.
     ///
-    /// # Returns
+    /// # Example
     ///
-    /// A Result indicating success or an error if the addition fails due to
-    /// a duplicate name or other issues.
-    pub fn add_config(&mut self, name: &String, path: PathBuf) -> Result<()> {
-        self.configs
-            .par_iter()
-            .any(|dir| &dir.name == name)
-            .then(|| {
-                println!("Config with name {name:#?} already exists.");
-                std::process::exit(1);
-            });
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
+    /// use sync_dotfiles_rs::hasher::HashAlgorithm;
+    ///
+    /// let mut dotconfig = DotConfig::new();
+    /// dotconfig.set_hash_algorithm(HashAlgorithm::Blake3);
+    ///
+    /// assert_eq!(dotconfig.hash_algorithm, HashAlgorithm::Blake3);
+    /// assert_eq!(dotconfig.configs[0].hash_algorithm, HashAlgorithm::Blake3);
+    /// ```
+    pub fn set_hash_algorithm(&mut self, hash_algorithm: HashAlgorithm) {
+        self.hash_algorithm = hash_algorithm;
+        self.propagate_settings();
+    }
 
-        let mut conf_type = None;
-        if path.is_dir() {
-            conf_type = Some(ConfType::Dir);
-        } else if path.is_file() {
-            conf_type = Some(ConfType::File);
+    /// Copy the settings that apply to every config, like the change
+    /// detection mode or the hash algorithm, to each config.
+    ///
+    /// The settings are public fields that can change after the config file
+    /// is parsed, so they are copied again by every method that syncs or
+    /// resolves the configs. The repo paths of the configs stored inside
+    /// each other are recorded as well, see `Config::nested_repo_paths`.
+    fn propagate_settings(&mut self) {
+        for dir in self.configs.iter_mut() {
+            dir.change_detection = self.change_detection;
+            dir.hash_algorithm = self.hash_algorithm;
+            dir.lfs_threshold = self.lfs_threshold;
+            dir.mirror_home = self.mirror_home;
+            dir.default_trim_trailing_newline = self.trim_trailing_newline;
         }
 
-        self.configs.push(Config::new(
-            name.to_string(),
-            path.to_string_lossy().to_string(),
-            None,
-            conf_type,
-        ));
+        // Resolved relative to the dotconfigs directory, as it's the same
+        // for every config
+        let repo_paths = self
+            .configs
+            .iter()
+            .map(|dir| dir.repo_path_in(Path::new("")).ok())
+            .collect::<Vec<Option<PathBuf>>>();
+        for (dir, repo_path) in self.configs.iter_mut().zip(&repo_paths) {
+            dir.nested_repo_paths = repo_path
+                .as_ref()
+                .map(|repo_path| {
+                    repo_paths
+                        .iter()
+                        .flatten()
+                        .filter(|other| *other != repo_path)
+                        .filter_map(|other| other.strip_prefix(repo_path).ok())
+                        .map(Path::to_path_buf)
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
+    }
+
+    /// Clone the git repository of a `Github` dotconfigs path, and use the
+    /// clone as the local dotconfigs path for the current run.
+    ///
+    /// The repository is cloned into `~/.cache/sync-dotfiles/<repo-hash>`,
+    /// where the hash is the same whether or not the URL ends in `.git`, and
+    /// an existing clone is reused. The URL is kept when the config is saved.
+    /// Local dotconfigs paths are left unchanged. With the `dry_run` option,
+    /// the repository is not cloned and the missing clone is used as is.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success, or an error if `git` can't be run or the
+    /// repository fails to be cloned (e.g. if the remote is unreachable).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
+    ///
+    /// let mut dotconfig = DotConfig::new();
+    /// dotconfig.ensure_github_cloned().expect("Failed to clone the dotconfigs");
+    ///
+    /// assert_eq!(dotconfig.dotconfigs_path.to_string(), "~/dotfiles");
+    /// ```
+    ///
+    /// Nothing is cloned with the `dry_run` option:
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::{DotConfig, DotconfigPath};
+    /// use sync_dotfiles_rs::options::{self, SyncOptions};
+    ///
+    /// let home = std::env::temp_dir().join("sync-dotfiles-doctest-dry-run-clone");
+    /// let _ = std::fs::remove_dir_all(&home);
+    /// options::set(SyncOptions {
+    ///     home: Some(home.clone()),
+    ///     dry_run: true,
+    ///     ..Default::default()
+    /// });
+    ///
+    /// let mut dotconfig = DotConfig::new();
+    /// dotconfig.dotconfigs_path = DotconfigPath::Github(String::from("https://github.com/user/dotfiles"));
+    /// dotconfig.ensure_github_cloned().expect("Failed to clone the dotconfigs");
+    ///
+    /// assert!(!home.exists());
+    /// ```
+    pub fn ensure_github_cloned(&mut self) -> Result<()> {
+        let DotconfigPath::Github(url) = &self.dotconfigs_path else {
+            return Ok(());
+        };
+
+        let clone_dir = github_clone_dir(url)?;
+        if !clone_dir.join(".git").is_dir() {
+            if options::get().dry_run {
+                println!("Would clone {url} into {clone_dir:#?}");
+            } else {
+                clone_github_repo(url, &clone_dir)?;
+            }
+        }
+
+        self.override_dotconfigs_path(DotconfigPath::Local(clone_dir.display().to_string()));
 
         Ok(())
     }
 
-    /// Create a new `DotConfig` instance with default template.
+    /// Bring the clone of a `Github` dotconfigs path up to date, like a
+    /// `git pull` of the dotfiles repository.
     ///
-    /// This method constructs a new `DotConfig` structure with default
-    /// settings. It initializes the `dotconfigs_path` with the default
-    /// dotfiles directory path and includes a single default
-    /// configuration in the `configs` vector.
+    /// The default branch of the remote is fetched, and the clone is
+    /// fast-forwarded to it. Nothing is done for a local dotconfigs path, or
+    /// if the repository isn't cloned yet (see
+    /// `DotConfig::ensure_github_cloned`). With the `dry_run` option, the
+    /// clone is left as is.
     ///
     /// # Returns
     ///
-    /// A `DotConfig` struct with default settings.
-    /// Create a new dotconfig file with default template
-    pub fn new() -> Self {
-        DotConfig::default()
+    /// A Result indicating success, or an error naming the changed files if
+    /// the clone has uncommitted changes, or if it can't be fetched or
+    /// fast-forwarded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
+    ///
+    /// let dotconfig = DotConfig::new();
+    /// dotconfig.sync_remote().expect("Failed to update the dotconfigs");
+    /// ```
+    pub fn sync_remote(&self) -> Result<()> {
+        let DotconfigPath::Github(url) = &self.dotconfigs_path else {
+            return Ok(());
+        };
+
+        let clone_dir = github_clone_dir(url)?;
+        if !clone_dir.join(".git").is_dir() {
+            return Ok(());
+        }
+
+        if options::get().dry_run {
+            println!("Would fast-forward {clone_dir:#?} to {url}");
+            return Ok(());
+        }
+
+        fast_forward_clone(&clone_dir)
     }
 
-    /// Edit the `sync-dotfiles` configuration file.
+    /// Commit all the changes of the dotconfigs directory and push them to
+    /// its `origin` remote, e.g. after pulling into the clone of a `Github`
+    /// dotconfigs path.
     ///
-    /// This method opens the `sync-dotfiles` configuration file in the
-    /// editor specified by the `EDITOR` environment variable.
-    /// If the `EDITOR` environment variable is not set, it will open the
-    /// `sync-dotfiles` configuration file in the vim editor.
+    /// Nothing is done if there is nothing to commit.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The commit message (default: `sync-dotfiles: update <N>
+    ///   configs`, with the number of configs changed).
     ///
     /// # Returns
     ///
-    /// A Result indicating success or an error if the editor fails to open.
-    pub fn edit_config_file(&self) -> Result<()> {
-        let editor: String = std::env::var("EDITOR").unwrap_or("vim".into());
+    /// A Result indicating success, or an error if the dotconfigs directory
+    /// isn't a git repository, or the changes fail to be committed or pushed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
+    ///
+    /// let dotconfig = DotConfig::new();
+    /// match dotconfig.commit_and_push(None) {
+    ///     Ok(()) => println!("Published the dotconfigs"),
+    ///     Err(err) => eprintln!("Error publishing the dotconfigs: {:?}", err),
+    /// }
+    /// ```
+    pub fn commit_and_push(&self, message: Option<String>) -> Result<()> {
+        let dotconfigs_dir = match &self.dotconfigs_path {
+            DotconfigPath::Local(local_dotconfigs_path) => fix_path!(local_dotconfigs_path),
+            DotconfigPath::Github(url) => github_clone_dir(url)?,
+        };
 
-        process::Command::new(editor)
-            .arg(CONFIG_PATH.lock().unwrap().as_path())
-            .status()
-            .context("Failed to open the editor")?;
+        let changed_files = changed_files(&dotconfigs_dir)
+            .with_context(|| format!("Failed to list the changes of {:#?}", dotconfigs_dir))?;
+        if changed_files.is_empty() {
+            info!("Nothing to commit in {dotconfigs_dir:#?}");
+            return Ok(());
+        }
+
+        let changed_configs = self
+            .configs
+            .iter()
+            .filter(|config| {
+                config.repo_path_in(&dotconfigs_dir).is_ok_and(|path| {
+                    changed_files
+                        .iter()
+                        .any(|file| dotconfigs_dir.join(file).starts_with(&path))
+                })
+            })
+            .count();
+        let message =
+            message.unwrap_or_else(|| format!("sync-dotfiles: update {changed_configs} configs"));
+
+        if options::get().dry_run {
+            println!("Would commit {dotconfigs_dir:#?} as {message:#?} and push it to origin");
+            return Ok(());
+        }
+
+        run_git(&dotconfigs_dir, &["add", "--all"]).context("Failed to stage the changes")?;
+        run_git(&dotconfigs_dir, &["commit", "--quiet", "-m", &message])
+            .context("Failed to commit the changes")?;
+
+        info!("Pushing {dotconfigs_dir:#?} to origin");
+        run_git(&dotconfigs_dir, &["push", "--quiet", "origin", "HEAD"])
+            .context("Failed to push the changes")?;
 
         Ok(())
     }
-}
 
-/// Display implementation for DotConfig.
-///
-/// This implementation allows you to print a human-readable representation
-/// of a `DotConfig` instance.
-///
-/// # Example
-///
-/// ```rust
-/// use sync_dotfiles_rs::dotconfig::DotConfig;
-///
-/// let dotconfig = DotConfig::new();
-/// println!("{}", dotconfig);
-/// ```
-impl fmt::Display for DotConfig {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "DotConfig {{")?;
-        writeln!(f, "    dotconfigs_path: {},", self.dotconfigs_path)?;
-        writeln!(f, "    configs: [")?;
-        self.configs.iter().for_each(|config| {
-            writeln!(f, "        {config},").expect("Failed to display config");
+    /// Set the directory that `$DOTFILES` and `$DOTCONFIGS` expand to in the
+    /// config paths to the local dotconfigs path.
+    fn update_dotfiles_dir(&self) {
+        utils::set_dotfiles_dir(match &self.dotconfigs_path {
+            DotconfigPath::Local(path) => Some(fix_path!(path)),
+            DotconfigPath::Github(_) => None,
         });
-        writeln!(f, "    ],")?;
-        writeln!(f, "}}")
     }
-}
 
-/// Display implementation for DotconfigPath.
-///
-/// This implementation allows you to print a human-readable representation
-/// of a `DotconfigPath` instance.
-///
-/// # Example
-///
-/// ```rust
-/// use sync_dotfiles_rs::dotconfig::DotconfigPath;
-///
-/// let dotconfig_path = DotconfigPath::Local("path/to/dotconfigs".to_string());
-/// println!("{}", dotconfig_path);
-/// ```
-impl fmt::Display for DotconfigPath {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            DotconfigPath::Local(local_dotconfigs_path) => {
-                write!(f, "{local_dotconfigs_path}")
+    /// Save the current configuration to a local file.
+    ///
+    /// This method serializes the `DotConfig` structure into the format of
+    /// the configuration file specified in the `CONFIG_PATH` mutex (RON
+    /// unless the file is detected or forced to be TOML or JSON) and writes
+    /// it to that file.
+    ///
+    /// The configuration file contains information about the dotconfig
+    /// directory and the list of configuration files to sync.
+    ///
+    /// With `host_state` enabled, the hashes and sync times of the
+    /// configurations are saved in the state file of the current host
+    /// instead of the configuration file.
+    ///
+    /// A configuration file with a `.gz` extension is written gzip
+    /// compressed. With the `--compress-config` option, an uncompressed
+    /// configuration file is replaced by its compressed version (e.g.
+    /// `config.ron.gz`), which is used from then on.
+    ///
+    /// A configuration merged from the fragments of a config directory is
+    /// saved back to the fragments, every configuration to the fragment it
+    /// was defined in. New configurations are added to the base fragment.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or an error if any file operations fail.
+    pub fn save_configs(&self) -> Result<()> {
+        let mut config_path = CONFIG_PATH.lock().unwrap();
+        if options::get().dry_run {
+            println!("Would save the config file {:#?}", *config_path);
+            return Ok(());
+        }
+        let format = ConfigFormat::of_file(&config_path);
+
+        if self.host_state {
+            self.save_host_state(&config_path)?;
+        }
+
+        if let Some(fragments) = &self.fragments {
+            return self.save_config_fragments(fragments);
+        }
+
+        let config = format
+            .serialize(&self.saved_config())
+            .context("Failed to serialize config")?;
+        let config = with_comment_header(format, &config_path, config);
+
+        if options::get().show_config_diff {
+            print_config_diff(&config_path, &config);
+        }
+
+        // Replace the uncompressed config file by its compressed version
+        let uncompressed_path = match options::get().compress_config && !is_gzipped(&config_path) {
+            true => {
+                let compressed_path = gzip_path(&config_path);
+                Some(std::mem::replace(&mut *config_path, compressed_path))
             }
-            DotconfigPath::Github(remote_dotconfigs_path) => {
-                write!(f, "{remote_dotconfigs_path}")
+            false => None,
+        };
+
+        info!("Saving config file to {:#?}", config_path.display());
+
+        write_config_file(config_path.as_path(), &config)
+            .context("Failed to write to config file")?;
+
+        if let Some(uncompressed_path) = uncompressed_path {
+            if uncompressed_path.exists() {
+                fs::remove_file(&uncompressed_path)
+                    .context("Failed to remove the uncompressed config file")?;
             }
         }
+
+        Ok(())
     }
-}
 
-/// Debug implementation for DotconfigPath.
-///
-/// This implementation allows you to print a human-readable representation
-/// of a `DotconfigPath` instance.
-///
-/// # Example
-///
-/// ```rust
-/// use sync_dotfiles_rs::dotconfig::DotconfigPath;
-///
-/// let dotconfig_path = DotconfigPath::Github(
-///         "https://github.com/user/repo".to_string());
-/// println!("{:?}", dotconfig_path);
-/// ```
-impl fmt::Debug for DotconfigPath {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            DotconfigPath::Local(local_dotconfigs_path) => {
-                write!(f, "{local_dotconfigs_path}")
-            }
-            DotconfigPath::Github(remote_dotconfigs_path) => {
-                write!(f, "{remote_dotconfigs_path}")
+    /// Save the configuration back to the fragments of the config directory
+    /// it was merged from, see `save_configs`.
+    fn save_config_fragments(&self, fragments: &ConfigFragments) -> Result<()> {
+        let mut config = self.saved_config();
+        let (base_configs, fragment_configs) = std::mem::take(&mut config.configs)
+            .into_iter()
+            .partition::<Vec<Config>, _>(|dir| !fragments.sources.contains_key(&dir.name));
+        config.configs = base_configs;
+
+        let mut contents = vec![(
+            fragments.base.clone(),
+            ConfigFormat::Ron
+                .serialize(&config)
+                .context("Failed to serialize config")?,
+        )];
+        for path in &fragments.paths {
+            let fragment = ConfigFragment {
+                configs: fragment_configs
+                    .iter()
+                    .filter(|dir| fragments.sources.get(&dir.name) == Some(path))
+                    .cloned()
+                    .collect(),
+            };
+            let content = to_string_pretty(&fragment, get_ron_formatter())
+                .context("Failed to serialize config fragment")?;
+            contents.push((path.clone(), content));
+        }
+
+        for (path, content) in contents {
+            let content = with_comment_header(ConfigFormat::Ron, &path, content);
+            if options::get().show_config_diff {
+                print_config_diff(&path, &content);
             }
+
+            info!("Saving config fragment to {:#?}", path.display());
+
+            fs::write(&path, content).context("Failed to write to config fragment")?;
         }
+
+        Ok(())
     }
-}
 
-/// Default implementation for DotConfig.
-///
-/// This implementation creates a new `DotConfig` instance with default
-/// settings.
-impl Default for DotConfig {
-    fn default() -> Self {
-        DotConfig {
-            dotconfigs_path: DotconfigPath::Local(String::from("~/dotfiles")),
-            configs: vec![Config::default()],
+    /// Get the configuration as it is saved to the configuration file.
+    ///
+    /// The dotconfigs path is the one of the configuration file if it was
+    /// overridden, and with `host_state` enabled the machine-local state of
+    /// the configurations is left out.
+    fn saved_config(&self) -> DotConfig {
+        let mut config = self.clone();
+
+        // Keep the dotconfigs path of the config file if it was overridden
+        if let Some(dotconfigs_path) = config.saved_dotconfigs_path.take() {
+            config.dotconfigs_path = dotconfigs_path;
+        }
+
+        // The state is saved in the state file of the host
+        if config.host_state {
+            config.configs.iter_mut().for_each(|dir| {
+                dir.hash = None;
+                dir.last_sync = None;
+            });
         }
+
+        config
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    /// Load the hashes and sync times of the configurations from the state
+    /// file of the current host.
+    ///
+    /// Without a state file (e.g. on the first run of a host, or when
+    /// `host_state` was just enabled), the state stored in the configuration
+    /// file is kept, and moved to the state file when it is saved.
+    fn load_host_state(&mut self, config_path: &Path) -> Result<()> {
+        let state_path = host_state_path(config_path)?;
+        if !state_path.exists() {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(&state_path)
+            .with_context(|| format!("Failed to read the host state file {state_path:#?}"))?;
+        let mut state: HostState = Options::default()
+            .with_default_extension(Extensions::IMPLICIT_SOME)
+            .from_str(&content)
+            .with_context(|| format!("Failed to parse the host state file {state_path:#?}"))?;
+
+        self.configs.iter_mut().for_each(|dir| {
+            let config_state = state.configs.remove(&dir.name).unwrap_or_default();
+            dir.hash = config_state.hash;
+            dir.last_sync = config_state.last_sync;
+        });
+
+        Ok(())
+    }
+
+    /// Save the hashes and sync times of the configurations to the state
+    /// file of the current host.
+    fn save_host_state(&self, config_path: &Path) -> Result<()> {
+        let state_path = host_state_path(config_path)?;
+        let state = HostState {
+            configs: self
+                .configs
+                .iter()
+                .map(|dir| {
+                    let config_state = ConfigState {
+                        hash: dir.hash.clone(),
+                        last_sync: dir.last_sync,
+                    };
+                    (dir.name.clone(), config_state)
+                })
+                .collect(),
+        };
+
+        let content = to_string_pretty(&state, get_ron_formatter())
+            .context("Failed to serialize the host state")?;
+
+        if let Some(state_dir) = state_path.parent() {
+            fs::create_dir_all(state_dir).context("Failed to create the state directory")?;
+        }
+
+        info!("Saving host state to {:#?}", state_path.display());
+        fs::write(&state_path, content)
+            .with_context(|| format!("Failed to write the host state file {state_path:#?}"))
+    }
+
+    /// Pull all configured files based on their metadata.
+    ///
+    /// This method iterates through the list of configured files and checks
+    /// whether each file needs to be updated based on its metadata.
+    ///
+    /// If a configuration file requires an update, it updates the metadata
+    /// in the config file and replaces the file with the latest version from
+    /// the source specified in the `DotConfig` structure.
+    ///
+    /// Changes are detected using the `change_detection` mode of the
+    /// `DotConfig`.
+    ///
+    /// With the `--no-metadata-update` option, the files are copied but the
+    /// metadata is left as it is, so the recorded hashes keep describing the
+    /// last regular pull.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the report of the files copied from all the
+    /// pulled configs and the summary of the pulled, skipped and failed
+    /// configs. With the `--fail-fast` option, the first config that fails
+    /// to be pulled is returned as an error instead.
+    ///
+    /// # Example
+    ///
+    /// With `mirror_home`, the configs are stored at their path relative to
+    /// the home directory, and pushed back from there:
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use sync_dotfiles_rs::dotconfig::{DotConfig, DotconfigPath};
+    /// use sync_dotfiles_rs::options::{self, SyncOptions};
+    /// use std::fs;
+    ///
+    /// let path = std::env::temp_dir().join("sync-dotfiles-doctest-mirror-home");
+    /// let _ = fs::remove_dir_all(&path);
+    /// let home = path.join("home");
+    /// fs::create_dir_all(home.join(".config/nvim/lua")).unwrap();
+    /// fs::write(home.join(".config/nvim/lua/init.lua"), "init").unwrap();
+    /// fs::write(home.join(".config/starship.toml"), "toml").unwrap();
+    /// fs::write(home.join(".vimrc"), "set nu").unwrap();
+    ///
+    /// options::set(SyncOptions {
+    ///     home: Some(home.clone()),
+    ///     ..Default::default()
+    /// });
+    ///
+    /// let mut dotconfig = DotConfig::new();
+    /// dotconfig.mirror_home = true;
+    /// dotconfig.dotconfigs_path =
+    ///     DotconfigPath::Local(path.join("dotconfigs").display().to_string());
+    /// dotconfig.configs = vec![
+    ///     Config::new_dir("nvim", "~/.config/nvim"),
+    ///     Config::new_file("starship", "~/.config/starship.toml"),
+    ///     Config::new_file("vimrc", "~/.vimrc"),
+    /// ];
+    ///
+    /// dotconfig.pull_updated_configs().unwrap();
+    ///
+    /// let dotconfigs = path.join("dotconfigs");
+    /// assert!(dotconfigs.join(".config/nvim/lua/init.lua").is_file());
+    /// assert!(dotconfigs.join(".config/starship.toml").is_file());
+    /// assert!(dotconfigs.join(".vimrc").is_file());
+    ///
+    /// fs::write(dotconfigs.join(".config/starship.toml"), "pushed").unwrap();
+    /// dotconfig.push_updated_configs().unwrap();
+    ///
+    /// assert_eq!(
+    ///     fs::read_to_string(home.join(".config/starship.toml")).unwrap(),
+    ///     "pushed"
+    /// );
+    ///
+    /// fs::remove_dir_all(&path).unwrap();
+    /// ```
+    pub fn pull_updated_configs(&mut self) -> Result<(CopyReport, SyncSummary)> {
+        self.pull_configs(!options::get().no_metadata_update)
+    }
+
+    /// Pull all the changed configs, see `pull_updated_configs`, updating
+    /// their metadata only if `update_metadata` is set.
+    fn pull_configs(&mut self, update_metadata: bool) -> Result<(CopyReport, SyncSummary)> {
+        self.propagate_settings();
+        let selected = self.selected_config_names()?;
+        let mut report = CopyReport::default();
+
+        // iterate through all the configs
+        let outcomes = self
+            .configs
+            .iter_mut()
+            .filter(|dir| is_selected(&selected, dir))
+            .map(|dir| {
+                let outcome =
+                    pull_updated_config(dir, &self.dotconfigs_path, update_metadata, &mut report);
+                fail_fast(dir.name.clone(), outcome)
+            })
+            .collect::<Result<Vec<(String, Result<Option<SkipReason>>)>>>()?;
+
+        let mut summary = SyncSummary::from_outcomes(outcomes, SyncAction::Pulled);
+        summary.copied = report.clone();
+
+        Ok((report, summary))
+    }
+
+    /// Push Updatable configs back to their local destination in the system
+    ///
+    /// The `push_updated_configs` method is responsible for pushing all the
+    /// updated configuration files to their specified destination paths.
+    ///
+    /// It iterates through the list of configured files and, for each
+    /// configuration, checks if it needs to be updated based on its metadata.
+    /// If an update is required, it pushes the updated configuration to its
+    /// specified destination.
+    ///
+    /// This operation is essential when you want to synchronize your local
+    /// configurations with a remote repository or directory specified in the
+    /// `DotConfig` structure's `dotconfigs_path` field.
+    ///
+    /// # Errors
+    ///
+    /// This method may return an error if any file operations or the pushing
+    /// process fail. Errors could include issues with file I/O, authentication,
+    /// or network connectivity.
+    ///
+    /// # Note
+    ///
+    /// Before using this method, ensure that you have configured the
+    /// `dotconfigs_path` field in your `DotConfig` instance to specify where
+    /// you want to push the updated configurations. The method will use this
+    /// destination to push the changes.
+    ///
+    /// Additionally, this method does not perform metadata checks and
+    /// forcefully pushes all updated configurations, overwriting existing files
+    /// if necessary.
+    ///
+    /// This method is often used in conjunction with `pull_updated_configs` to
+    /// maintain a consistent state between local and remote configurations.
+    ///
+    /// If the `dotconfigs_path` field specifies a local directory, the method
+    /// copies the updated configurations from your local system to the
+    /// specified directory. If it specifies a remote repository URL, the
+    /// method may use Git or another version control system to push changes to
+    /// the remote repository.
+    ///
+    /// For security reasons, be cautious when using this method in automated
+    /// scripts, as it may overwrite existing files in the destination
+    /// directory.
+    ///
+    /// # Threading
+    ///
+    /// The configs are processed in parallel on the global Rayon thread pool.
+    /// The per-file hashing of directory configs runs on the same pool, so
+    /// the nested parallelism is bounded by the size of that pool.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the summary of the pushed, skipped and failed
+    /// configs. With the `--fail-fast` option, the first config that fails
+    /// to be pushed is returned as an error instead.
+    ///
+    /// # Example
+    ///
+    /// With the `dry_run` option, neither a pull nor a push changes any
+    /// file:
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use sync_dotfiles_rs::dotconfig::{DotConfig, DotconfigPath};
+    /// use sync_dotfiles_rs::options::{self, SyncOptions};
+    /// use std::fs;
+    ///
+    /// let path = std::env::temp_dir().join("sync-dotfiles-doctest-dry-run-sync");
+    /// let _ = fs::remove_dir_all(&path);
+    /// fs::create_dir_all(path.join("local/nvim")).unwrap();
+    /// fs::create_dir_all(path.join("dotconfigs/nvim")).unwrap();
+    /// fs::write(path.join("local/nvim/init.lua"), "local").unwrap();
+    /// fs::write(path.join("local/.vimrc"), "set nonu").unwrap();
+    /// fs::write(path.join("dotconfigs/nvim/init.lua"), "stored").unwrap();
+    /// fs::write(path.join("dotconfigs/nvim/stale.lua"), "stale").unwrap();
+    /// fs::write(path.join("dotconfigs/.vimrc"), "set nu").unwrap();
+    ///
+    /// let mut dotconfig = DotConfig::new();
+    /// dotconfig.dotconfigs_path =
+    ///     DotconfigPath::Local(path.join("dotconfigs").display().to_string());
+    /// dotconfig.configs = vec![
+    ///     Config::new_dir("nvim", path.join("local/nvim").display().to_string()),
+    ///     Config::new_file("vimrc", path.join("local/.vimrc").display().to_string()),
+    /// ];
+    ///
+    /// options::set(SyncOptions {
+    ///     dry_run: true,
+    ///     ..Default::default()
+    /// });
+    /// dotconfig.pull_updated_configs().expect("Failed to pull");
+    /// dotconfig.push_updated_configs().expect("Failed to push");
+    ///
+    /// let read = |file: &str| fs::read_to_string(path.join(file)).unwrap();
+    /// assert_eq!(read("dotconfigs/nvim/init.lua"), "stored");
+    /// assert_eq!(read("dotconfigs/nvim/stale.lua"), "stale");
+    /// assert_eq!(read("dotconfigs/.vimrc"), "set nu");
+    /// assert_eq!(read("local/nvim/init.lua"), "local");
+    /// assert_eq!(read("local/.vimrc"), "set nonu");
+    ///
+    /// fs::remove_dir_all(&path).unwrap();
+    /// ```
+    pub fn push_updated_configs(&mut self) -> Result<SyncSummary> {
+        self.propagate_settings();
+        let selected = self.selected_config_names()?;
+        let report = Mutex::new(CopyReport::default());
+
+        let outcomes = self
+            .configs
+            .par_iter()
+            .filter(|dir| is_selected(&selected, dir))
+            .map(|dir| {
+                fail_fast(
+                    dir.name.clone(),
+                    push_updated_config(dir, &self.dotconfigs_path, &report),
+                )
+            })
+            .collect::<Result<Vec<(String, Result<Option<SkipReason>>)>>>()?;
+
+        let mut summary = SyncSummary::from_outcomes(outcomes, SyncAction::Pushed);
+        summary.copied = report.into_inner().unwrap();
+
+        Ok(summary)
+    }
+
+    /// Sync all the configs in both directions.
+    ///
+    /// For every config, the local config and its copy in the dotconfigs
+    /// directory are compared with the hash stored at the last sync:
+    ///
+    /// - If only the local config changed (or only it exists), it is pulled
+    ///   into the dotconfigs directory.
+    /// - If only the dotconfigs copy changed (or only it exists), it is
+    ///   pushed to the local system.
+    /// - If both changed, the config is reported as a conflict and left
+    ///   untouched.
+    ///
+    /// The dotconfigs copy can only be compared by its contents, so with the
+    /// `MetadataStamp` change detection a config that changed locally and
+    /// differs from its dotconfigs copy is always reported as a conflict.
+    ///
+    /// Configs that fail to sync don't stop the other configs from being
+    /// synced, and are reported in the summary.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the summary of the sync, or an error if the
+    /// dotconfigs path is not local or the selected profile is invalid.
+    pub fn sync_configs(&mut self) -> Result<SyncSummary> {
+        self.propagate_settings();
+        let DotconfigPath::Local(local_dotconfigs_path) = &self.dotconfigs_path else {
+            return Err(anyhow::anyhow!(
+                "Cannot sync non-local dotconfigs path: {}",
+                self.dotconfigs_path
+            ));
+        };
+        let selected = self.selected_config_names()?;
+
+        let mut summary = SyncSummary::default();
+        self.configs
+            .iter_mut()
+            .filter(|dir| is_selected(&selected, dir))
+            .try_for_each(|dir| -> Result<()> {
+                let outcome = sync_config(dir, local_dotconfigs_path, &mut summary.copied);
+                match fail_fast(dir.name.clone(), outcome)? {
+                    (name, Ok(action)) => summary.record(name, action),
+                    (name, Err(e)) => summary.errors.push((name, format!("{e:#}"))),
+                }
+
+                Ok(())
+            })?;
+
+        Ok(summary)
+    }
+
+    /// Compare every config with its copy in the dotconfigs directory,
+    /// without syncing anything, like a `git status` of the configs.
+    ///
+    /// The local config and its copy are compared with the hash stored at
+    /// the last sync, the same way as `DotConfig::sync_configs` does, to
+    /// tell which side changed. Configs that aren't synced on the current
+    /// operating system are left out.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the name and the `SyncState` of every selected
+    /// config, or an error if the dotconfigs path is not local, the selected
+    /// profile is invalid or a config fails to be hashed.
+    pub fn status(&mut self) -> Result<Vec<(String, SyncState)>> {
+        self.propagate_settings();
+        let DotconfigPath::Local(local_dotconfigs_path) = &self.dotconfigs_path else {
+            return Err(anyhow::anyhow!(
+                "Cannot compare non-local dotconfigs path: {}",
+                self.dotconfigs_path
+            ));
+        };
+        let selected = self.selected_config_names()?;
+
+        self.configs
+            .iter()
+            .filter(|dir| is_selected(&selected, dir) && dir.applies_to_current_os())
+            .map(|dir| {
+                let state = config_status(dir, local_dotconfigs_path)
+                    .with_context(|| format!("Failed to compare {:#?}", dir.name))?;
+                Ok((dir.name.clone(), state))
+            })
+            .collect()
+    }
+
+    /// Lock the config file against other `sync-dotfiles` processes.
+    ///
+    /// The lock is held on a `.lock` file next to the config file until the
+    /// returned `FileLock` is dropped.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the lock, `None` if another process holds the
+    /// lock, or an error if the lock file can't be opened.
+    pub fn try_lock() -> Result<Option<FileLock>> {
+        let mut lock_path = CONFIG_PATH.lock().unwrap().clone().into_os_string();
+        lock_path.push(".lock");
+
+        utils::try_lock_file(PathBuf::from(lock_path))
+    }
+
+    /// Forcefully pull the latest versions of all configured files from the
+    /// source.
+    ///
+    /// This method iterates through the list of configured files and pulls
+    /// the latest versions of each file from the source specified in the
+    /// `DotConfig` structure.
+    ///
+    /// It does not perform metadata checks and forcefully updates all
+    /// configured files.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the summary of the pulled, skipped and failed
+    /// configs. With the `--fail-fast` option, the first config that fails
+    /// to be pulled is returned as an error instead.
+    pub fn force_pull_configs(&mut self) -> Result<SyncSummary> {
+        self.propagate_settings();
+        let selected = self.selected_config_names()?;
+        if let DotconfigPath::Local(local_dotconfigs_path) = &self.dotconfigs_path {
+            check_not_home_ancestor(&fix_path!(local_dotconfigs_path), &utils::home_dir()?)?;
+        }
+        let report = Mutex::new(CopyReport::default());
+
+        let outcomes = self
+            .configs
+            .par_iter()
+            .filter(|dir| is_selected(&selected, dir))
+            .map(|dir| {
+                if !dir.applies_to_current_os() {
+                    return Ok((dir.name.clone(), Ok(Some(SkipReason::OtherOs))));
+                }
+
+                let DotconfigPath::Local(local_dotconfigs_path) = &self.dotconfigs_path else {
+                    return Ok((
+                        dir.name.clone(),
+                        Ok(Some(SkipReason::NonLocalDotconfigsPath)),
+                    ));
+                };
+
+                info!("Force pulling {:#?}.", dir.name);
+
+                let outcome = dir.pull_config(local_dotconfigs_path).map(|copied| {
+                    report.lock().unwrap().merge(copied);
+                    None
+                });
+                fail_fast(dir.name.clone(), outcome)
+            })
+            .collect::<Result<Vec<(String, Result<Option<SkipReason>>)>>>()?;
+
+        let mut summary = SyncSummary::from_outcomes(outcomes, SyncAction::Pulled);
+        summary.copied = report.into_inner().unwrap();
+
+        Ok(summary)
+    }
+
+    /// Forcefully push all the configured files to their specified destinations.
+    ///
+    /// This method iterates through the list of configured files and
+    /// forcefully pushes each file to its specified destination path as
+    /// defined in the `DotConfig` structure.
+    ///
+    /// It does not perform metadata checks and forcefully updates all
+    /// configured files, overwriting existing files if necessary.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the summary of the pushed, skipped and failed
+    /// configs. With the `--fail-fast` option, the first config that fails
+    /// to be pushed is returned as an error instead.
+    pub fn force_push_configs(&mut self) -> Result<SyncSummary> {
+        self.propagate_settings();
+        let selected = self.selected_config_names()?;
+        if let DotconfigPath::Local(local_dotconfigs_path) = &self.dotconfigs_path {
+            check_not_home_ancestor(&fix_path!(local_dotconfigs_path), &utils::home_dir()?)?;
+        }
+        let report = Mutex::new(CopyReport::default());
+
+        let outcomes = self
+            .configs
+            .par_iter()
+            .filter(|dir| is_selected(&selected, dir))
+            .map(|dir| {
+                if !dir.applies_to_current_os() {
+                    return Ok((dir.name.clone(), Ok(Some(SkipReason::OtherOs))));
+                }
+
+                let DotconfigPath::Local(local_dotconfigs_path) = &self.dotconfigs_path else {
+                    return Ok((
+                        dir.name.clone(),
+                        Ok(Some(SkipReason::NonLocalDotconfigsPath)),
+                    ));
+                };
+
+                info!("Force pushing {:#?}.", dir.name);
+
+                let outcome = dir
+                    .repo_path_in(&fix_path!(local_dotconfigs_path))
+                    .and_then(|dotconfigs_config_path| dir.push_config(&dotconfigs_config_path))
+                    .map(|copied| {
+                        report.lock().unwrap().merge(copied);
+                        None
+                    });
+                fail_fast(dir.name.clone(), outcome)
+            })
+            .collect::<Result<Vec<(String, Result<Option<SkipReason>>)>>>()?;
+
+        let mut summary = SyncSummary::from_outcomes(outcomes, SyncAction::Pushed);
+        summary.copied = report.into_inner().unwrap();
+
+        Ok(summary)
+    }
+
+    /// Recreate the configs missing from the dotconfigs directory from their
+    /// local copies.
+    ///
+    /// Only the configs whose copy in the dotconfigs directory is missing
+    /// (e.g. after a partial clone or a manual deletion) are pulled, unlike
+    /// `force_pull_configs`, and their metadata is updated to match the new
+    /// copy. Configs that already exist in the dotconfigs directory are left
+    /// as they are, even if they changed.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the summary of the pulled, skipped and failed
+    /// configs. With the `--fail-fast` option, the first config that fails
+    /// to be pulled is returned as an error instead.
+    pub fn repair(&mut self) -> Result<SyncSummary> {
+        self.propagate_settings();
+        let selected = self.selected_config_names()?;
+        let update_metadata = !options::get().no_metadata_update;
+        let mut report = CopyReport::default();
+
+        let outcomes = self
+            .configs
+            .iter_mut()
+            .filter(|dir| is_selected(&selected, dir))
+            .map(|dir| {
+                let outcome =
+                    repair_config(dir, &self.dotconfigs_path, update_metadata, &mut report);
+                fail_fast(dir.name.clone(), outcome)
+            })
+            .collect::<Result<Vec<(String, Result<Option<SkipReason>>)>>>()?;
+
+        let mut summary = SyncSummary::from_outcomes(outcomes, SyncAction::Pulled);
+        summary.copied = report;
+
+        Ok(summary)
+    }
+
+    /// Copy the configs stored in a dotconfigs directory into another
+    /// dotconfigs directory, e.g. to migrate to a new checkout.
+    ///
+    /// Every config is copied from where it is stored in the `from`
+    /// directory to the same relative path in the `to` directory, replacing
+    /// its previous copy there. The local system and the metadata of the
+    /// configs are left untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The dotconfigs directory the configs are copied from.
+    /// * `to` - The dotconfigs directory the configs are copied into.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the summary of the copied (as pulled), skipped
+    /// and failed configs, or an error if the `from` directory doesn't exist
+    /// or both directories are the same. With the `--fail-fast` option, the
+    /// first config that fails to be copied is returned as an error instead.
+    pub fn transfer(&mut self, from: &Path, to: &Path) -> Result<SyncSummary> {
+        self.propagate_settings();
+        if !from.is_dir() {
+            return Err(anyhow::anyhow!(
+                "The dotconfigs directory {:#?} does not exist",
+                from
+            ));
+        }
+        if fs::canonicalize(to).ok() == Some(fs::canonicalize(from)?) {
+            return Err(anyhow::anyhow!(
+                "Cannot transfer the configs of {:#?} into the same directory",
+                from
+            ));
+        }
+
+        let selected = self.selected_config_names()?;
+        let mut report = CopyReport::default();
+
+        let outcomes = self
+            .configs
+            .iter()
+            .filter(|dir| is_selected(&selected, dir))
+            .map(|dir| {
+                let outcome = transfer_config(dir, from, to, &mut report);
+                fail_fast(dir.name.clone(), outcome)
+            })
+            .collect::<Result<Vec<(String, Result<Option<SkipReason>>)>>>()?;
+
+        let mut summary = SyncSummary::from_outcomes(outcomes, SyncAction::Pulled);
+        summary.copied = report;
+
+        Ok(summary)
+    }
+
+    /// Remove metadata from all configured files within the `DotConfig` structure.
+    ///
+    /// This method iterates through the list of configured files and removes
+    /// the metadata associated with each file. Specifically, it clears the
+    /// hash, configuration type and last sync time information.
+    ///
+    /// This operation is useful when the user wants to update the
+    /// configuration files with the latest versions without updating
+    /// their hashes or types.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or an error if clearing the metadata fails
+    /// for any configured file.
+    pub fn clean_metadata_from_configs(&mut self) -> Result<()> {
+        self.configs.iter_mut().for_each(|dir| {
+            dir.hash = None;
+            dir.conf_type = None;
+            dir.last_sync = None;
+        });
+
+        info!("Metadata removed from the config file.");
+        Ok(())
+    }
+
+    /// Remove the metadata of a single config, see
+    /// `clean_metadata_from_configs`.
+    ///
+    /// This is useful to rehash a config from scratch on the next sync
+    /// (e.g. after editing its files by hand), without touching the
+    /// metadata of the other configs.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the config.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or an error if there is no config with
+    /// the given name.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::{config::Config, dotconfig::DotConfig};
+    ///
+    /// let mut vimrc = Config::new_file("vimrc", "~/.vimrc");
+    /// vimrc.hash = Some(String::from("abcd1234"));
+    /// let mut zshrc = Config::new_file("zshrc", "~/.zshrc");
+    /// zshrc.hash = Some(String::from("ef567890"));
+    ///
+    /// let mut dotconfig = DotConfig::new();
+    /// *dotconfig.configs_mut() = vec![vimrc, zshrc];
+    ///
+    /// dotconfig.clear_metadata_for("vimrc").unwrap();
+    /// assert_eq!(dotconfig.configs()[0].hash, None);
+    /// assert_eq!(dotconfig.configs()[1].hash.as_deref(), Some("ef567890"));
+    ///
+    /// assert!(dotconfig.clear_metadata_for("nvim").is_err());
+    /// ```
+    pub fn clear_metadata_for(&mut self, name: &str) -> Result<()> {
+        let Some(dir) = self.configs.iter_mut().find(|dir| dir.name == name) else {
+            return Err(anyhow::anyhow!("No config named {:#?}", name));
+        };
+
+        dir.hash = None;
+        dir.conf_type = None;
+        dir.last_sync = None;
+
+        info!("Metadata of {:#?} removed from the config file.", name);
+        Ok(())
+    }
+
+    /// Clean all files and directories in the dotconfig directory except the
+    /// .git folder.
+    ///
+    /// This method recursively iterates over all files and directories within
+    /// the dotconfig directory (specified in `dotconfigs_path`). It deletes
+    /// all files and directories except for the `.git` folder,
+    /// which is typically used for version control.
+    ///
+    /// All the entries to be removed are collected before anything is
+    /// removed, and are then removed deepest-first, so that no directory is
+    /// removed before its contents have been visited.
+    ///
+    /// This operation is useful when the user wants to perform maintenance or
+    /// replace existing configurations in the dotconfig directory.
+    ///
+    /// With the `dry_run` option, the entries are printed instead of being
+    /// removed.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or an error if the dotconfig directory is
+    /// not a local path, is the home directory or one of its ancestors, or
+    /// any file or directory removal fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::{DotConfig, DotconfigPath};
+    /// use sync_dotfiles_rs::options::{self, SyncOptions};
+    ///
+    /// let path = std::env::temp_dir().join("sync-dotfiles-doctest-dry-run");
+    /// std::fs::create_dir_all(path.join("nvim")).unwrap();
+    /// std::fs::write(path.join("nvim/init.lua"), "lua").unwrap();
+    ///
+    /// let mut dotconfig = DotConfig::new();
+    /// dotconfig.dotconfigs_path = DotconfigPath::Local(path.display().to_string());
+    ///
+    /// options::set(SyncOptions {
+    ///     dry_run: true,
+    ///     ..Default::default()
+    /// });
+    /// dotconfig.clean_dotconfigs_dir().expect("Failed to clean");
+    /// assert!(path.join("nvim/init.lua").exists());
+    ///
+    /// options::set(SyncOptions::default());
+    /// dotconfig.clean_dotconfigs_dir().expect("Failed to clean");
+    /// assert!(!path.join("nvim").exists());
+    /// ```
+    pub fn clean_dotconfigs_dir(&self) -> Result<()> {
+        let DotconfigPath::Local(local_dotconfigs_path) = &self.dotconfigs_path else {
+            return Err(anyhow::anyhow!(
+                "Cannot clean non-local dotconfigs path: {}",
+                self.dotconfigs_path
+            ));
+        };
+        let path = fix_path!(local_dotconfigs_path);
+        check_not_home_ancestor(&path, &utils::home_dir()?)?;
+        info!("Cleaning all the configs inside {path:#?}");
+
+        // collect all the files and directories inside the dotconfigs folder,
+        // skipping the path itself and the .git folder
+        let mut entries = walkdir::WalkDir::new(&path)
+            .min_depth(1)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| !utils::is_git_path(e.path().strip_prefix(&path).unwrap_or(e.path())))
+            .collect::<Vec<walkdir::DirEntry>>();
+
+        // remove the deepest entries first
+        entries.sort_by_key(|e| std::cmp::Reverse(e.depth()));
+
+        if options::get().dry_run {
+            entries
+                .iter()
+                .for_each(|e| println!("Would remove {:#?}", e.path()));
+            return Ok(());
+        }
+
+        entries.iter().try_for_each(|e| {
+            // remove the file or directory depending on the type, directories
+            // may still contain nested .git folders
+            if e.file_type().is_dir() {
+                std::fs::remove_dir_all(e.path())
+                    .with_context(|| format!("Failed to remove directory {:#?}", e.path()))
+            } else {
+                std::fs::remove_file(e.path())
+                    .with_context(|| format!("Failed to remove file {:#?}", e.path()))
+            }
+        })
+    }
+
+    /// Summarize the contents of the dotconfig directory.
+    ///
+    /// This method walks the dotconfig directory (specified in
+    /// `dotconfigs_path`) the same way `clean_dotconfigs_dir` does, skipping
+    /// the `.git` folder, and counts the files along with their total size.
+    ///
+    /// It is used to show the scale of destructive operations like `Clean`
+    /// and `ForcePull` before they are performed.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing a tuple of the number of files and the total size
+    /// in bytes, or an error if the dotconfig directory is not a local path
+    /// or is the home directory or one of its ancestors.
+    pub fn summarize_dotconfigs(&self) -> Result<(usize, u64)> {
+        let DotconfigPath::Local(local_dotconfigs_path) = &self.dotconfigs_path else {
+            return Err(anyhow::anyhow!(
+                "Cannot summarize non-local dotconfigs path: {}",
+                self.dotconfigs_path
+            ));
+        };
+        let path = fix_path!(local_dotconfigs_path);
+        check_not_home_ancestor(&path, &utils::home_dir()?)?;
+
+        let (files, bytes) = walkdir::WalkDir::new(&path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| !utils::is_git_path(e.path().strip_prefix(&path).unwrap_or(e.path())))
+            .filter(|e| e.file_type().is_file())
+            .fold((0, 0), |(files, bytes), e| {
+                let len = e.metadata().map(|m| m.len()).unwrap_or(0);
+                (files + 1, bytes + len)
+            });
+
+        Ok((files, bytes))
+    }
+
+    /// Check that the filesystem of the dotconfigs directory has enough free
+    /// space to pull the selected configs.
+    ///
+    /// The space needed is estimated as the total size of the local configs,
+    /// minus the size of their current copies in the dotconfigs directory
+    /// which are replaced. Compressed configs and stubs take less space than
+    /// that, so the estimate errs on the safe side.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the estimated space needed in bytes, or an error
+    /// if the available space is lower than that. Non-local dotconfigs paths
+    /// are not checked.
+    pub fn check_free_space(&self) -> Result<u64> {
+        let DotconfigPath::Local(local_dotconfigs_path) = &self.dotconfigs_path else {
+            return Ok(0);
+        };
+        let path = fix_path!(local_dotconfigs_path);
+        let selected = self.selected_config_names()?;
+
+        let needed = self
+            .configs
+            .par_iter()
+            .filter(|dir| is_selected(&selected, dir) && dir.applies_to_current_os())
+            .map(|dir| {
+                let local_size = utils::path_size(fix_path!(dir.primary_path()));
+                let repo_size = dir.repo_path_in(&path).map_or(0, utils::path_size);
+                local_size.saturating_sub(repo_size)
+            })
+            .sum::<u64>();
+
+        let available = utils::available_space(&path)?;
+        if needed > available {
+            return Err(anyhow::anyhow!(
+                "Not enough free space in {:#?}: the configs need about {}, but only {} are available. \
+                 Free up some space, or use --no-space-check to skip this check",
+                path,
+                utils::format_bytes(needed),
+                utils::format_bytes(available)
+            ));
+        }
+
+        Ok(needed)
+    }
+
+    /// Resolve the configs of a profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the profile.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the configs of the profile in the order they are
+    /// listed in the profile, or an error if the profile doesn't exist or
+    /// references an unknown config name.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
+    ///
+    /// let mut dotconfig = DotConfig::new();
+    /// dotconfig.configs = vec![
+    ///     Config::new_file("vimrc", "~/.vimrc"),
+    ///     Config::new_dir("i3", "~/.config/i3"),
+    /// ];
+    /// dotconfig
+    ///     .profiles
+    ///     .insert(String::from("laptop"), vec![String::from("vimrc")]);
+    ///
+    /// let configs = dotconfig.configs_for_profile("laptop").unwrap();
+    /// assert_eq!(configs.len(), 1);
+    /// assert_eq!(configs[0].name, "vimrc");
+    ///
+    /// assert!(dotconfig.configs_for_profile("desktop").is_err());
+    /// ```
+    pub fn configs_for_profile(&self, name: &str) -> Result<Vec<&Config>> {
+        let Some(config_names) = self.profiles.get(name) else {
+            return Err(anyhow::anyhow!("Unknown profile: {:#?}", name));
+        };
+
+        config_names
+            .iter()
+            .map(|config_name| {
+                self.configs
+                    .iter()
+                    .find(|config| &config.name == config_name)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Profile {:#?} references an unknown config: {:#?}",
+                            name,
+                            config_name
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    /// Get the names of the configs selected by the `--profile`, `--name`
+    /// and `--exclude-config` options.
+    ///
+    /// Returns `None` if none of them is given, i.e. all the configs are
+    /// selected. A name that isn't a config (or a config of the selected
+    /// profile) is reported as an error. Excluded configs are left out even
+    /// if they are selected by the profile or name.
+    fn selected_config_names(&self) -> Result<Option<Vec<String>>> {
+        let options = options::get();
+
+        if let Some(name) = options
+            .exclude_config
+            .iter()
+            .find(|name| !self.configs.iter().any(|config| &config.name == *name))
+        {
+            return Err(anyhow::anyhow!("Unknown config: {:#?}", name));
+        }
+
+        let names = self.included_config_names(&options)?;
+        if options.exclude_config.is_empty() {
+            return Ok(names);
+        }
+
+        let names = names.unwrap_or_else(|| {
+            self.configs
+                .iter()
+                .map(|config| config.name.clone())
+                .collect()
+        });
+        Ok(Some(
+            names
+                .into_iter()
+                .filter(|name| !options.exclude_config.contains(name))
+                .collect(),
+        ))
+    }
+
+    /// Get the names of the configs selected by the `--profile` and `--name`
+    /// options, see `selected_config_names`.
+    fn included_config_names(&self, options: &options::SyncOptions) -> Result<Option<Vec<String>>> {
+        let names = match &options.profile {
+            Some(profile) => Some(
+                self.configs_for_profile(profile)?
+                    .iter()
+                    .map(|config| config.name.clone())
+                    .collect::<Vec<String>>(),
+            ),
+            None => None,
+        };
+
+        let Some(name) = options.name.clone() else {
+            return Ok(names);
+        };
+
+        if !self.configs.iter().any(|config| config.name == name) {
+            return Err(anyhow::anyhow!("Unknown config: {:#?}", name));
+        }
+        if names.is_some_and(|names| !names.contains(&name)) {
+            return Err(anyhow::anyhow!(
+                "Config {:#?} is not part of the selected profile",
+                name
+            ));
+        }
+
+        Ok(Some(vec![name]))
+    }
+
+    /// Validate the configuration and find problems that don't prevent it
+    /// from being used.
+    ///
+    /// Configs without a valid `path` or `paths`, invalid config names (see
+    /// `Config::validate_name`), invalid `repo_path`s, configs stored inside
+    /// the stored copy of another config and profiles referencing unknown
+    /// configs are reported as errors. The following are
+    /// reported as warnings:
+    ///
+    /// - Config names that differ only in case, which collide on
+    ///   case-insensitive filesystems (e.g. on macOS or Windows).
+    /// - Files inside directory configs whose names differ only in case.
+    /// - Configs stored at the same path inside the dotconfigs directory
+    ///   (e.g. through their `repo_path`), which overwrite each other on
+    ///   pull.
+    /// - The placeholder config of the default template.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the list of warnings, or an error if the
+    /// configuration is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
+    ///
+    /// let mut dotconfig = DotConfig::new();
+    /// dotconfig.configs = vec![
+    ///     Config::new_file("vim", "~/.vimrc"),
+    ///     Config::new_file("Vim", "~/.vim/vimrc"),
+    /// ];
+    ///
+    /// let warnings = dotconfig.validate().expect("Failed to validate");
+    /// assert_eq!(warnings.len(), 1);
+    /// ```
+    pub fn validate(&self) -> Result<Vec<String>> {
+        self.configs.iter().try_for_each(|config| {
+            config.validate_name()?;
+            config.validate_paths()?;
+            config.validate_repo_path()?;
+            config.validate_ignore()?;
+            config.validate_hash_exclude()
+        })?;
+        self.validate_repo_path_nesting()?;
+
+        self.profiles
+            .keys()
+            .try_for_each(|profile| self.configs_for_profile(profile).map(|_| ()))?;
+
+        let names = self
+            .configs
+            .iter()
+            .map(|config| PathBuf::from(&config.name))
+            .collect::<Vec<PathBuf>>();
+
+        let mut warnings = utils::case_collisions(&names)
+            .into_iter()
+            .map(|(a, b)| {
+                format!(
+                    "Configs {:#?} and {:#?} differ only in case and collide on case-insensitive filesystems",
+                    a, b
+                )
+            })
+            .collect::<Vec<String>>();
+
+        if self.configs.iter().any(Config::is_placeholder) {
+            warnings.push(String::from(
+                "The placeholder config of the default template is still present, replace it with your configs",
+            ));
+        }
+
+        self.configs.iter().for_each(|config| {
+            config.case_collisions().into_iter().for_each(|(a, b)| {
+                warnings.push(format!(
+                    "{:#?} and {:#?} in {:#?} differ only in case and collide on case-insensitive filesystems",
+                    a, b, config.name
+                ));
+            });
+        });
+
+        self.configs
+            .iter()
+            .filter(|config| config.conf_type_mismatch())
+            .for_each(|config| {
+                warnings.push(format!(
+                    "Config {:#?} is stored as a directory, but its path {:#?} looks like a file",
+                    config.name,
+                    config.primary_path()
+                ));
+            });
+
+        self.repo_path_collisions()?
+            .into_iter()
+            .for_each(|(a, b, path)| {
+                warnings.push(format!(
+                    "Configs {:#?} and {:#?} are both stored at {:#?} in the dotconfigs directory and overwrite each other",
+                    a, b, path
+                ));
+            });
+
+        Ok(warnings)
+    }
+
+    /// Check that no config is stored inside the stored copy of another
+    /// config, as resolved by `Config::repo_path_in`, since pulling the
+    /// outer config would remove or overwrite the files of the inner one.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating whether the configs are stored apart, or an error
+    /// naming the first nested pair of configs.
+    fn validate_repo_path_nesting(&self) -> Result<()> {
+        let dotconfigs_path = match &self.dotconfigs_path {
+            DotconfigPath::Local(local_dotconfigs_path) => fix_path!(local_dotconfigs_path),
+            DotconfigPath::Github(_) => PathBuf::new(),
+        };
+
+        let repo_paths = self
+            .configs
+            .iter()
+            .map(|config| Ok((config, config.repo_path_in(&dotconfigs_path)?)))
+            .collect::<Result<Vec<(&Config, PathBuf)>>>()?;
+        for (outer, outer_path) in &repo_paths {
+            for (inner, inner_path) in &repo_paths {
+                if inner_path != outer_path && inner_path.starts_with(outer_path) {
+                    return Err(anyhow::anyhow!(
+                        "Config {:#?} is stored at {:#?}, inside the stored copy of {:#?} at {:#?}",
+                        inner.name,
+                        inner_path,
+                        outer.name,
+                        outer_path
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Find the configs that are stored at the same path inside the
+    /// dotconfigs directory, as resolved by `Config::repo_path_in`.
+    ///
+    /// The paths of a non-local dotconfigs path are resolved relative to
+    /// its root.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the names of every colliding pair of configs and
+    /// their shared path, or an error if a path can't be resolved.
+    fn repo_path_collisions(&self) -> Result<Vec<(String, String, PathBuf)>> {
+        let dotconfigs_path = match &self.dotconfigs_path {
+            DotconfigPath::Local(local_dotconfigs_path) => fix_path!(local_dotconfigs_path),
+            DotconfigPath::Github(_) => PathBuf::new(),
+        };
+
+        let mut destinations: HashMap<PathBuf, &str> = HashMap::new();
+        let mut collisions = Vec::new();
+        for config in &self.configs {
+            let path = config.repo_path_in(&dotconfigs_path)?;
+            match destinations.entry(path) {
+                Entry::Occupied(entry) => collisions.push((
+                    entry.get().to_string(),
+                    config.name.clone(),
+                    entry.key().clone(),
+                )),
+                Entry::Vacant(entry) => {
+                    entry.insert(&config.name);
+                }
+            }
+        }
+
+        Ok(collisions)
+    }
+
+    /// Find the configs that were not synced recently.
+    ///
+    /// A config is stale if its last sync is older than the given threshold.
+    /// Configs that were never synced since the sync time started being
+    /// recorded have no last sync time and are not reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The maximum age of the last sync.
+    ///
+    /// # Returns
+    ///
+    /// A vector of the stale configs, in the order of the configs.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
+    /// use sync_dotfiles_rs::utils::unix_now;
+    /// use std::time::Duration;
+    ///
+    /// let mut nvim = Config::new_dir("nvim", "~/.config/nvim");
+    /// nvim.last_sync = Some(unix_now() - 45 * 24 * 60 * 60);
+    /// let mut vimrc = Config::new_file("vimrc", "~/.vimrc");
+    /// vimrc.last_sync = Some(unix_now());
+    ///
+    /// let mut dotconfig = DotConfig::new();
+    /// dotconfig.configs = vec![nvim, vimrc];
+    ///
+    /// let stale = dotconfig.stale_configs(Duration::from_secs(30 * 24 * 60 * 60));
+    /// assert_eq!(stale.len(), 1);
+    /// assert_eq!(stale[0].name, "nvim");
+    /// ```
+    pub fn stale_configs(&self, threshold: Duration) -> Vec<&Config> {
+        let now = utils::unix_now();
+
+        self.configs
+            .iter()
+            .filter(|config| {
+                config
+                    .last_sync
+                    .is_some_and(|last_sync| now.saturating_sub(last_sync) > threshold.as_secs())
+            })
+            .collect()
+    }
+
+    /// Compare the configs with the configs of another `DotConfig`, e.g.
+    /// the config file of another machine.
+    ///
+    /// The configs are matched by name, and the configs in both are
+    /// compared by their paths.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The `DotConfig` to compare with.
+    ///
+    /// # Returns
+    ///
+    /// The configs added in, removed from and changed in `other`, in the
+    /// order of the configs.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
+    ///
+    /// let mut laptop = DotConfig::new();
+    /// laptop.configs = vec![
+    ///     Config::new_file("vimrc", "~/.vimrc"),
+    ///     Config::new_dir("nvim", "~/.config/nvim"),
+    /// ];
+    ///
+    /// let mut desktop = DotConfig::new();
+    /// desktop.configs = vec![
+    ///     Config::new_dir("nvim", "~/dotfiles/nvim"),
+    ///     Config::new_dir("i3", "~/.config/i3"),
+    /// ];
+    ///
+    /// let diff = laptop.diff(&desktop);
+    /// assert_eq!(diff.added[0].name, "i3");
+    /// assert_eq!(diff.removed[0].name, "vimrc");
+    /// assert_eq!(diff.changed[0].name, "nvim");
+    /// assert_eq!(diff.changed[0].other_path, "~/dotfiles/nvim");
+    /// ```
+    pub fn diff(&self, other: &DotConfig) -> ConfigDiff {
+        let find = |configs: &[Config], name: &str| {
+            configs
+                .iter()
+                .find(|config| config.name == name)
+                .map(|config| config.destinations().join(", "))
+        };
+        let entry = |config: &Config| DiffEntry {
+            name: config.name.clone(),
+            path: config.destinations().join(", "),
+        };
+
+        let mut diff = ConfigDiff::default();
+        self.configs
+            .iter()
+            .for_each(|config| match find(&other.configs, &config.name) {
+                None => diff.removed.push(entry(config)),
+                Some(other_path) => {
+                    let path = config.destinations().join(", ");
+                    if path != other_path {
+                        diff.changed.push(ChangedEntry {
+                            name: config.name.clone(),
+                            path,
+                            other_path,
+                        });
+                    }
+                }
+            });
+        diff.added = other
+            .configs
+            .iter()
+            .filter(|config| find(&self.configs, &config.name).is_none())
+            .map(entry)
+            .collect();
+
+        diff
+    }
+
+    /// Get the absolute path of a config inside the dotconfigs directory,
+    /// resolved the same way as when the config is pushed.
+    ///
+    /// The path is resolved whether or not the config exists in the
+    /// dotconfigs directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the config.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the path, or an error if there is no config with
+    /// that name or the dotconfigs path is not local.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use sync_dotfiles_rs::dotconfig::{DotConfig, DotconfigPath};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut dotconfig = DotConfig::new();
+    /// dotconfig.dotconfigs_path = DotconfigPath::Local(String::from("/tmp/dotfiles"));
+    /// dotconfig.configs = vec![Config::new_dir("nvim", "~/.config/nvim")];
+    ///
+    /// assert_eq!(
+    ///     dotconfig.resolve_repo_path("nvim").unwrap(),
+    ///     PathBuf::from("/tmp/dotfiles/nvim")
+    /// );
+    /// assert!(dotconfig.resolve_repo_path("zsh").is_err());
+    ///
+    /// dotconfig.mirror_home = true;
+    /// assert_eq!(
+    ///     dotconfig.resolve_repo_path("nvim").unwrap(),
+    ///     PathBuf::from("/tmp/dotfiles/.config/nvim")
+    /// );
+    /// ```
+    pub fn resolve_repo_path(&mut self, name: &str) -> Result<PathBuf> {
+        self.propagate_settings();
+        let Some(config) = self.configs.iter().find(|config| config.name == name) else {
+            return Err(anyhow::anyhow!("No config named {:#?}", name));
+        };
+        let DotconfigPath::Local(local_dotconfigs_path) = &self.dotconfigs_path else {
+            return Err(anyhow::anyhow!(
+                "Cannot resolve non-local dotconfigs path: {}",
+                self.dotconfigs_path
+            ));
+        };
+
+        let path = config.repo_path_in(&fix_path!(local_dotconfigs_path))?;
+
+        Ok(std::path::absolute(path)?)
+    }
+
+    /// Write the copy of a config in the dotconfigs directory to a writer,
+    /// without pushing it.
+    ///
+    /// The contents of a file config are written as they are. For a
+    /// directory config, the paths of its files are listed instead, one per
+    /// line and relative to the directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the config.
+    /// * `writer` - The writer the config is written to, e.g. stdout.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success, or an error if there is no config with
+    /// that name, the dotconfigs path is not local, or the config is missing
+    /// from the dotconfigs directory or stored as a compressed archive.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use sync_dotfiles_rs::dotconfig::{DotConfig, DotconfigPath};
+    /// use std::fs;
+    ///
+    /// let path = std::env::temp_dir().join("sync-dotfiles-doctest-cat");
+    /// let _ = fs::remove_dir_all(&path);
+    /// fs::create_dir_all(path.join("nvim/lua")).unwrap();
+    /// fs::write(path.join("nvim/lua/init.lua"), "").unwrap();
+    /// fs::write(path.join("nvim/init.lua"), "").unwrap();
+    /// fs::write(path.join("starship.toml"), "format = '$all'").unwrap();
+    ///
+    /// let mut dotconfig = DotConfig::new();
+    /// dotconfig.dotconfigs_path = DotconfigPath::Local(path.display().to_string());
+    /// dotconfig.configs = vec![
+    ///     Config::new_dir("nvim", "~/.config/nvim"),
+    ///     Config::new_file("starship", "~/.config/starship.toml"),
+    /// ];
+    /// dotconfig.configs[1].repo_path = Some(String::from("starship.toml"));
+    ///
+    /// let mut output = Vec::new();
+    /// dotconfig.cat_config("starship", &mut output).unwrap();
+    /// assert_eq!(output, b"format = '$all'");
+    ///
+    /// let mut output = Vec::new();
+    /// dotconfig.cat_config("nvim", &mut output).unwrap();
+    /// assert_eq!(String::from_utf8(output).unwrap(), "init.lua\nlua/init.lua\n");
+    ///
+    /// assert!(dotconfig.cat_config("zsh", &mut Vec::new()).is_err());
+    ///
+    /// fs::remove_dir_all(&path).unwrap();
+    /// ```
+    pub fn cat_config<W>(&mut self, name: &str, writer: &mut W) -> Result<()>
+    where
+        W: Write,
+    {
+        let path = self.resolve_repo_path(name)?;
+        let config = self.configs.iter().find(|config| config.name == name);
+        if config.is_some_and(Config::is_compressed) && path.is_file() {
+            return Err(anyhow::anyhow!(
+                "Config {:#?} is stored as a compressed archive: {:#?}",
+                name,
+                path
+            ));
+        }
+
+        if path.is_file() {
+            io::copy(&mut fs::File::open(&path)?, writer)?;
+        } else if path.is_dir() {
+            // List the files the same way they are walked on pull
+            walkdir::WalkDir::new(&path)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+                .filter_map(|e| e.path().strip_prefix(&path).ok().map(Path::to_path_buf))
+                .filter(|relative| !utils::is_git_path(relative))
+                .try_for_each(|relative| writeln!(writer, "{}", relative.display()))?;
+        } else {
+            return Err(anyhow::anyhow!(
+                "Config {:#?} does not exist in the dotconfigs directory: {:#?}",
+                name,
+                path
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Resolve the paths of all the configs without syncing them.
+    ///
+    /// For every path of every config, this lists the raw path, the path it
+    /// resolves to (after `~` expansion and the `/home/<username>` rewrite),
+    /// the path of the config inside the dotconfigs directory and the type of
+    /// the config. This is meant for diagnosing path issues.
+    ///
+    /// # Returns
+    ///
+    /// A vector of the resolved entries, in the order of the configs.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::Config;
+    /// use sync_dotfiles_rs::dotconfig::{DotConfig, DotconfigPath};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut dotconfig = DotConfig::new();
+    /// dotconfig.dotconfigs_path = DotconfigPath::Local(String::from("/tmp/dotfiles"));
+    /// dotconfig.configs = vec![Config::new_file("vimrc", "~/.vimrc")];
+    ///
+    /// let entries = dotconfig.debug_resolution();
+    /// assert_eq!(entries[0].path, "~/.vimrc");
+    /// assert_eq!(entries[0].dotconfigs_path, Some(PathBuf::from("/tmp/dotfiles/.vimrc")));
+    /// ```
+    pub fn debug_resolution(&mut self) -> Vec<ResolvedEntry> {
+        self.propagate_settings();
+        self.configs
+            .iter()
+            .flat_map(|config| {
+                let dotconfigs_path = match &self.dotconfigs_path {
+                    DotconfigPath::Local(local_dotconfigs_path) => {
+                        config.repo_path_in(&fix_path!(local_dotconfigs_path)).ok()
+                    }
+                    DotconfigPath::Github(_) => None,
+                };
+
+                config
+                    .destinations()
+                    .into_iter()
+                    .map(|path| {
+                        let resolved_path = fix_path!(path);
+
+                        // Prefer the type on disk, so a stale conf_type shows up
+                        let conf_type = if resolved_path.is_dir() {
+                            Some(ConfType::Dir)
+                        } else if resolved_path.is_file() {
+                            Some(ConfType::File)
+                        } else {
+                            config.conf_type.clone()
+                        };
+
+                        ResolvedEntry {
+                            name: config.name.clone(),
+                            path: path.to_string(),
+                            resolved_path,
+                            dotconfigs_path: dotconfigs_path.clone(),
+                            conf_type,
+                        }
+                    })
+                    .collect::<Vec<ResolvedEntry>>()
+            })
+            .collect()
+    }
+
+    /// Add a new configuration to the `DotConfig` structure.
+    ///
+    /// This method adds a new configuration to the `DotConfig` structure.
+    /// It creates a new `Config` struct with the specified name and path and
+    /// appends it to the list of configurations. It also checks if a
+    /// configuration with the same name already exists to prevent duplicates.
+    ///
+    /// With `overwrite`, an existing configuration with the same name is
+    /// updated with the new path instead (see `Config::set_path`). Its hash
+    /// is cleared, so it is synced again from the new path.
+    ///
+    /// The placeholder configuration of the default template is removed, so
+    /// it isn't synced along with the first real configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A reference to a String representing the name of the
+    ///   new configuration.
+    /// * `path` - A reference to a Path representing the path of the
+    ///   new configuration.
+    /// * `overwrite` - Whether to update an existing configuration with the
+    ///   same name instead of failing.
+    /// * `conf_type` - The type of the new configuration, used instead of
+    ///   detecting it from the path (e.g. when the path doesn't exist yet).
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or an error if the addition fails due to
+    /// a duplicate or invalid name (see `Config::validate_name`) or other
+    /// issues.
+    pub fn add_config(
+        &mut self,
+        name: &String,
+        path: PathBuf,
+        overwrite: bool,
+        conf_type: Option<ConfType>,
+    ) -> Result<()> {
+        let path_str = path.to_string_lossy().to_string();
+
+        if let Some(existing) = self.configs.iter_mut().find(|dir| &dir.name == name) {
+            if !overwrite {
+                return Err(anyhow::anyhow!(
+                    "Config with name {name:#?} already exists."
+                ));
+            }
+
+            existing.set_path(path_str)?;
+            if conf_type.is_some() {
+                existing.conf_type = conf_type;
+            }
+
+            return Ok(());
+        }
+
+        let mut config = Config::try_from_path(&path, Some(name.to_string()))?;
+        if conf_type.is_some() {
+            config.conf_type = conf_type;
+        }
+
+        self.configs.retain(|config| !config.is_placeholder());
+        self.configs.push(config);
+
+        Ok(())
+    }
+
+    /// Rename a configuration, keeping its metadata (e.g. its hash).
+    ///
+    /// The copy of the config inside the local dotconfigs directory is moved
+    /// along if it is stored under the config name, as directory configs and
+    /// files outside of the home directory root are. Files stored under
+    /// their file name (e.g. `.vimrc`) or at a custom `repo_path` stay where
+    /// they are. The config is renamed in the profiles as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `old` - The name of the config to be renamed.
+    /// * `new` - The new name of the config.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success, or an error if there is no config named
+    /// `old`, a config named `new` already exists, the new name is invalid
+    /// (see `Config::validate_name`) or the stored config fails to be moved.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::{config::Config, dotconfig::DotConfig};
+    ///
+    /// let mut vimrc = Config::new_file("vimrc", "~/.vimrc");
+    /// vimrc.hash = Some(String::from("abcd1234"));
+    ///
+    /// let mut dotconfig = DotConfig::new();
+    /// *dotconfig.configs_mut() = vec![vimrc, Config::new_file("zshrc", "~/.zshrc")];
+    ///
+    /// dotconfig.rename_config("vimrc", "vim").unwrap();
+    /// assert_eq!(dotconfig.configs()[0].name, "vim");
+    /// assert_eq!(dotconfig.configs()[0].hash.as_deref(), Some("abcd1234"));
+    ///
+    /// assert!(dotconfig.rename_config("vim", "zshrc").is_err());
+    /// assert!(dotconfig.rename_config("nvim", "neovim").is_err());
+    /// ```
+    pub fn rename_config(&mut self, old: &str, new: &str) -> Result<()> {
+        self.propagate_settings();
+        if self.configs.iter().any(|dir| dir.name == new) {
+            return Err(anyhow::anyhow!("Config with name {new:#?} already exists."));
+        }
+
+        let Some(dir) = self.configs.iter_mut().find(|dir| dir.name == old) else {
+            return Err(anyhow::anyhow!("No config named {:#?}", old));
+        };
+
+        let mut renamed = dir.clone();
+        renamed.name = new.to_string();
+        renamed.validate_name()?;
+
+        if let DotconfigPath::Local(local_dotconfigs_path) = &self.dotconfigs_path {
+            let dotconfigs_path = fix_path!(local_dotconfigs_path);
+            let old_path = dir.repo_path_in(&dotconfigs_path)?;
+            let new_path = renamed.repo_path_in(&dotconfigs_path)?;
+            move_stored_config(&dotconfigs_path, &old_path, &new_path)?;
+        }
+
+        *dir = renamed;
+        self.profiles.values_mut().for_each(|names| {
+            names
+                .iter_mut()
+                .filter(|name| name.as_str() == old)
+                .for_each(|name| *name = new.to_string());
+        });
+
+        info!("Renamed {:#?} to {:#?}", old, new);
+        Ok(())
+    }
+
+    /// Import the packages of a GNU Stow directory as a `DotConfig`.
+    ///
+    /// Every package (a directory inside the stow directory) mirrors the home
+    /// directory, and each of its top-level entries is imported as a config
+    /// with its path inside the home directory. The entries of directories
+    /// shared between packages (e.g. `.config` or `.local/share`) are
+    /// imported as separate configs instead. Stow's `dot-` prefixes are
+    /// translated to `.`.
+    ///
+    /// A package with a single entry is named after the package, otherwise
+    /// the configs are named `<package>-<entry>`.
+    ///
+    /// The stow directory is used as the dotconfigs directory, with the
+    /// `repo_path` of every config pointing inside its package, so the files
+    /// don't have to be moved.
+    ///
+    /// # Arguments
+    ///
+    /// * `stow_dir` - The path to the stow directory containing the packages.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the imported `DotConfig`, or an error if the stow
+    /// directory can't be read.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
+    ///
+    /// match DotConfig::from_stow("~/dotfiles") {
+    ///     Ok(dotconfig) => println!("{}", dotconfig),
+    ///     Err(err) => eprintln!("Error importing the stow directory: {:?}", err),
+    /// }
+    /// ```
+    pub fn from_stow<P>(stow_dir: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let stow_dir = fix_path!(stow_dir.as_ref().to_path_buf());
+        if !stow_dir.is_dir() {
+            return Err(anyhow::anyhow!(
+                "Stow directory does not exist: {:#?}",
+                stow_dir
+            ));
+        }
+
+        // every non-hidden directory inside the stow directory is a package
+        let mut packages = fs::read_dir(&stow_dir)
+            .with_context(|| format!("Failed to read the stow directory {:#?}", stow_dir))?
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_dir() && !e.file_name().to_string_lossy().starts_with('.'))
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect::<Vec<String>>();
+        packages.sort();
+
+        let mut configs = Vec::new();
+        for package in packages {
+            let package_path = stow_dir.join(&package);
+            let entries = stow_package_entries(&package_path, Path::new(""))?;
+            let single_entry = entries.len() == 1;
+
+            for entry in entries {
+                let home_path = stow_home_path(&entry);
+                let name = if single_entry {
+                    package.clone()
+                } else {
+                    let entry_name = home_path.file_name().unwrap_or_default().to_string_lossy();
+                    format!("{package}-{}", entry_name.trim_start_matches('.'))
+                };
+                let path = format!("~/{}", home_path.display());
+
+                let mut config = if package_path.join(&entry).is_dir() {
+                    Config::new_dir(name, path)
+                } else {
+                    Config::new_file(name, path)
+                };
+                config.repo_path = Some(Path::new(&package).join(&entry).display().to_string());
+
+                configs.push(config);
+            }
+        }
+
+        Ok(DotConfig {
+            dotconfigs_path: DotconfigPath::Local(stow_dir.display().to_string()),
+            configs,
+            ..Default::default()
+        })
+    }
+
+    /// Create a new `DotConfig` instance with default template.
+    ///
+    /// This method constructs a new `DotConfig` structure with default
+    /// settings. It initializes the `dotconfigs_path` with the default
+    /// dotfiles directory path and includes a single default
+    /// configuration in the `configs` vector.
+    ///
+    /// # Returns
+    ///
+    /// A `DotConfig` struct with default settings.
+    /// Create a new dotconfig file with default template
+    pub fn new() -> Self {
+        DotConfig::default()
+    }
+
+    /// Create a new `DotConfig` instance with a config for every well-known
+    /// dotfile (e.g. `~/.bashrc` or `~/.config/nvim`) found in the home
+    /// directory.
+    ///
+    /// Only the dotfiles that exist are added, as a directory or file config
+    /// depending on what is found. The default template is returned if none
+    /// of them exist.
+    ///
+    /// # Returns
+    ///
+    /// A `DotConfig` struct with the detected configs, or an error if the
+    /// home directory can't be found.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::config::ConfType;
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
+    /// use sync_dotfiles_rs::options::{self, SyncOptions};
+    ///
+    /// let home = std::env::temp_dir().join("sync-dotfiles-doctest-autodetect");
+    /// let _ = std::fs::remove_dir_all(&home);
+    /// std::fs::create_dir_all(home.join(".config/nvim")).unwrap();
+    /// std::fs::write(home.join(".bashrc"), "alias ll='ls -l'\n").unwrap();
+    ///
+    /// options::set(SyncOptions {
+    ///     home: Some(home.clone()),
+    ///     ..Default::default()
+    /// });
+    ///
+    /// let dotconfig = DotConfig::autodetect().expect("Failed to find the home directory");
+    /// let configs = dotconfig
+    ///     .configs
+    ///     .iter()
+    ///     .map(|c| (c.name.as_str(), c.path.as_str(), c.conf_type.clone()))
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(
+    ///     configs,
+    ///     vec![
+    ///         ("bashrc", "~/.bashrc", Some(ConfType::File)),
+    ///         ("nvim", "~/.config/nvim", Some(ConfType::Dir)),
+    ///     ]
+    /// );
+    ///
+    /// std::fs::remove_dir_all(&home).unwrap();
+    /// ```
+    pub fn autodetect() -> Result<Self> {
+        let home_dir = utils::home_dir()?;
+
+        let configs = AUTODETECT_DOTFILES
+            .iter()
+            .filter_map(|(name, path)| {
+                let local_path = home_dir.join(path);
+                let path = format!("~/{path}");
+
+                if local_path.is_dir() {
+                    Some(Config::new_dir(*name, path))
+                } else if local_path.exists() {
+                    Some(Config::new_file(*name, path))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<Config>>();
+
+        if configs.is_empty() {
+            return Ok(DotConfig::new());
+        }
+
+        Ok(DotConfig {
+            configs,
+            ..Default::default()
+        })
+    }
+
+    /// Create a new config file with the default template, e.g. when no
+    /// config file is found on the first run.
+    ///
+    /// The template is written in the format of the file extension, and the
+    /// missing parent directories of the config file are created.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the config file to be created.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success, or an error if the config file already
+    /// exists or fails to be written.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
+    ///
+    /// let dir = std::env::temp_dir().join("sync-dotfiles-doctest-create-config");
+    /// let _ = std::fs::remove_dir_all(&dir);
+    /// let path = dir.join("sync-dotfiles/config.ron");
+    ///
+    /// DotConfig::create_config_file(&path).expect("Failed to create config");
+    ///
+    /// let dotconfig = DotConfig::from_file(&path).expect("Failed to parse config");
+    /// assert_eq!(dotconfig.configs.len(), DotConfig::new().configs.len());
+    /// assert!(DotConfig::create_config_file(&path).is_err());
+    /// ```
+    pub fn create_config_file<P>(path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if path.exists() {
+            return Err(anyhow::anyhow!(
+                "Config file {} already exists",
+                path.display()
+            ));
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create the config directory")?;
+        }
+
+        let config = ConfigFormat::of_file(path)
+            .serialize(&DotConfig::new())
+            .context("Failed to serialize config")?;
+
+        write_config_file(path, &config).context("Failed to write to config file")
+    }
+
+    /// Generate the JSON Schema of the `sync-dotfiles` configuration format.
+    ///
+    /// The schema describes the `DotConfig`, `Config`, `ConfType` and
+    /// `DotconfigPath` structures, and can be used by editors to validate
+    /// and autocomplete the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the pretty-printed JSON Schema, or an error if the
+    /// schema fails to serialize.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
+    ///
+    /// let schema = DotConfig::json_schema().expect("Failed to generate schema");
+    /// assert!(schema.contains("dotconfigs_path"));
+    /// ```
+    pub fn json_schema() -> Result<String> {
+        let schema = schemars::schema_for!(DotConfig);
+
+        serde_json::to_string_pretty(&schema).context("Failed to serialize the JSON schema")
+    }
+
+    /// Get a one-line summary of the effective runtime configuration, e.g.
+    /// to include in bug reports.
+    ///
+    /// The summary lists the config file in use and its format, the hash
+    /// algorithm and change detection mode, the dotconfigs directory or
+    /// repository, and the number of threads used to sync the configs.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
+    ///
+    /// let path = Some(format!("{}/examples/config.ron", env!("CARGO_MANIFEST_DIR")));
+    /// let dotconfig = DotConfig::parse_dotconfig(&path).expect("Failed to parse config");
+    ///
+    /// let summary = dotconfig.runtime_summary();
+    /// assert!(summary.starts_with(&format!("Config: {} (RON)", path.unwrap())));
+    /// assert!(summary.contains("hash: sha1 (content hash)"));
+    /// assert!(summary.contains("dotconfigs: local "));
+    /// ```
+    pub fn runtime_summary(&self) -> String {
+        let config_path = CONFIG_PATH.lock().unwrap().clone();
+        let format = match config_path.is_dir() {
+            true => String::from("RON fragments"),
+            false => ConfigFormat::of_file(&config_path).to_string(),
+        };
+        let change_detection = match self.change_detection {
+            ChangeDetection::ContentHash => "content hash",
+            ChangeDetection::MetadataStamp => "metadata stamp",
+        };
+        let dotconfigs = match &self.dotconfigs_path {
+            DotconfigPath::Local(path) => format!("local {}", fix_path!(path).display()),
+            DotconfigPath::Github(url) => format!("git {url}"),
+        };
+
+        format!(
+            "Config: {} ({format}), hash: {} ({change_detection}), dotconfigs: {dotconfigs}, jobs: {}",
+            std::path::absolute(&config_path)
+                .unwrap_or(config_path)
+                .display(),
+            self.hash_algorithm.prefix(),
+            rayon::current_num_threads()
+        )
+    }
+
+    /// Edit the `sync-dotfiles` configuration file.
+    ///
+    /// This method opens the `sync-dotfiles` configuration file in the
+    /// editor specified by the `EDITOR` environment variable.
+    /// If the `EDITOR` environment variable is not set, it will open the
+    /// `sync-dotfiles` configuration file in the vim editor.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or an error if the editor fails to open.
+    pub fn edit_config_file(&self) -> Result<()> {
+        let editor: String = std::env::var("EDITOR").unwrap_or("vim".into());
+
+        process::Command::new(editor)
+            .arg(CONFIG_PATH.lock().unwrap().as_path())
+            .status()
+            .context("Failed to open the editor")?;
+
+        Ok(())
+    }
+
+    /// Get the configs of the dotconfig.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::DotConfig;
+    ///
+    /// let dotconfig = DotConfig::new();
+    /// assert_eq!(dotconfig.configs().len(), 1);
+    /// ```
+    pub fn configs(&self) -> &[Config] {
+        &self.configs
+    }
+
+    /// Get a mutable reference to the configs of the dotconfig.
+    pub fn configs_mut(&mut self) -> &mut Vec<Config> {
+        &mut self.configs
+    }
+}
+
+/// Iterate over the configs of a `DotConfig`.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::config::Config;
+/// use sync_dotfiles_rs::dotconfig::DotConfig;
+///
+/// let mut dotconfig = DotConfig::new();
+/// *dotconfig.configs_mut() = vec![Config::new_file("vim", "~/.vimrc")];
+///
+/// let mut names = Vec::new();
+/// for config in &dotconfig {
+///     names.push(config.name.clone());
+/// }
+/// assert_eq!(names, ["vim"]);
+/// ```
+impl<'a> IntoIterator for &'a DotConfig {
+    type Item = &'a Config;
+    type IntoIter = std::slice::Iter<'a, Config>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.configs.iter()
+    }
+}
+
+/// Display implementation for DotConfig.
+///
+/// This implementation allows you to print a human-readable representation
+/// of a `DotConfig` instance.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::dotconfig::DotConfig;
+///
+/// let dotconfig = DotConfig::new();
+/// println!("{}", dotconfig);
+/// ```
+impl fmt::Display for DotConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "DotConfig {{")?;
+        writeln!(f, "    dotconfigs_path: {},", self.dotconfigs_path)?;
+        writeln!(f, "    configs: [")?;
+        self.configs.iter().for_each(|config| {
+            writeln!(f, "        {config},").expect("Failed to display config");
+        });
+        writeln!(f, "    ],")?;
+        if !self.change_detection.is_content_hash() {
+            writeln!(f, "    change_detection: {:?},", self.change_detection)?;
+        }
+        if !self.hash_algorithm.is_sha1() {
+            writeln!(f, "    hash_algorithm: {:?},", self.hash_algorithm)?;
+        }
+        if !self.profiles.is_empty() {
+            writeln!(f, "    profiles: {:?},", self.profiles)?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+/// Display implementation for DotconfigPath.
+///
+/// This implementation allows you to print a human-readable representation
+/// of a `DotconfigPath` instance.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::dotconfig::DotconfigPath;
+///
+/// let dotconfig_path = DotconfigPath::Local("path/to/dotconfigs".to_string());
+/// println!("{}", dotconfig_path);
+/// ```
+impl fmt::Display for DotconfigPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DotconfigPath::Local(local_dotconfigs_path) => {
+                write!(f, "{local_dotconfigs_path}")
+            }
+            DotconfigPath::Github(remote_dotconfigs_path) => {
+                write!(f, "{remote_dotconfigs_path}")
+            }
+        }
+    }
+}
+
+/// FromStr implementation for DotconfigPath.
+///
+/// Git URLs (e.g. `https://...`, `ssh://...` or `git@host:user/repo`) are
+/// parsed as remote repositories, and everything else as a local directory.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::dotconfig::DotconfigPath;
+///
+/// let remote: DotconfigPath = "https://github.com/user/repo".parse().unwrap();
+/// assert!(matches!(remote, DotconfigPath::Github(_)));
+///
+/// let local: DotconfigPath = "~/dotfiles".parse().unwrap();
+/// assert!(matches!(local, DotconfigPath::Local(_)));
+/// ```
+impl FromStr for DotconfigPath {
+    type Err = anyhow::Error;
+
+    fn from_str(path: &str) -> Result<Self> {
+        if path.is_empty() {
+            return Err(anyhow::anyhow!("The dotconfigs path is empty"));
+        }
+
+        let is_url = ["https://", "http://", "ssh://", "git://", "git@"]
+            .iter()
+            .any(|prefix| path.starts_with(prefix));
+
+        if is_url {
+            Ok(DotconfigPath::Github(path.to_string()))
+        } else {
+            Ok(DotconfigPath::Local(path.to_string()))
+        }
+    }
+}
+
+/// Enum representing the file format of the sync-dotfiles config file.
+///
+/// The format is detected from the extension of the config file (`.toml`,
+/// `.json`, and RON for everything else), unless it is forced with the
+/// `--config-format` option.
+///
+/// # Example
+///
+/// ```rust
+/// use std::path::Path;
+/// use sync_dotfiles_rs::dotconfig::ConfigFormat;
+///
+/// assert_eq!(ConfigFormat::detect(Path::new("config.toml")), ConfigFormat::Toml);
+/// assert_eq!(ConfigFormat::detect(Path::new("config.ron")), ConfigFormat::Ron);
+/// assert_eq!(ConfigFormat::detect(Path::new("config.toml.gz")), ConfigFormat::Toml);
+/// assert_eq!("json".parse::<ConfigFormat>().unwrap(), ConfigFormat::Json);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// Rusty Object Notation (the default format).
+    Ron,
+    /// Tom's Obvious Minimal Language.
+    Toml,
+    /// JavaScript Object Notation.
+    Json,
+}
+
+impl ConfigFormat {
+    /// Detect the format of a config file from its extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the config file.
+    ///
+    /// # Returns
+    ///
+    /// The format matching the extension, RON for unknown extensions.
+    pub fn detect(path: &Path) -> Self {
+        // The format of a compressed config file is that of the compressed file
+        let path = match is_gzipped(path) {
+            true => Path::new(path.file_stem().unwrap_or_default()),
+            false => path,
+        };
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => ConfigFormat::Toml,
+            Some("json") => ConfigFormat::Json,
+            _ => ConfigFormat::Ron,
+        }
+    }
+
+    /// Get the format of a config file, which is the format forced with
+    /// `--config-format` if any, otherwise the detected one.
+    fn of_file(path: &Path) -> Self {
+        options::get()
+            .config_format
+            .unwrap_or_else(|| Self::detect(path))
+    }
+
+    /// Parse a config in this format.
+    fn parse(self, content: &str) -> Result<DotConfig> {
+        Ok(match self {
+            ConfigFormat::Ron => Options::default()
+                .with_default_extension(Extensions::IMPLICIT_SOME)
+                .from_str(content)?,
+            ConfigFormat::Toml => toml::from_str(content)?,
+            ConfigFormat::Json => serde_json::from_str(content)?,
+        })
+    }
+
+    /// Get the block of comments at the beginning of a config file in this
+    /// format, e.g. a description of the config written by the user.
+    ///
+    /// Blank lines and RON extension attributes (`#![enable(...)]`) before
+    /// and between the comments are skipped. JSON doesn't support comments.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The content of the config file.
+    ///
+    /// # Returns
+    ///
+    /// The comment lines with a trailing new line, or `None` if the file
+    /// doesn't start with comments.
+    fn comment_header(self, content: &str) -> Option<String> {
+        let prefix = match self {
+            ConfigFormat::Ron => "//",
+            ConfigFormat::Toml => "#",
+            ConfigFormat::Json => return None,
+        };
+
+        let header = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with("#!["))
+            .take_while(|line| line.starts_with(prefix))
+            .map(|line| format!("{line}\n"))
+            .collect::<String>();
+
+        (!header.is_empty()).then_some(header)
+    }
+
+    /// Serialize a config in this format.
+    fn serialize(self, config: &DotConfig) -> Result<String> {
+        Ok(match self {
+            ConfigFormat::Ron => to_string_pretty(config, get_ron_formatter())?,
+            ConfigFormat::Toml => toml::to_string_pretty(config)?,
+            ConfigFormat::Json => serde_json::to_string_pretty(config)?,
+        })
+    }
+}
+
+/// FromStr implementation for ConfigFormat.
+///
+/// The format is parsed from its lowercase name (`ron`, `toml` or `json`).
+impl FromStr for ConfigFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(format: &str) -> Result<Self> {
+        match format {
+            "ron" => Ok(ConfigFormat::Ron),
+            "toml" => Ok(ConfigFormat::Toml),
+            "json" => Ok(ConfigFormat::Json),
+            _ => Err(anyhow::anyhow!(
+                "Unknown config format {format:?}, expected one of ron, toml or json"
+            )),
+        }
+    }
+}
+
+/// Display implementation for ConfigFormat.
+///
+/// The format is displayed by its name, e.g. `RON`.
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigFormat::Ron => write!(f, "RON"),
+            ConfigFormat::Toml => write!(f, "TOML"),
+            ConfigFormat::Json => write!(f, "JSON"),
+        }
+    }
+}
+
+/// Debug implementation for DotconfigPath.
+///
+/// This implementation allows you to print a human-readable representation
+/// of a `DotconfigPath` instance.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::dotconfig::DotconfigPath;
+///
+/// let dotconfig_path = DotconfigPath::Github(
+///         "https://github.com/user/repo".to_string());
+/// println!("{:?}", dotconfig_path);
+/// ```
+impl fmt::Debug for DotconfigPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DotconfigPath::Local(local_dotconfigs_path) => {
+                write!(f, "{local_dotconfigs_path}")
+            }
+            DotconfigPath::Github(remote_dotconfigs_path) => {
+                write!(f, "{remote_dotconfigs_path}")
+            }
+        }
+    }
+}
+
+/// Default implementation for DotConfig.
+///
+/// This implementation creates a new `DotConfig` instance with default
+/// settings.
+impl Default for DotConfig {
+    fn default() -> Self {
+        DotConfig {
+            dotconfigs_path: DotconfigPath::Local(String::from("~/dotfiles")),
+            configs: vec![Config::default()],
+            change_detection: ChangeDetection::default(),
+            hash_algorithm: HashAlgorithm::default(),
+            profiles: BTreeMap::new(),
+            lfs_threshold: None,
+            mirror_home: false,
+            trim_trailing_newline: false,
+            host_state: false,
+            saved_dotconfigs_path: None,
+            fragments: None,
+        }
+    }
+}
+
+/// Summary of a bidirectional sync performed by `DotConfig::sync_configs`,
+/// also used for the one-way pull and push operations.
+///
+/// # Exit codes
+///
+/// The summary maps to the exit code of the `sync` command:
+///
+/// - `0`: All the configs are in sync (whether or not anything changed).
+/// - `1`: At least one config failed to sync.
+/// - `2`: At least one config has a conflict, and none failed.
+#[derive(Debug, Default)]
+pub struct SyncSummary {
+    /// Names of the configs pulled into the dotconfigs directory.
+    pub pulled: Vec<String>,
+    /// Names of the configs pushed to the local system.
+    pub pushed: Vec<String>,
+    /// Names of the configs changed both locally and in the dotconfigs
+    /// directory since the last sync.
+    pub conflicts: Vec<String>,
+    /// Names of the configs that failed to sync, along with the error.
+    pub errors: Vec<(String, String)>,
+    /// Names of the configs that were skipped, along with the reason.
+    pub skipped: Vec<(String, SkipReason)>,
+    /// Files copied in either direction by the operation.
+    pub copied: CopyReport,
+}
+
+impl SyncSummary {
+    /// Get the exit code of the `sync` command for this summary.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::SyncSummary;
+    ///
+    /// let mut summary = SyncSummary::default();
+    /// assert_eq!(summary.exit_code(), 0);
+    ///
+    /// summary.conflicts.push(String::from("nvim"));
+    /// assert_eq!(summary.exit_code(), 2);
+    /// ```
+    pub fn exit_code(&self) -> i32 {
+        if !self.errors.is_empty() {
+            1
+        } else if !self.conflicts.is_empty() {
+            2
+        } else {
+            0
+        }
+    }
+
+    /// Get the names of the configs grouped by their outcome, e.g.
+    /// `Pulled: nvim, vimrc`, with one line per non-empty group.
+    ///
+    /// The skipped configs are grouped by the reason they were skipped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::dotconfig::{SkipReason, SyncSummary};
+    ///
+    /// let mut summary = SyncSummary::default();
+    /// summary.pulled = vec![String::from("nvim"), String::from("vimrc")];
+    /// summary.skipped = vec![
+    ///     (String::from("zsh"), SkipReason::UpToDate),
+    ///     (String::from("i3"), SkipReason::Missing),
+    ///     (String::from("git"), SkipReason::UpToDate),
+    /// ];
+    ///
+    /// assert_eq!(
+    ///     summary.grouped_report(),
+    ///     "Pulled: nvim, vimrc\n\
+    ///      Skipped (already up-to date): zsh, git\n\
+    ///      Skipped (does not exist): i3"
+    /// );
+    /// ```
+    pub fn grouped_report(&self) -> String {
+        let mut skipped: Vec<(SkipReason, Vec<&str>)> = Vec::new();
+        self.skipped.iter().for_each(|(name, reason)| {
+            match skipped.iter_mut().find(|(group, _)| group == reason) {
+                Some((_, names)) => names.push(name),
+                None => skipped.push((*reason, vec![name])),
+            }
+        });
+
+        let errors = self
+            .errors
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<&str>>();
+
+        [
+            (
+                String::from("Pulled"),
+                self.pulled.iter().map(String::as_str).collect(),
+            ),
+            (
+                String::from("Pushed"),
+                self.pushed.iter().map(String::as_str).collect(),
+            ),
+            (
+                String::from("Conflicts"),
+                self.conflicts.iter().map(String::as_str).collect(),
+            ),
+        ]
+        .into_iter()
+        .chain(
+            skipped
+                .into_iter()
+                .map(|(reason, names)| (format!("Skipped ({reason})"), names)),
+        )
+        .chain(std::iter::once((String::from("Errors"), errors)))
+        .filter(|(_, names): &(String, Vec<&str>)| !names.is_empty())
+        .map(|(group, names)| format!("{group}: {}", names.join(", ")))
+        .collect::<Vec<String>>()
+        .join("\n")
+    }
+
+    /// Record the action taken for a config.
+    fn record(&mut self, name: String, action: SyncAction) {
+        match action {
+            SyncAction::Pulled => self.pulled.push(name),
+            SyncAction::Pushed => self.pushed.push(name),
+            SyncAction::Conflict => self.conflicts.push(name),
+            SyncAction::Skipped(reason) => self.skipped.push((name, reason)),
+        }
+    }
+
+    /// Build the summary of a one-way sync operation from the outcome of
+    /// every config, which is either an error or the reason the config was
+    /// skipped. The configs that weren't skipped were synced with the given
+    /// action.
+    fn from_outcomes(
+        outcomes: Vec<(String, Result<Option<SkipReason>>)>,
+        synced: SyncAction,
+    ) -> Self {
+        let mut summary = SyncSummary::default();
+        outcomes
+            .into_iter()
+            .for_each(|(name, outcome)| match outcome {
+                Ok(reason) => summary.record(name, reason.map_or(synced, SyncAction::Skipped)),
+                Err(e) => summary.errors.push((name, format!("{e:#}"))),
+            });
+
+        summary
+    }
+}
+
+/// Display implementation for SyncSummary.
+///
+/// Prints the one-line summary of the sync, e.g.
+/// `Synced: 1 pulled, 2 pushed, 0 conflicts, 0 errors`.
+impl fmt::Display for SyncSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Synced: {} pulled, {} pushed, {} conflicts, {} errors",
+            self.pulled.len(),
+            self.pushed.len(),
+            self.conflicts.len(),
+            self.errors.len()
+        )
+    }
+}
+
+/// A config path resolved by `DotConfig::debug_resolution`.
+#[derive(Debug)]
+pub struct ResolvedEntry {
+    /// Name of the config.
+    pub name: String,
+    /// Path of the config as written in the config file.
+    pub path: String,
+    /// Path the config resolves to on the local system.
+    pub resolved_path: PathBuf,
+    /// Path of the config inside the dotconfigs directory, if the dotconfigs
+    /// path is local and the path of the config can be resolved in it.
+    pub dotconfigs_path: Option<PathBuf>,
+    /// Type of the config on disk, or the configured type if the config does
+    /// not exist.
+    pub conf_type: Option<ConfType>,
+}
+
+/// Differences between the configs of two `DotConfig`s, see
+/// `DotConfig::diff`.
+///
+/// The differences are displayed one per line, prefixed with `+` for added,
+/// `-` for removed and `~` for changed configs, and can be serialized as
+/// JSON.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::dotconfig::{ConfigDiff, DiffEntry};
+///
+/// let diff = ConfigDiff {
+///     added: vec![DiffEntry {
+///         name: String::from("i3"),
+///         path: String::from("~/.config/i3"),
+///     }],
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(diff.to_string(), "+ i3 (~/.config/i3)\n");
+/// assert!(serde_json::to_string(&diff).unwrap().contains("\"added\""));
+/// assert!(ConfigDiff::default().is_empty());
+/// ```
+#[derive(Debug, Default, Serialize)]
+pub struct ConfigDiff {
+    /// Configs only in the other `DotConfig`.
+    pub added: Vec<DiffEntry>,
+    /// Configs missing from the other `DotConfig`.
+    pub removed: Vec<DiffEntry>,
+    /// Configs in both with different paths.
+    pub changed: Vec<ChangedEntry>,
+}
+
+impl ConfigDiff {
+    /// Check if the configs of both `DotConfig`s are the same.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Display implementation for ConfigDiff.
+impl fmt::Display for ConfigDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.added {
+            writeln!(f, "+ {} ({})", entry.name, entry.path)?;
+        }
+        for entry in &self.removed {
+            writeln!(f, "- {} ({})", entry.name, entry.path)?;
+        }
+        for entry in &self.changed {
+            writeln!(
+                f,
+                "~ {}: {} -> {}",
+                entry.name, entry.path, entry.other_path
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A config added or removed between two `DotConfig`s, see `ConfigDiff`.
+#[derive(Debug, Serialize)]
+pub struct DiffEntry {
+    /// Name of the config.
+    pub name: String,
+    /// Paths of the config, separated by commas.
+    pub path: String,
+}
+
+/// A config with different paths in two `DotConfig`s, see `ConfigDiff`.
+#[derive(Debug, Serialize)]
+pub struct ChangedEntry {
+    /// Name of the config.
+    pub name: String,
+    /// Paths of the config in the first `DotConfig`, separated by commas.
+    pub path: String,
+    /// Paths of the config in the other `DotConfig`, separated by commas.
+    pub other_path: String,
+}
+
+/// Reason why a config was skipped by a sync operation.
+///
+/// The reason is displayed as the human readable explanation of the skip.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::dotconfig::SkipReason;
+///
+/// assert_eq!(SkipReason::UpToDate.to_string(), "already up-to date");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The local config does not exist.
+    Missing,
+    /// The config does not exist in the dotconfigs directory.
+    MissingInDotconfigs,
+    /// The config is the same in both places.
+    UpToDate,
+    /// The dotconfigs path is not a local directory.
+    NonLocalDotconfigsPath,
+    /// The config changed between a file and a directory on one side since
+    /// the last sync.
+    TypeChanged,
+    /// The config is not synced on the current operating system.
+    OtherOs,
+    /// The config already exists in the dotconfigs directory.
+    PresentInDotconfigs,
+}
+
+/// Display implementation for SkipReason.
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::Missing => write!(f, "does not exist"),
+            SkipReason::MissingInDotconfigs => {
+                write!(f, "does not exist in the dotconfigs directory")
+            }
+            SkipReason::UpToDate => write!(f, "already up-to date"),
+            SkipReason::NonLocalDotconfigsPath => {
+                write!(f, "dotconfigs_path is not a local directory")
+            }
+            SkipReason::TypeChanged => write!(f, "changed between a file and a directory"),
+            SkipReason::OtherOs => write!(f, "not synced on this operating system"),
+            SkipReason::PresentInDotconfigs => {
+                write!(f, "already exists in the dotconfigs directory")
+            }
+        }
+    }
+}
+
+/// State of a config compared with its copy in the dotconfigs directory,
+/// see `DotConfig::status`.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::dotconfig::SyncState;
+///
+/// assert_eq!(SyncState::LocalNewer.to_string(), "local newer");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncState {
+    /// The config is the same in both places.
+    UpToDate,
+    /// The local config changed (or only it exists), and would be pulled.
+    LocalNewer,
+    /// The dotconfigs copy changed (or only it exists), and would be pushed.
+    RepoNewer,
+    /// The config exists neither locally nor in the dotconfigs directory.
+    Missing,
+    /// The config changed on both sides, or changed between a file and a
+    /// directory on one side, and has to be synced by hand.
+    Conflict,
+}
+
+/// Display implementation for SyncState.
+impl fmt::Display for SyncState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncState::UpToDate => write!(f, "up-to-date"),
+            SyncState::LocalNewer => write!(f, "local newer"),
+            SyncState::RepoNewer => write!(f, "repo newer"),
+            SyncState::Missing => write!(f, "missing"),
+            SyncState::Conflict => write!(f, "conflict"),
+        }
+    }
+}
+
+/// Action taken for a single config by a sync operation.
+#[derive(Clone, Copy)]
+enum SyncAction {
+    Skipped(SkipReason),
+    Pulled,
+    Pushed,
+    Conflict,
+}
+
+/// Stop at the error of a config when the `--fail-fast` option is set.
+///
+/// # Returns
+///
+/// The name and outcome of the config, or its error with the `--fail-fast`
+/// option, which makes the surrounding collection of the outcomes fail.
+fn fail_fast<T>(name: String, outcome: Result<T>) -> Result<(String, Result<T>)> {
+    match outcome {
+        Err(e) if options::get().fail_fast => Err(e.context(format!("Failed to sync {name:?}"))),
+        outcome => Ok((name, outcome)),
+    }
+}
+
+/// Pull a single config if it changed, see `DotConfig::pull_updated_configs`.
+///
+/// # Returns
+///
+/// A Result containing the reason the config was skipped, or `None` if it
+/// was pulled.
+fn pull_updated_config(
+    dir: &mut Config,
+    dotconfigs_path: &DotconfigPath,
+    update_metadata: bool,
+    report: &mut CopyReport,
+) -> Result<Option<SkipReason>> {
+    if !dir.applies_to_current_os() {
+        return Ok(Some(SkipReason::OtherOs));
+    }
+
+    // check if the config dir exists
+    if !dir.path_exists() {
+        // if the config dir does not exist, exit safely
+        return Ok(Some(SkipReason::Missing));
+    }
+
+    // check if the config needs to be updated
+    if !dir
+        .check_update_metadata_required()
+        .context("Failed to get metadata digest")?
+    {
+        return Ok(Some(SkipReason::UpToDate));
+    }
+
+    let DotconfigPath::Local(local_dotconfigs_path) = dotconfigs_path else {
+        return Ok(Some(SkipReason::NonLocalDotconfigsPath));
+    };
+
+    info!("Updating {:#?}.", dir.name);
+
+    // update the metadata in the config file
+    if update_metadata {
+        dir.update_metadata()
+            .context("Failed to update config hash")?;
+    }
+
+    // The type of the config is still needed to copy it
+    let mut pulled = dir.clone();
+    if pulled.conf_type.is_none() {
+        pulled
+            .update_config_type()
+            .context("Failed to update config type")?;
+    }
+
+    // Replace the config file with the latest version
+    report.merge(pulled.pull_config(local_dotconfigs_path)?);
+
+    Ok(None)
+}
+
+/// Push a single config if it differs from its dotconfigs copy, see
+/// `DotConfig::push_updated_configs`.
+///
+/// # Returns
+///
+/// A Result containing the reason the config was skipped, or `None` if it
+/// was pushed.
+fn push_updated_config(
+    dir: &Config,
+    dotconfigs_path: &DotconfigPath,
+    report: &Mutex<CopyReport>,
+) -> Result<Option<SkipReason>> {
+    if !dir.applies_to_current_os() {
+        return Ok(Some(SkipReason::OtherOs));
+    }
+
+    let DotconfigPath::Local(local_dotconfigs_path) = dotconfigs_path else {
+        return Ok(Some(SkipReason::NonLocalDotconfigsPath));
+    };
+
+    let dotconfigs_config_path = dir
+        .repo_path_in(&fix_path!(local_dotconfigs_path))
+        .context("Failed to resolve the dotconfigs path of the config")?;
+
+    // The hashes of a file and a directory always differ, and one can't be
+    // copied onto the other
+    if dir.type_changed(&dotconfigs_config_path) {
+        return Ok(Some(SkipReason::TypeChanged));
+    }
+
+    // The dotconfigs copy doesn't keep the modification times, so it can
+    // only be compared by its contents. A missing local config is always
+    // pushed, so it is not hashed.
+    let local_config_hash = if dir.path_exists() {
+        dir.metadata_digest_with(ChangeDetection::ContentHash)
+            .context("Failed to get metadata digest")?
+    } else {
+        String::new()
+    };
+
+    let Some(dotconfigs_hash) = dir
+        .repo_digest(&dotconfigs_config_path)
+        .context("Failed to get the dotconfigs digest")?
+    else {
+        return Ok(Some(SkipReason::MissingInDotconfigs));
+    };
+
+    if dotconfigs_hash == local_config_hash && !dir.needs_link(&dotconfigs_config_path) {
+        return Ok(Some(SkipReason::UpToDate));
+    }
+
+    info!("Updating {:#?}.", dir.name);
+
+    let copied = dir.push_config(&dotconfigs_config_path)?;
+    report.lock().unwrap().merge(copied);
+
+    Ok(None)
+}
+
+/// Compare a single config with its copy in the dotconfigs directory, see
+/// `DotConfig::status`.
+fn config_status(dir: &Config, local_dotconfigs_path: &String) -> Result<SyncState> {
+    let repo_config_path = dir.repo_path_in(&fix_path!(local_dotconfigs_path))?;
+    if dir.type_changed(&repo_config_path) {
+        return Ok(SyncState::Conflict);
+    }
+
+    let state = match (dir.path_exists(), dir.repo_digest(&repo_config_path)?) {
+        (false, None) => SyncState::Missing,
+        (true, None) => SyncState::LocalNewer,
+        (false, Some(_)) => SyncState::RepoNewer,
+        (true, Some(repo_hash)) => {
+            if dir.metadata_digest_with(ChangeDetection::ContentHash)? == repo_hash {
+                return Ok(SyncState::UpToDate);
+            }
+
+            match (
+                dir.check_update_metadata_required()?,
+                !dir.hash_matches(&repo_hash),
+            ) {
+                (true, true) => SyncState::Conflict,
+                (true, false) => SyncState::LocalNewer,
+                (false, _) => SyncState::RepoNewer,
+            }
+        }
+    };
+
+    Ok(state)
+}
+
+/// Sync a single config in both directions, see `DotConfig::sync_configs`.
+fn sync_config(
+    dir: &mut Config,
+    local_dotconfigs_path: &String,
+    report: &mut CopyReport,
+) -> Result<SyncAction> {
+    if !dir.applies_to_current_os() {
+        return Ok(SyncAction::Skipped(SkipReason::OtherOs));
+    }
+
+    let repo_config_path = dir.repo_path_in(&fix_path!(local_dotconfigs_path))?;
+    if dir.type_changed(&repo_config_path) {
+        return Ok(SyncAction::Skipped(SkipReason::TypeChanged));
+    }
+    let repo_hash = dir.repo_digest(&repo_config_path)?;
+
+    let action = match (dir.path_exists(), repo_hash) {
+        (false, None) => return Ok(SyncAction::Skipped(SkipReason::Missing)),
+        (true, None) => SyncAction::Pulled,
+        (false, Some(_)) => SyncAction::Pushed,
+        (true, Some(repo_hash)) => {
+            let local_hash = dir.metadata_digest_with(ChangeDetection::ContentHash)?;
+            let local_changed = dir.check_update_metadata_required()?;
+
+            if local_hash == repo_hash {
+                if local_changed {
+                    dir.update_metadata()?;
+                }
+                // A symlinked config is linked even if both sides match
+                if !dir.needs_link(&repo_config_path) {
+                    return Ok(SyncAction::Skipped(SkipReason::UpToDate));
+                }
+                SyncAction::Pushed
+            } else {
+                // The stored hash is the content hash of both sides at the
+                // last sync, unless the metadata stamps are used
+                let repo_changed = !dir.hash_matches(&repo_hash);
+                match (local_changed, repo_changed) {
+                    (true, true) => {
+                        println!(
+                            "Conflict: {:#?} changed both locally and in the dotconfigs directory.",
+                            dir.name
+                        );
+                        return Ok(SyncAction::Conflict);
+                    }
+                    (true, false) => SyncAction::Pulled,
+                    (false, _) => SyncAction::Pushed,
+                }
+            }
+        }
+    };
+
+    match action {
+        SyncAction::Pulled => {
+            info!("Pulling {:#?}.", dir.name);
+
+            dir.update_metadata()?;
+            report.merge(dir.pull_config(local_dotconfigs_path)?);
+        }
+        SyncAction::Pushed => {
+            info!("Pushing {:#?}.", dir.name);
+
+            // The type can't be detected from the missing local config
+            if dir.conf_type.is_none() {
+                dir.conf_type = Some(if repo_config_path.is_dir() {
+                    ConfType::Dir
+                } else {
+                    ConfType::File
+                });
+            }
+
+            report.merge(dir.push_config(&repo_config_path)?);
+            dir.update_metadata()?;
+        }
+        _ => {}
+    }
+
+    Ok(action)
+}
+
+/// Copy a single config from a dotconfigs directory into another one, see
+/// `DotConfig::transfer`.
+///
+/// # Returns
+///
+/// A Result containing the reason the config was skipped, or `None` if it
+/// was copied.
+fn transfer_config(
+    dir: &Config,
+    from: &Path,
+    to: &Path,
+    report: &mut CopyReport,
+) -> Result<Option<SkipReason>> {
+    let from_config_path = dir
+        .repo_path_in(from)
+        .context("Failed to resolve the dotconfigs path of the config")?;
+    if !from_config_path.exists() && !from_config_path.is_symlink() {
+        return Ok(Some(SkipReason::MissingInDotconfigs));
+    }
+
+    // Keep the layout of the source dotconfigs directory
+    let to_config_path = to.join(from_config_path.strip_prefix(from)?);
+
+    if options::get().dry_run {
+        println!(
+            "Would copy {:#?} -> {:#?}",
+            from_config_path, to_config_path
+        );
+        return Ok(None);
+    }
+
+    info!("Transferring {:#?} to {:#?}.", dir.name, to_config_path);
+
+    if let Some(parent) = to_config_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if from_config_path.is_symlink() {
+        utils::replace_with_symlink(fs::read_link(&from_config_path)?, &to_config_path)?;
+        report.add_file(0);
+    } else if from_config_path.is_dir() {
+        utils::copy_dir(from_config_path.as_path(), to_config_path.as_path())?;
+        for file in hasher::list_dir_files(&to_config_path)? {
+            report.add_file(fs::metadata(file)?.len());
+        }
+    } else {
+        report.add_file(fs::copy(&from_config_path, &to_config_path)?);
+    }
+
+    Ok(None)
+}
+
+/// Pull a single config if it is missing from the dotconfigs directory, see
+/// `DotConfig::repair`.
+///
+/// # Returns
+///
+/// A Result containing the reason the config was skipped, or `None` if it
+/// was pulled.
+fn repair_config(
+    dir: &mut Config,
+    dotconfigs_path: &DotconfigPath,
+    update_metadata: bool,
+    report: &mut CopyReport,
+) -> Result<Option<SkipReason>> {
+    if !dir.applies_to_current_os() {
+        return Ok(Some(SkipReason::OtherOs));
+    }
+
+    let DotconfigPath::Local(local_dotconfigs_path) = dotconfigs_path else {
+        return Ok(Some(SkipReason::NonLocalDotconfigsPath));
+    };
+
+    if !dir.path_exists() {
+        return Ok(Some(SkipReason::Missing));
+    }
+
+    // The type of the config is needed to resolve and copy it
+    let mut pulled = dir.clone();
+    if pulled.conf_type.is_none() {
+        pulled
+            .update_config_type()
+            .context("Failed to update config type")?;
+    }
+
+    let repo_config_path = pulled
+        .repo_path_in(&fix_path!(local_dotconfigs_path))
+        .context("Failed to resolve the dotconfigs path of the config")?;
+    if repo_config_path.exists() || repo_config_path.is_symlink() {
+        return Ok(Some(SkipReason::PresentInDotconfigs));
+    }
+
+    info!("Repairing {:#?}.", dir.name);
+
+    if update_metadata {
+        dir.update_metadata()
+            .context("Failed to update config hash")?;
+    }
+
+    report.merge(pulled.pull_config(local_dotconfigs_path)?);
+
+    Ok(None)
+}
+
+/// List the entries of a GNU Stow package to be imported as configs.
+///
+/// The entries are relative to the package, and the directories shared
+/// between packages are replaced by their own entries.
+fn stow_package_entries(package: &Path, relative: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries = fs::read_dir(package.join(relative))
+        .with_context(|| format!("Failed to read the stow package {:#?}", package))?
+        .filter_map(|e| e.ok())
+        .filter(|e| !STOW_IGNORED_ENTRIES.contains(&e.file_name().to_string_lossy().as_ref()))
+        .map(|e| relative.join(e.file_name()))
+        .collect::<Vec<PathBuf>>();
+    entries.sort();
+
+    let mut result = Vec::new();
+    for entry in entries {
+        let home_path = stow_home_path(&entry);
+        let shared = STOW_SHARED_DIRS
+            .iter()
+            .any(|dir| Path::new(dir) == home_path);
+
+        if shared && package.join(&entry).is_dir() {
+            result.extend(stow_package_entries(package, &entry)?);
+        } else {
+            result.push(entry);
+        }
+    }
+
+    Ok(result)
+}
+
+/// Translate the path of a GNU Stow package entry to its path inside the
+/// home directory, replacing the `dot-` prefixes with `.`.
+fn stow_home_path(entry: &Path) -> PathBuf {
+    entry
+        .iter()
+        .map(|component| {
+            let component = component.to_string_lossy();
+            match component.strip_prefix("dot-") {
+                Some(name) => format!(".{name}"),
+                None => component.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Refuse to use a dotconfigs directory that is the home directory or one
+/// of its ancestors.
+///
+/// Cleaning or force pulling such a dotconfigs directory would delete the
+/// whole home directory, so it is reported as an error instead.
+fn check_not_home_ancestor(dotconfigs_path: &Path, home: &Path) -> Result<()> {
+    let resolve = |path: &Path| {
+        fs::canonicalize(path)
+            .or_else(|_| std::path::absolute(path))
+            .unwrap_or(path.to_path_buf())
+    };
+
+    let (dotconfigs_path, home) = (resolve(dotconfigs_path), resolve(home));
+    if home.starts_with(&dotconfigs_path) {
+        return Err(anyhow::anyhow!(
+            "Refusing to use {:#?} as the dotconfigs path: it contains the home directory {:#?}, \
+             and cleaning it would delete your home directory. Set dotconfigs_path to a dedicated directory",
+            dotconfigs_path,
+            home
+        ));
+    }
+
+    Ok(())
+}
+
+/// Print the changes of the config file as a unified diff between its
+/// current content and the new serialization.
+///
+/// A missing config file is diffed as an empty file.
+fn print_config_diff(config_path: &Path, config: &str) {
+    let current = read_config_file(config_path).unwrap_or_default();
+    if current == config {
+        println!("No changes to the config file {:#?}", config_path);
+        return;
+    }
+
+    let path = config_path.display().to_string();
+    print!(
+        "{}",
+        TextDiff::from_lines(current.as_str(), config)
+            .unified_diff()
+            .header(&path, &path)
+    );
+}
+
+/// Move a config stored inside the dotconfigs directory from `old_path` to
+/// `new_path`, see `DotConfig::rename_config`.
+///
+/// Nothing is moved if the paths are the same or the config isn't stored
+/// yet. The directory named after the config that is left empty by a
+/// nested file (e.g. `<old>/config.yml`) is removed.
+fn move_stored_config(dotconfigs_path: &Path, old_path: &Path, new_path: &Path) -> Result<()> {
+    if old_path == new_path || fs::symlink_metadata(old_path).is_err() {
+        return Ok(());
+    }
+
+    if fs::symlink_metadata(new_path).is_ok() {
+        return Err(anyhow::anyhow!(
+            "Cannot move {:#?}, {:#?} already exists",
+            old_path,
+            new_path
+        ));
+    }
+
+    if options::get().dry_run {
+        println!("Would move {:#?} -> {:#?}", old_path, new_path);
+        return Ok(());
+    }
+
+    if let Some(parent) = new_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create the directory {:#?}", parent))?;
+    }
+    fs::rename(old_path, new_path)
+        .with_context(|| format!("Failed to move {:#?} to {:#?}", old_path, new_path))?;
+
+    if let Some(parent) = old_path.parent() {
+        if parent != dotconfigs_path {
+            // Only succeeds if the directory is empty
+            let _ = fs::remove_dir(parent);
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the directory a `Github` dotconfigs path is cloned into, see
+/// `DotConfig::ensure_github_cloned`.
+fn github_clone_dir(url: &str) -> Result<PathBuf> {
+    let url = url.trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    let repo_hash = format!("{:x}", Sha1::digest(url.as_bytes()));
+
+    Ok(utils::home_dir()?
+        .join(".cache/sync-dotfiles")
+        .join(&repo_hash[..16]))
+}
+
+/// Clone the git repository at `url` into `clone_dir`, creating its missing
+/// parent directories.
+fn clone_github_repo(url: &str, clone_dir: &Path) -> Result<()> {
+    if let Some(parent) = clone_dir.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create the directory {:#?}", parent))?;
+    }
+
+    info!("Cloning {url} into {clone_dir:#?}");
+    run_git(
+        Path::new("."),
+        &[
+            OsStr::new("clone"),
+            OsStr::new("--quiet"),
+            OsStr::new(url),
+            clone_dir.as_os_str(),
+        ],
+    )
+    .with_context(|| format!("Failed to clone {url}"))?;
+
+    Ok(())
+}
+
+/// Fetch the default branch of the `origin` remote of a clone and
+/// fast-forward the clone to it, see `DotConfig::sync_remote`.
+fn fast_forward_clone(clone_dir: &Path) -> Result<()> {
+    let dirty_files = changed_files(clone_dir)?;
+    if !dirty_files.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Refusing to pull {:#?}, it has uncommitted changes: {}",
+            clone_dir,
+            dirty_files.join(", ")
+        ));
+    }
+
+    if options::get().dry_run {
+        println!("Would fast-forward {clone_dir:#?} to its origin remote");
+        return Ok(());
+    }
+
+    info!("Fetching the latest changes into {clone_dir:#?}");
+    run_git(clone_dir, &["fetch", "--quiet", "origin"])?;
+
+    // origin/HEAD is only set by git clone, so look it up if it's missing
+    let default_branch = match run_git(
+        clone_dir,
+        &["symbolic-ref", "--short", "refs/remotes/origin/HEAD"],
+    ) {
+        Ok(branch) => branch,
+        Err(_) => {
+            run_git(clone_dir, &["remote", "set-head", "origin", "--auto"])?;
+            run_git(
+                clone_dir,
+                &["symbolic-ref", "--short", "refs/remotes/origin/HEAD"],
+            )?
+        }
+    };
+
+    run_git(
+        clone_dir,
+        &["merge", "--ff-only", "--quiet", default_branch.trim()],
+    )
+    .with_context(|| {
+        format!(
+            "Failed to fast-forward {:#?} to {}",
+            clone_dir,
+            default_branch.trim()
+        )
+    })?;
+
+    Ok(())
+}
+
+/// List the files of a git repository that are changed, staged or
+/// untracked, relative to its root.
+///
+/// The NUL separated status is parsed, so paths are never quoted, and the
+/// new path of a renamed or copied file is listed.
+fn changed_files(repo_dir: &Path) -> Result<Vec<String>> {
+    let status = run_git(
+        repo_dir,
+        &["status", "--porcelain", "-z", "--untracked-files=all"],
+    )?;
+
+    let mut files = Vec::new();
+    let mut entries = status.split('\0').filter(|entry| !entry.is_empty());
+    while let Some(entry) = entries.next() {
+        let (code, path) = entry.split_at_checked(3).unwrap_or((entry, ""));
+        files.push(path.to_string());
+
+        // Renames and copies are followed by their original path
+        if code.contains(['R', 'C']) {
+            entries.next();
+        }
+    }
+
+    Ok(files)
+}
+
+/// Run `git` with the given arguments inside `dir`.
+///
+/// # Returns
+///
+/// A Result containing the standard output of `git`, or an error with its
+/// standard error if it can't be run or fails.
+fn run_git<S: AsRef<OsStr>>(dir: &Path, args: &[S]) -> Result<String> {
+    let output = process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context("Failed to run git, is it installed?")?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git {} failed: {}",
+            args.iter()
+                .map(|arg| arg.as_ref().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Keep the leading comments of a config file in its new content, see
+/// `ConfigFormat::comment_header`.
+fn with_comment_header(format: ConfigFormat, config_path: &Path, config: String) -> String {
+    match format.comment_header(&read_config_file(config_path).unwrap_or_default()) {
+        Some(header) => header + &config,
+        None => config,
+    }
+}
+
+/// Fragments of a config directory a `DotConfig` was merged from, see
+/// `DotConfig::parse_config_fragments`.
+#[derive(Clone)]
+struct ConfigFragments {
+    /// The base fragment, setting the dotconfigs path and the options.
+    base: PathBuf,
+    /// The other fragments, in order.
+    paths: Vec<PathBuf>,
+    /// The fragment of every config defined outside of the base fragment,
+    /// by config name.
+    sources: HashMap<String, PathBuf>,
+}
+
+/// A config fragment other than the base fragment, only listing configs.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFragment {
+    /// The configs of the fragment.
+    #[serde(default)]
+    configs: Vec<Config>,
+}
+
+/// Machine-local state of the configs, saved per host when `host_state` is
+/// enabled.
+#[derive(Serialize, Deserialize, Default)]
+struct HostState {
+    /// State of every config, by config name.
+    configs: BTreeMap<String, ConfigState>,
+}
+
+/// Machine-local state of a single config.
+#[derive(Serialize, Deserialize, Default)]
+struct ConfigState {
+    /// Hash of the config, see `Config::hash`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
+    /// Time of the last sync of the config, see `Config::last_sync`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_sync: Option<u64>,
+}
+
+/// Get the path of the state file of the current host, inside the `state`
+/// directory next to the config file.
+fn host_state_path(config_path: &Path) -> Result<PathBuf> {
+    let state_dir = config_path.parent().unwrap_or(Path::new("")).join("state");
+
+    Ok(state_dir.join(format!("{}.ron", utils::hostname()?)))
+}
+
+/// Check if a config file is gzip compressed, from its `.gz` extension.
+fn is_gzipped(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "gz")
+}
+
+/// Get the path of the gzip compressed version of a config file.
+fn gzip_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".gz");
+    PathBuf::from(path)
+}
+
+/// Read the content of a config file, decompressing it if it is gzipped.
+fn read_config_file(path: &Path) -> io::Result<String> {
+    if !is_gzipped(path) {
+        return fs::read_to_string(path);
+    }
+
+    let mut content = String::new();
+    GzDecoder::new(fs::File::open(path)?).read_to_string(&mut content)?;
+
+    Ok(content)
+}
+
+/// Write the content of a config file, compressing it if it is gzipped.
+fn write_config_file(path: &Path, content: &str) -> io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    if !is_gzipped(path) {
+        return file.write_all(content.as_bytes());
+    }
+
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    encoder.write_all(content.as_bytes())?;
+    encoder.finish()?;
+
+    Ok(())
+}
+
+/// Check if a config is selected by the given config names.
+///
+/// All configs are selected if no config names are given.
+fn is_selected(selected: &Option<Vec<String>>, config: &Config) -> bool {
+    selected
+        .as_ref()
+        .is_none_or(|names| names.contains(&config.name))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_clean_nested_dotconfigs_dir() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-clean");
+        let _ = fs::remove_dir_all(&path);
+
+        fs::create_dir_all(path.join("nvim/lua/plugins")).unwrap();
+        fs::create_dir_all(path.join("i3/scripts")).unwrap();
+        fs::create_dir_all(path.join(".git/objects")).unwrap();
+        fs::write(path.join("nvim/init.lua"), "").unwrap();
+        fs::write(path.join("nvim/lua/plugins/lsp.lua"), "").unwrap();
+        fs::write(path.join("i3/scripts/bar.sh"), "").unwrap();
+        fs::write(path.join(".vimrc"), "").unwrap();
+        fs::write(path.join(".git/HEAD"), "").unwrap();
+
+        let mut dotconfig = DotConfig::new();
+        dotconfig.dotconfigs_path = DotconfigPath::Local(path.to_string_lossy().to_string());
+
+        dotconfig
+            .clean_dotconfigs_dir()
+            .expect("Failed to clean the dotconfigs directory");
+
+        let remaining = fs::read_dir(&path)
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect::<Vec<_>>();
+        assert_eq!(remaining, vec![".git"]);
+        assert!(path.join(".git/HEAD").exists());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_dotconfigs_path_containing_home() {
+        let home = Path::new("/home/user");
+
+        assert!(check_not_home_ancestor(Path::new("/home/user"), home).is_err());
+        assert!(check_not_home_ancestor(Path::new("/home/user/"), home).is_err());
+        assert!(check_not_home_ancestor(Path::new("/home"), home).is_err());
+        assert!(check_not_home_ancestor(Path::new("/"), home).is_err());
+        assert!(check_not_home_ancestor(Path::new("/home/user/dotfiles"), home).is_ok());
+        assert!(check_not_home_ancestor(Path::new("/home/username"), home).is_ok());
+
+        // The guard triggers before anything is removed
+        let mut dotconfig = DotConfig::new();
+        dotconfig.dotconfigs_path = DotconfigPath::Local(String::from("~"));
+        assert!(dotconfig.summarize_dotconfigs().is_err());
+    }
+
+    #[test]
+    fn test_pull_without_metadata_update() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-no-metadata-update");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(path.join(".vimrc"), "set nu").unwrap();
+
+        let mut dotconfig = DotConfig::new();
+        dotconfig.dotconfigs_path =
+            DotconfigPath::Local(path.join("dotconfigs").display().to_string());
+        dotconfig.configs = vec![Config::new(
+            String::from("vimrc"),
+            path.join(".vimrc").display().to_string(),
+            Some(String::from("sha1:0000")),
+            None,
+        )];
+
+        let (report, summary) = dotconfig.pull_configs(false).expect("Failed to pull");
+
+        assert!(summary.skipped.is_empty());
+        assert_eq!(summary.pulled, vec![String::from("vimrc")]);
+        assert_eq!(report.files_copied, 1);
+        assert!(path.join("dotconfigs/vimrc/.vimrc").exists());
+        assert_eq!(dotconfig.configs[0].hash.as_deref(), Some("sha1:0000"));
+        assert_eq!(dotconfig.configs[0].conf_type, None);
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_repair_missing_configs() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-repair");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("nvim")).unwrap();
+        fs::write(path.join(".vimrc"), "set nu").unwrap();
+        fs::write(path.join("nvim/init.lua"), "").unwrap();
+
+        let mut dotconfig = DotConfig::new();
+        dotconfig.dotconfigs_path =
+            DotconfigPath::Local(path.join("dotconfigs").display().to_string());
+        dotconfig.configs = vec![
+            Config::new_file("vimrc", path.join(".vimrc").display().to_string()),
+            Config::new_dir("nvim", path.join("nvim").display().to_string()),
+            Config::new_file("zshrc", path.join(".zshrc").display().to_string()),
+        ];
+        dotconfig.pull_configs(true).expect("Failed to pull");
+
+        // Only the lost copy is pulled again
+        fs::remove_dir_all(path.join("dotconfigs/nvim")).unwrap();
+        fs::write(path.join(".vimrc"), "set rnu").unwrap();
+        dotconfig.configs[1].hash = None;
+
+        let summary = dotconfig.repair().expect("Failed to repair");
+        assert_eq!(summary.pulled, vec![String::from("nvim")]);
+        assert_eq!(
+            summary.skipped,
+            vec![
+                (String::from("vimrc"), SkipReason::PresentInDotconfigs),
+                (String::from("zshrc"), SkipReason::Missing),
+            ]
+        );
+        assert_eq!(summary.copied.files_copied, 1);
+        assert!(path.join("dotconfigs/nvim/init.lua").exists());
+        assert!(dotconfig.configs[1].hash.is_some());
+        let vimrc = dotconfig.configs[0]
+            .repo_path_in(&path.join("dotconfigs"))
+            .unwrap();
+        assert_eq!(fs::read_to_string(vimrc).unwrap(), "set nu");
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_transfer_configs() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-transfer");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("old/editors/nvim/lua")).unwrap();
+        fs::create_dir_all(path.join("new/editors/nvim")).unwrap();
+        fs::write(path.join("old/editors/nvim/lua/init.lua"), "lua").unwrap();
+        fs::write(path.join("old/.vimrc"), "set nu").unwrap();
+        fs::write(path.join("new/editors/nvim/stale.lua"), "").unwrap();
+
+        let mut dotconfig = DotConfig::new();
+        dotconfig.configs = vec![
+            Config::new_file("vimrc", "~/.vimrc"),
+            Config::new_dir("nvim", "~/.config/nvim"),
+            Config::new_file("zshrc", "~/.zshrc"),
+        ];
+        dotconfig.configs[1].repo_path = Some(String::from("editors/nvim"));
+
+        let summary = dotconfig
+            .transfer(&path.join("old"), &path.join("new"))
+            .expect("Failed to transfer");
+        assert_eq!(summary.pulled, vec!["vimrc", "nvim"]);
+        assert_eq!(
+            summary.skipped,
+            vec![(String::from("zshrc"), SkipReason::MissingInDotconfigs)]
+        );
+        assert_eq!(summary.copied.files_copied, 2);
+
+        // The layout is kept and the previous copies are replaced
+        assert_eq!(
+            fs::read_to_string(path.join("new/.vimrc")).unwrap(),
+            "set nu"
+        );
+        assert!(path.join("new/editors/nvim/lua/init.lua").exists());
+        assert!(!path.join("new/editors/nvim/stale.lua").exists());
+
+        assert!(dotconfig
+            .transfer(&path.join("old"), &path.join("old/../old"))
+            .is_err());
+
+        // A missing source isn't mistaken for a missing destination
+        assert!(dotconfig
+            .transfer(&path.join("missing"), &path.join("missing-too"))
+            .is_err());
+        assert!(!path.join("missing-too").exists());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_clone_github_repo() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-github-clone");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("remote")).unwrap();
+        fs::write(path.join("remote/.vimrc"), "set nu").unwrap();
+
+        let git = |args: &[&str]| {
+            let status = process::Command::new("git")
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(path.join("remote"))
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success());
+        };
+        git(&["init", "--quiet"]);
+        git(&["add", "."]);
+        git(&["commit", "--quiet", "-m", "init"]);
+
+        let url = path.join("remote").display().to_string();
+        clone_github_repo(&url, &path.join("cache/clone")).expect("Failed to clone");
+        assert_eq!(
+            fs::read_to_string(path.join("cache/clone/.vimrc")).unwrap(),
+            "set nu"
+        );
+
+        let missing = path.join("missing").display().to_string();
+        assert!(clone_github_repo(&missing, &path.join("cache/missing")).is_err());
+
+        assert_eq!(
+            github_clone_dir("https://github.com/user/dotfiles.git").unwrap(),
+            github_clone_dir("https://github.com/user/dotfiles/").unwrap()
+        );
+        assert_ne!(
+            github_clone_dir("https://github.com/user/dotfiles").unwrap(),
+            github_clone_dir("https://github.com/user/other").unwrap()
+        );
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_fast_forward_clone() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-github-pull");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("remote")).unwrap();
+        fs::write(path.join("remote/.vimrc"), "set nu").unwrap();
+
+        let remote = path.join("remote");
+        let commit = |message: &str| {
+            run_git(&remote, &["add", "."]).unwrap();
+            run_git(
+                &remote,
+                &["-c", "user.name=test", "-c", "user.email=test@example.com"]
+                    .into_iter()
+                    .chain(["commit", "--quiet", "-m", message])
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap();
+        };
+        run_git(&remote, &["init", "--quiet"]).unwrap();
+        commit("init");
+
+        let clone = path.join("clone");
+        clone_github_repo(&remote.display().to_string(), &clone).expect("Failed to clone");
+
+        fs::write(path.join("remote/.zshrc"), "setopt autocd").unwrap();
+        commit("add zshrc");
+
+        fast_forward_clone(&clone).expect("Failed to fast-forward");
+        assert_eq!(
+            fs::read_to_string(clone.join(".zshrc")).unwrap(),
+            "setopt autocd"
+        );
+
+        // Uncommitted changes are never overwritten
+        fs::write(clone.join(".vimrc"), "set nonu").unwrap();
+        let err = fast_forward_clone(&clone).unwrap_err().to_string();
+        assert!(err.contains("uncommitted changes: .vimrc"), "{err}");
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_commit_and_push() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-github-push");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("remote.git")).unwrap();
+
+        let remote = path.join("remote.git");
+        run_git(&remote, &["init", "--quiet", "--bare"]).unwrap();
+        let clone = path.join("clone");
+        clone_github_repo(&remote.display().to_string(), &clone).expect("Failed to clone");
+        run_git(&clone, &["config", "user.name", "test"]).unwrap();
+        run_git(&clone, &["config", "user.email", "test@example.com"]).unwrap();
+
+        let mut dotconfig = DotConfig::new();
+        dotconfig.dotconfigs_path = DotconfigPath::Local(clone.display().to_string());
+        dotconfig.configs = vec![
+            Config::new_file("vimrc", "~/.vimrc"),
+            Config::new_dir("nvim", "~/.config/nvim"),
+            Config::new_file("zshrc", "~/.zshrc"),
+        ];
+        fs::create_dir_all(clone.join("nvim/lua")).unwrap();
+        fs::write(clone.join("nvim/lua/init.lua"), "lua").unwrap();
+        fs::write(clone.join(".vimrc"), "set nu").unwrap();
+
+        dotconfig.commit_and_push(None).expect("Failed to commit");
+        assert_eq!(
+            run_git(&remote, &["log", "-1", "--format=%s"]).unwrap(),
+            "sync-dotfiles: update 2 configs\n"
+        );
+
+        // Nothing is committed without changes
+        dotconfig
+            .commit_and_push(Some(String::from("empty")))
+            .expect("Failed to commit");
+        assert_eq!(
+            run_git(&remote, &["rev-list", "--count", "HEAD"]).unwrap(),
+            "1\n"
+        );
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_changed_files() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-changed-files");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        run_git(&path, &["init", "--quiet"]).unwrap();
+        run_git(&path, &["config", "user.name", "test"]).unwrap();
+        run_git(&path, &["config", "user.email", "test@example.com"]).unwrap();
+        fs::write(path.join("old name.txt"), "old").unwrap();
+        run_git(&path, &["add", "--all"]).unwrap();
+        run_git(&path, &["commit", "--quiet", "-m", "init"]).unwrap();
+
+        // Renamed files are listed by their new path, and no path is quoted
+        run_git(&path, &["mv", "old name.txt", "nëw name.txt"]).unwrap();
+        fs::write(path.join("with space.txt"), "new").unwrap();
+        assert_eq!(
+            changed_files(&path).unwrap(),
+            vec!["nëw name.txt", "with space.txt"]
+        );
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rename_config() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-rename");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("dotconfigs/nvim/lua")).unwrap();
+        fs::create_dir_all(path.join("dotconfigs/app")).unwrap();
+        fs::write(path.join("dotconfigs/nvim/lua/init.lua"), "lua").unwrap();
+        fs::write(path.join("dotconfigs/app/config.yml"), "app").unwrap();
+        fs::write(path.join("dotconfigs/.vimrc"), "set nu").unwrap();
+
+        let mut dotconfig = DotConfig::new();
+        dotconfig.dotconfigs_path =
+            DotconfigPath::Local(path.join("dotconfigs").display().to_string());
+        dotconfig.configs = vec![
+            Config::new_dir("nvim", "~/.config/nvim"),
+            Config::new_file("app", path.join("app/config.yml").display().to_string()),
+            Config::new_file("vimrc", "~/.vimrc"),
+        ];
+        dotconfig.configs[0].hash = Some(String::from("abcd1234"));
+        dotconfig
+            .profiles
+            .insert(String::from("laptop"), vec![String::from("nvim")]);
+
+        dotconfig
+            .rename_config("nvim", "neovim")
+            .expect("Failed to rename");
+        dotconfig
+            .rename_config("app", "myapp")
+            .expect("Failed to rename");
+        dotconfig
+            .rename_config("vimrc", "vim")
+            .expect("Failed to rename");
+
+        assert_eq!(dotconfig.configs[0].name, "neovim");
+        assert_eq!(dotconfig.configs[0].hash.as_deref(), Some("abcd1234"));
+        assert_eq!(dotconfig.profiles["laptop"], vec!["neovim"]);
+        assert!(path.join("dotconfigs/neovim/lua/init.lua").exists());
+        assert!(!path.join("dotconfigs/nvim").exists());
+        assert!(path.join("dotconfigs/myapp/config.yml").exists());
+        assert!(!path.join("dotconfigs/app").exists());
+        // Files stored under their file name stay in place
+        assert!(path.join("dotconfigs/.vimrc").exists());
+
+        assert!(dotconfig.rename_config("neovim", "vim").is_err());
+        assert!(dotconfig.rename_config("neovim", "a/b").is_err());
+        assert_eq!(dotconfig.configs[0].name, "neovim");
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_nested_files_with_same_name() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-nested-files");
+        let _ = fs::remove_dir_all(&path);
+        for app in ["app1", "app2"] {
+            fs::create_dir_all(path.join(app)).unwrap();
+            fs::write(path.join(app).join("config.yml"), app).unwrap();
+        }
+
+        let mut dotconfig = DotConfig::new();
+        dotconfig.dotconfigs_path =
+            DotconfigPath::Local(path.join("dotconfigs").display().to_string());
+        dotconfig.configs = ["app1", "app2"]
+            .map(|app| {
+                Config::new(
+                    String::from(app),
+                    path.join(app).join("config.yml").display().to_string(),
+                    None,
+                    Some(ConfType::File),
+                )
+            })
+            .to_vec();
+
+        let (report, _) = dotconfig.pull_configs(true).expect("Failed to pull");
+        assert_eq!(report.files_copied, 2);
+        for app in ["app1", "app2"] {
+            let repo_path = path.join("dotconfigs").join(app).join("config.yml");
+            assert_eq!(fs::read_to_string(repo_path).unwrap(), app);
+        }
+
+        // Each config is pushed back from its own copy
+        for app in ["app1", "app2"] {
+            fs::remove_file(path.join(app).join("config.yml")).unwrap();
+        }
+        dotconfig.force_push_configs().expect("Failed to push");
+        for app in ["app1", "app2"] {
+            let config_path = path.join(app).join("config.yml");
+            assert_eq!(fs::read_to_string(config_path).unwrap(), app);
+        }
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_push_continues_on_error() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-continue-on-error");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("local/.vimrc")).unwrap();
+        for name in ["vimrc", "zshrc"] {
+            fs::create_dir_all(path.join("dotconfigs").join(name)).unwrap();
+        }
+        fs::write(path.join("dotconfigs/vimrc/.vimrc"), "set nu").unwrap();
+        fs::write(path.join("dotconfigs/zshrc/.zshrc"), "").unwrap();
+
+        let mut dotconfig = DotConfig::new();
+        dotconfig.dotconfigs_path =
+            DotconfigPath::Local(path.join("dotconfigs").display().to_string());
+        dotconfig.configs = [("vimrc", ".vimrc"), ("zshrc", ".zshrc")]
+            .map(|(name, file)| {
+                Config::new_file(name, path.join("local").join(file).display().to_string())
+            })
+            .to_vec();
+
+        // The file config pushed onto a directory fails, without stopping
+        // the push of the other config
+        let summary = dotconfig.force_push_configs().expect("Failed to push");
+        assert_eq!(summary.errors.len(), 1);
+        assert_eq!(summary.errors[0].0, "vimrc");
+        assert_eq!(summary.pushed, vec![String::from("zshrc")]);
+        assert_eq!(summary.exit_code(), 1);
+        assert!(path.join("local/.zshrc").exists());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_hash_exclude() {
+        let mut dotconfig = DotConfig::new();
+        let mut nvim = Config::new_dir("nvim", "~/.config/nvim");
+        nvim.hash_exclude = Some(vec![String::from("[cache")]);
+        dotconfig.configs = vec![nvim];
+
+        assert!(dotconfig.validate().is_err());
+
+        let content = ConfigFormat::Ron.serialize(&dotconfig).unwrap();
+        let path = std::env::temp_dir().join("sync-dotfiles-test-invalid-hash-exclude.ron");
+        fs::write(&path, content).unwrap();
+        assert!(DotConfig::parse_dotconfig(&Some(path.display().to_string())).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_pull_reports_digest_errors() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-digest-errors");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("local/nvim")).unwrap();
+        fs::write(path.join("local/nvim/init.lua"), "").unwrap();
+        fs::write(path.join("local/.vimrc"), "set nu").unwrap();
+
+        let mut dotconfig = DotConfig::new();
+        dotconfig.dotconfigs_path =
+            DotconfigPath::Local(path.join("dotconfigs").display().to_string());
+        let mut nvim = Config::new_dir("nvim", path.join("local/nvim").display().to_string());
+        nvim.hash = Some(String::from("sha1:0000"));
+        nvim.hash_exclude = Some(vec![String::from("[cache")]);
+        dotconfig.configs = vec![
+            nvim,
+            Config::new_file("vimrc", path.join("local/.vimrc").display().to_string()),
+        ];
+
+        // The failed digest is a per-config error instead of a panic
+        let (_, summary) = dotconfig.pull_configs(true).expect("Failed to pull");
+        assert_eq!(summary.errors.len(), 1);
+        assert_eq!(summary.errors[0].0, "nvim");
+        assert_eq!(summary.pulled, vec![String::from("vimrc")]);
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_type_changed_configs() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-type-changed");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("local/.vimrc")).unwrap();
+        fs::write(path.join("local/nvim"), "").unwrap();
+        fs::create_dir_all(path.join("dotconfigs/vimrc")).unwrap();
+        fs::create_dir_all(path.join("dotconfigs/nvim")).unwrap();
+        fs::write(path.join("dotconfigs/vimrc/.vimrc"), "set nu").unwrap();
+        fs::write(path.join("dotconfigs/nvim/init.lua"), "").unwrap();
+
+        // A file config that became a directory, and a directory config
+        // that became a file
+        let mut dotconfig = DotConfig::new();
+        dotconfig.dotconfigs_path =
+            DotconfigPath::Local(path.join("dotconfigs").display().to_string());
+        dotconfig.configs = vec![
+            Config::new_file("vimrc", path.join("local/.vimrc").display().to_string()),
+            Config::new_dir("nvim", path.join("local/nvim").display().to_string()),
+        ];
+
+        let summary = dotconfig.push_updated_configs().expect("Failed to push");
+        assert!(summary.errors.is_empty());
+        assert_eq!(
+            summary.skipped,
+            vec![
+                (String::from("vimrc"), SkipReason::TypeChanged),
+                (String::from("nvim"), SkipReason::TypeChanged),
+            ]
+        );
+
+        let summary = dotconfig.sync_configs().expect("Failed to sync");
+        assert_eq!(summary.skipped.len(), 2);
+        assert!(summary
+            .skipped
+            .iter()
+            .all(|(_, reason)| *reason == SkipReason::TypeChanged));
+
+        // Both sides are left untouched
+        assert!(path.join("local/.vimrc").is_dir());
+        assert!(path.join("local/nvim").is_file());
+        assert!(path.join("dotconfigs/vimrc/.vimrc").is_file());
+        assert!(path.join("dotconfigs/nvim/init.lua").is_file());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_status() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-status");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("local")).unwrap();
+        for file in [".vimrc", ".zshrc", ".bashrc", ".tmux.conf"] {
+            fs::write(path.join("local").join(file), file).unwrap();
+        }
+
+        let mut dotconfig = DotConfig::new();
+        dotconfig.dotconfigs_path =
+            DotconfigPath::Local(path.join("dotconfigs").display().to_string());
+        dotconfig.configs = vec![
+            Config::new_file("vimrc", path.join("local/.vimrc").display().to_string()),
+            Config::new_file("zshrc", path.join("local/.zshrc").display().to_string()),
+            Config::new_file("bashrc", path.join("local/.bashrc").display().to_string()),
+            Config::new_file("tmux", path.join("local/.tmux.conf").display().to_string()),
+            Config::new_file("inputrc", path.join("local/.inputrc").display().to_string()),
+        ];
+        dotconfig.pull_updated_configs().expect("Failed to pull");
+
+        fs::write(path.join("local/.zshrc"), "setopt autocd").unwrap();
+        fs::write(path.join("dotconfigs/bashrc/.bashrc"), "set -o vi").unwrap();
+        fs::write(path.join("local/.tmux.conf"), "set -g mouse on").unwrap();
+        fs::write(path.join("dotconfigs/tmux/.tmux.conf"), "set -g mouse off").unwrap();
+
+        assert_eq!(
+            dotconfig.status().expect("Failed to get the status"),
+            vec![
+                (String::from("vimrc"), SyncState::UpToDate),
+                (String::from("zshrc"), SyncState::LocalNewer),
+                (String::from("bashrc"), SyncState::RepoNewer),
+                (String::from("tmux"), SyncState::Conflict),
+                (String::from("inputrc"), SyncState::Missing),
+            ]
+        );
+
+        // Nothing is synced
+        assert_eq!(
+            fs::read_to_string(path.join("dotconfigs/zshrc/.zshrc")).unwrap(),
+            ".zshrc"
+        );
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_hash_algorithm() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-hash-algorithm");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("local/nvim")).unwrap();
+        fs::write(path.join("local/.vimrc"), "set nu").unwrap();
+        fs::write(path.join("local/nvim/init.lua"), "vim.o.number = true").unwrap();
+
+        let mut dotconfig = DotConfig::new();
+        dotconfig.dotconfigs_path =
+            DotconfigPath::Local(path.join("dotconfigs").display().to_string());
+        dotconfig.configs = vec![
+            Config::new_file("vimrc", path.join("local/.vimrc").display().to_string()),
+            Config::new_dir("nvim", path.join("local/nvim").display().to_string()),
+        ];
+        dotconfig.pull_configs(true).expect("Failed to pull");
+        assert!(dotconfig.configs.iter().all(|config| config
+            .hash
+            .as_ref()
+            .unwrap()
+            .starts_with("sha1:")));
+
+        // Switching the algorithm replaces the hashes without a conflict
+        dotconfig.set_hash_algorithm(HashAlgorithm::Blake3);
+        assert_eq!(
+            dotconfig.status().expect("Failed to get the status"),
+            vec![
+                (String::from("vimrc"), SyncState::UpToDate),
+                (String::from("nvim"), SyncState::UpToDate),
+            ]
+        );
+        dotconfig.pull_configs(true).expect("Failed to pull");
+        assert!(dotconfig.configs.iter().all(|config| config
+            .hash
+            .as_ref()
+            .unwrap()
+            .starts_with("blake3:")));
+
+        let (report, summary) = dotconfig.pull_configs(true).expect("Failed to pull");
+        assert!(summary.pulled.is_empty());
+        assert_eq!(report.files_copied, 0);
+
+        // The algorithm is kept in the config file
+        let content = ConfigFormat::Ron.serialize(&dotconfig).unwrap();
+        assert!(content.contains("hash_algorithm: Blake3"));
+        let parsed = ConfigFormat::Ron.parse(&content).expect("Failed to parse");
+        assert_eq!(parsed.hash_algorithm, HashAlgorithm::Blake3);
+        assert!(!ConfigFormat::Ron
+            .serialize(&DotConfig::new())
+            .unwrap()
+            .contains("hash_algorithm"));
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_other_os_configs() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-other-os");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("local")).unwrap();
+        fs::write(path.join("local/.vimrc"), "set nu").unwrap();
+        fs::write(path.join("local/.skhdrc"), "").unwrap();
+
+        let mut dotconfig = DotConfig::new();
+        dotconfig.dotconfigs_path =
+            DotconfigPath::Local(path.join("dotconfigs").display().to_string());
+        dotconfig.configs = vec![
+            Config::new_file("vimrc", path.join("local/.vimrc").display().to_string()),
+            Config::new_file("skhd", path.join("local/.skhdrc").display().to_string()),
+        ];
+        dotconfig.configs[0].os = Some(vec![std::env::consts::OS.to_string()]);
+        dotconfig.configs[1].os = Some(vec![String::from("plan9")]);
+
+        let (_, summary) = dotconfig.pull_updated_configs().expect("Failed to pull");
+        assert_eq!(summary.pulled, vec![String::from("vimrc")]);
+        assert_eq!(
+            summary.skipped,
+            vec![(String::from("skhd"), SkipReason::OtherOs)]
+        );
+        assert!(dotconfig.configs[1].hash.is_none());
+        assert!(!path.join("dotconfigs/skhd").exists());
+
+        for summary in [
+            dotconfig.push_updated_configs().expect("Failed to push"),
+            dotconfig.sync_configs().expect("Failed to sync"),
+            dotconfig
+                .force_pull_configs()
+                .expect("Failed to force pull"),
+            dotconfig
+                .force_push_configs()
+                .expect("Failed to force push"),
+        ] {
+            assert!(summary
+                .skipped
+                .contains(&(String::from("skhd"), SkipReason::OtherOs)));
+        }
+        assert!(!path.join("dotconfigs/skhd").exists());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_free_space_estimate() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-free-space");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("local/nvim")).unwrap();
+        fs::write(path.join("local/nvim/init.lua"), "init").unwrap();
+        fs::write(path.join("local/.vimrc"), "set nu").unwrap();
+
+        let mut dotconfig = DotConfig::new();
+        dotconfig.dotconfigs_path =
+            DotconfigPath::Local(path.join("dotconfigs").display().to_string());
+        dotconfig.configs = vec![
+            Config::new_dir("nvim", path.join("local/nvim").display().to_string()),
+            Config::new_file("vimrc", path.join("local/.vimrc").display().to_string()),
+        ];
+
+        assert_eq!(dotconfig.check_free_space().unwrap(), 10);
+
+        // The copies already in the dotconfigs directory are replaced
+        dotconfig.pull_updated_configs().expect("Failed to pull");
+        fs::write(path.join("local/.vimrc"), "set nu rnu").unwrap();
+        assert_eq!(dotconfig.check_free_space().unwrap(), 4);
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_add_config_overwrite() {
+        let mut dotconfig = DotConfig::new();
+        let name = String::from("nvim");
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples");
+
+        dotconfig
+            .add_config(&name, PathBuf::from("~/.config/nvim"), false, None)
+            .expect("Failed to add config");
+        dotconfig.configs.last_mut().unwrap().hash = Some(String::from("sha1:abcd"));
+
+        assert!(dotconfig
+            .add_config(&name, path.clone(), false, None)
+            .is_err());
+
+        dotconfig
+            .add_config(&name, path.clone(), true, None)
+            .expect("Failed to overwrite config");
+
+        let configs = dotconfig
+            .configs
+            .iter()
+            .filter(|config| config.name == name)
+            .collect::<Vec<_>>();
+        assert_eq!(configs.len(), 1);
+        assert_eq!(configs[0].path, path.to_string_lossy());
+        assert_eq!(configs[0].conf_type, Some(ConfType::Dir));
+        assert_eq!(configs[0].hash, None);
+    }
+
+    #[test]
+    fn test_add_config_explicit_type() {
+        let mut dotconfig = DotConfig::new();
+        let path = std::env::temp_dir().join("sync-dotfiles-test-add-type/missing");
+        assert!(!path.exists());
+
+        dotconfig
+            .add_config(&String::from("missing"), path.clone(), false, None)
+            .expect("Failed to add config");
+        assert_eq!(dotconfig.configs.last().unwrap().conf_type, None);
+
+        dotconfig
+            .add_config(&String::from("pending"), path, false, Some(ConfType::Dir))
+            .expect("Failed to add config");
+        assert_eq!(
+            dotconfig.configs.last().unwrap().conf_type,
+            Some(ConfType::Dir)
+        );
+
+        // An explicit type wins over the detected one
+        dotconfig
+            .add_config(
+                &String::from("missing"),
+                PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples"),
+                true,
+                Some(ConfType::File),
+            )
+            .expect("Failed to overwrite config");
+        assert_eq!(dotconfig.configs[0].conf_type, Some(ConfType::File));
+    }
+
+    #[test]
+    fn test_invalid_config_names() {
+        let mut dotconfig = DotConfig::new();
+
+        for name in [
+            "../escape",
+            "CON",
+            "lpt1.txt",
+            "nvim/lua",
+            ".hidden",
+            "a:b",
+            "",
+        ] {
+            let error = dotconfig
+                .add_config(&String::from(name), PathBuf::from("~/.vimrc"), false, None)
+                .unwrap_err();
+            assert!(error.to_string().contains("Invalid config name"), "{name}");
+        }
+        let error = dotconfig
+            .add_config(
+                &String::from("../escape"),
+                PathBuf::from("~/.vimrc"),
+                false,
+                None,
+            )
+            .unwrap_err();
+        assert!(error.to_string().contains("'/'"));
+        assert!(dotconfig.configs[0].is_placeholder());
+
+        for name in ["console", "com10", "nvim.lua", "vim-.vimrc"] {
+            dotconfig
+                .add_config(&String::from(name), PathBuf::from("~/.vimrc"), false, None)
+                .expect("Failed to add config");
+        }
+
+        dotconfig.configs.push(Config::new_file("CON", "~/.vimrc"));
+        assert!(dotconfig.validate().is_err());
+    }
+
+    #[test]
+    fn test_repo_path_collisions() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-repo-path-collisions");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+
+        let mut dotconfig = DotConfig::new();
+        dotconfig.dotconfigs_path = DotconfigPath::Local(path.display().to_string());
+        let mut nvim = Config::new_dir("nvim", "~/.config/nvim");
+        nvim.repo_path = Some(String::from("editors/nvim"));
+        let mut neovim = Config::new_dir("neovim", "~/.local/share/nvim");
+        neovim.repo_path = Some(String::from("editors/nvim"));
+        dotconfig.configs = vec![
+            Config::new_file("vimrc", "~/.vimrc"),
+            nvim,
+            Config::new_file("zshrc", "~/.zshrc"),
+            neovim,
+        ];
+
+        let warnings = dotconfig.validate().expect("Failed to validate");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("\"nvim\" and \"neovim\""));
+        assert!(warnings[0].contains(&path.join("editors/nvim").display().to_string()));
+
+        // Configs with the same file name are stored apart
+        dotconfig.configs[3] = Config::new_file("vimrc-local", "~/.config/vim/.vimrc");
+        assert!(dotconfig.validate().expect("Failed to validate").is_empty());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_nested_repo_paths() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-nested-repo-paths");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("local/editors")).unwrap();
+        fs::create_dir_all(path.join("dotconfigs/editors/nvim")).unwrap();
+        fs::write(path.join("local/editors/.editorconfig"), "root = true").unwrap();
+        fs::write(path.join("dotconfigs/editors/nvim/init.lua"), "lua").unwrap();
+        fs::write(path.join("dotconfigs/editors/stale"), "").unwrap();
+
+        let mut editors =
+            Config::new_dir("editors", path.join("local/editors").display().to_string());
+        editors.repo_path = Some(String::from("editors"));
+        let mut nvim = Config::new_dir("nvim", "~/.config/nvim");
+        nvim.repo_path = Some(String::from("editors/nvim"));
+
+        let mut dotconfig = DotConfig::new();
+        dotconfig.dotconfigs_path =
+            DotconfigPath::Local(path.join("dotconfigs").display().to_string());
+        dotconfig.configs = vec![editors, nvim];
+
+        let err = dotconfig.validate().unwrap_err();
+        assert!(format!("{err:#}").contains("\"nvim\" is stored at"));
+
+        // Pulling the outer config keeps the copy of the inner one
+        dotconfig.propagate_settings();
+        assert_eq!(
+            dotconfig.configs[0].nested_repo_paths,
+            vec![PathBuf::from("nvim")]
+        );
+        dotconfig.configs[0]
+            .pull_config(&path.join("dotconfigs").display().to_string())
+            .expect("Failed to pull");
+        assert!(path.join("dotconfigs/editors/.editorconfig").exists());
+        assert!(path.join("dotconfigs/editors/nvim/init.lua").exists());
+        assert!(!path.join("dotconfigs/editors/stale").exists());
+
+        let config_path = path.join("config.ron");
+        fs::write(
+            &config_path,
+            r#"(dotconfigs_path: Local("/tmp/sync-dotfiles-dotconfigs"), configs: [(name: "editors", path: "~/editors", repo_path: "editors"), (name: "nvim", path: "~/.config/nvim", repo_path: "editors/nvim")])"#,
+        )
+        .unwrap();
+        assert!(DotConfig::parse_dotconfig_file(&config_path).is_err());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_conf_type_mismatch_warning() {
+        let mut dotconfig = DotConfig::new();
+        dotconfig.configs = vec![
+            Config::new_dir("app", "/tmp/sync-dotfiles-test-missing/config.toml"),
+            Config::new_dir("fish", "/tmp/sync-dotfiles-test-missing/conf.d"),
+        ];
+
+        let warnings = dotconfig.validate().expect("Failed to validate");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("\"app\" is stored as a directory"));
+    }
+
+    #[test]
+    fn test_add_config_removes_placeholder() {
+        let mut dotconfig = DotConfig::new();
+        assert!(dotconfig.configs[0].is_placeholder());
+
+        dotconfig
+            .add_config(
+                &String::from("vimrc"),
+                PathBuf::from("~/.vimrc"),
+                false,
+                None,
+            )
+            .expect("Failed to add config");
+
+        let names = dotconfig
+            .configs
+            .iter()
+            .map(|config| config.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["vimrc"]);
+    }
+
+    #[test]
+    fn test_parse_dotconfig_cached() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-cached.ron");
+        let config_path = Some(path.display().to_string());
+        fs::write(
+            &path,
+            r#"(dotconfigs_path: Local("~/dotfiles"), configs: [])"#,
+        )
+        .unwrap();
+
+        let config = DotConfig::parse_dotconfig_cached(&config_path).expect("Failed to parse");
+        assert!(config.configs.is_empty());
+
+        // A changed file is parsed again
+        fs::write(
+            &path,
+            r#"(dotconfigs_path: Local("~/dotfiles"), configs: [(name: "vimrc", path: "~/.vimrc")])"#,
+        )
+        .unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + std::time::Duration::from_secs(10))
+            .unwrap();
+
+        let config = DotConfig::parse_dotconfig_cached(&config_path).expect("Failed to parse");
+        assert_eq!(config.configs.len(), 1);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_config_not_found() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-missing.ron");
+        let _ = fs::remove_file(&path);
+
+        let err = DotConfig::parse_dotconfig(&Some(path.display().to_string()))
+            .err()
+            .expect("Parsed a missing config file");
+        let not_found = err
+            .downcast_ref::<ConfigNotFound>()
+            .expect("A missing config file isn't reported as not found");
+        assert_eq!(not_found.path, path);
+
+        // A config file failing to parse isn't reported as not found
+        fs::write(&path, "(configs: [").unwrap();
+        let err = DotConfig::parse_dotconfig(&Some(path.display().to_string()))
+            .err()
+            .expect("Parsed an invalid config file");
+        assert!(err.downcast_ref::<ConfigNotFound>().is_none());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_dotfiles_variable_in_paths() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-dotfiles-var.ron");
+        fs::write(
+            &path,
+            r#"(dotconfigs_path: Local("/tmp/sync-dotfiles-dotconfigs"), configs: [(name: "bin", path: "$DOTFILES/bin")])"#,
+        )
+        .unwrap();
+
+        let dotconfig =
+            DotConfig::parse_dotconfig(&Some(path.display().to_string())).expect("Failed to parse");
+        assert_eq!(
+            fix_path!(dotconfig.configs[0].primary_path()),
+            PathBuf::from("/tmp/sync-dotfiles-dotconfigs/bin")
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_config_formats_roundtrip() {
+        let mut dotconfig =
+            DotConfig::parse_dotconfig(&Some(String::from("./examples/config.ron")))
+                .expect("Failed to parse the existing dotconfig file");
+
+        for format in [ConfigFormat::Ron, ConfigFormat::Toml, ConfigFormat::Json] {
+            let content = format.serialize(&dotconfig).expect("Failed to serialize");
+            assert!(!content.contains("description"));
+        }
+
+        dotconfig.configs[0].description = Some(String::from("Scripts and small tools"));
+        for format in [ConfigFormat::Ron, ConfigFormat::Toml, ConfigFormat::Json] {
+            let content = format.serialize(&dotconfig).expect("Failed to serialize");
+            let parsed = format.parse(&content).expect("Failed to parse");
+
+            assert_eq!(
+                parsed.dotconfigs_path.to_string(),
+                dotconfig.dotconfigs_path.to_string()
+            );
+            assert_eq!(parsed.configs.len(), dotconfig.configs.len());
+            assert_eq!(parsed.configs[0].hash, dotconfig.configs[0].hash);
+            assert_eq!(
+                parsed.configs[0].description.as_deref(),
+                Some("Scripts and small tools")
+            );
+        }
+
+        // Optional values are parsed both with and without `Some(...)`
+        let implicit = r#"(dotconfigs_path: Local("~/dotfiles"), configs: [(name: "vimrc", path: "~/.vimrc", hash: "sha1:0a")])"#;
+        let explicit = r#"(dotconfigs_path: Local("~/dotfiles"), configs: [(name: "vimrc", path: "~/.vimrc", hash: Some("sha1:0a"))])"#;
+        for content in [implicit, explicit] {
+            let parsed = ConfigFormat::Ron.parse(content).expect("Failed to parse");
+            assert_eq!(parsed.configs[0].hash.as_deref(), Some("sha1:0a"));
+        }
+
+        // Only the leading comments are kept
+        let content =
+            "// My dotfiles\n\n#![enable(implicit_some)]\n// Synced daily\n(\n  // nvim\n)";
+        assert_eq!(
+            ConfigFormat::Ron.comment_header(content).unwrap(),
+            "// My dotfiles\n// Synced daily\n"
+        );
+        assert_eq!(ConfigFormat::Ron.comment_header("(configs: [])"), None);
+
+        // A config in another format doesn't parse in a forced format
+        let content = ConfigFormat::Ron.serialize(&dotconfig).unwrap();
+        assert!(ConfigFormat::Toml.parse(&content).is_err());
+    }
+
+    #[test]
+    fn test_gzipped_config_file() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-gzip-config");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+
+        let content = fs::read_to_string("./examples/config.ron").unwrap();
+        let config_path = path.join("config.ron.gz");
+        write_config_file(&config_path, &content).expect("Failed to write config");
+
+        assert_eq!(&fs::read(&config_path).unwrap()[..2], [0x1f, 0x8b]);
+        assert_eq!(read_config_file(&config_path).unwrap(), content);
+
+        let parsed =
+            DotConfig::parse_dotconfig_file(&config_path).expect("Failed to parse gzipped config");
+        let expected = DotConfig::parse_dotconfig_file(Path::new("./examples/config.ron")).unwrap();
+        assert_eq!(
+            ConfigFormat::Ron.serialize(&parsed).unwrap(),
+            ConfigFormat::Ron.serialize(&expected).unwrap()
+        );
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_host_state() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-host-state");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        let config_path = path.join("config.ron");
+
+        let mut dotconfig = DotConfig::new();
+        dotconfig.host_state = true;
+        dotconfig.configs = vec![Config::new_file("vimrc", "~/.vimrc")];
+        dotconfig.configs[0].hash = Some(String::from("sha1:0a"));
+        dotconfig.configs[0].last_sync = Some(1);
+
+        // The state stored in the config file is kept without a state file
+        let content = ConfigFormat::Ron.serialize(&dotconfig).unwrap();
+        fs::write(&config_path, content).unwrap();
+        let parsed = DotConfig::parse_dotconfig_file(&config_path).unwrap();
+        assert_eq!(parsed.configs[0].hash.as_deref(), Some("sha1:0a"));
+
+        // The config file is saved without the state, which is loaded from
+        // the state file of the host
+        let content = ConfigFormat::Ron
+            .serialize(&dotconfig.saved_config())
+            .unwrap();
+        assert!(!content.contains("sha1:0a"));
+        fs::write(&config_path, content).unwrap();
+        dotconfig.save_host_state(&config_path).unwrap();
+        assert!(path
+            .join(format!("state/{}.ron", utils::hostname().unwrap()))
+            .is_file());
+
+        let parsed = DotConfig::parse_dotconfig_file(&config_path).unwrap();
+        assert_eq!(parsed.configs[0].hash.as_deref(), Some("sha1:0a"));
+        assert_eq!(parsed.configs[0].last_sync, Some(1));
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_config_fragments() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-conf.d");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(
+            path.join("00-base.ron"),
+            r#"(dotconfigs_path: Local("/tmp/sync-dotfiles-dotconfigs"), configs: [(name: "vimrc", path: "~/.vimrc")], mirror_home: true)"#,
+        )
+        .unwrap();
+        fs::write(
+            path.join("10-editors.ron"),
+            "// Editors\n(configs: [(name: \"nvim\", path: \"~/.config/nvim\")])",
+        )
+        .unwrap();
+        fs::write(path.join("README"), "not a fragment").unwrap();
+
+        let mut dotconfig = DotConfig::parse_dotconfig_file(&path).expect("Failed to parse");
+        let names = dotconfig
+            .configs
+            .iter()
+            .map(|config| config.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, vec!["vimrc", "nvim"]);
+        assert!(dotconfig.configs[1].mirror_home);
+
+        // Every config is saved back to its fragment
+        dotconfig.configs[1].hash = Some(String::from("sha1:0a"));
+        dotconfig
+            .configs
+            .push(Config::new_file("zshrc", "~/.zshrc"));
+        let fragments = dotconfig.fragments.clone().unwrap();
+        dotconfig.save_config_fragments(&fragments).unwrap();
+
+        let base = fs::read_to_string(path.join("00-base.ron")).unwrap();
+        let editors = fs::read_to_string(path.join("10-editors.ron")).unwrap();
+        assert!(base.contains("zshrc") && !base.contains("nvim"));
+        assert!(editors.starts_with("// Editors\n") && editors.contains("sha1:0a"));
+        assert_eq!(
+            DotConfig::parse_dotconfig_file(&path)
+                .unwrap()
+                .configs
+                .len(),
+            3
+        );
+
+        // Configs defined in more than one fragment are reported
+        fs::write(
+            path.join("20-vim.ron"),
+            r#"(configs: [(name: "vimrc", path: "~/.vimrc")])"#,
+        )
+        .unwrap();
+        let err = DotConfig::parse_dotconfig_file(&path).err().unwrap();
+        assert!(err.to_string().contains("\"vimrc\" is defined in both"));
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_import_stow_packages() {
+        let path = std::env::temp_dir().join("sync-dotfiles-test-stow");
+        let _ = fs::remove_dir_all(&path);
+
+        fs::create_dir_all(path.join("nvim/.config/nvim/lua")).unwrap();
+        fs::create_dir_all(path.join("vim/.vim")).unwrap();
+        fs::create_dir_all(path.join("zsh")).unwrap();
+        fs::create_dir_all(path.join(".git")).unwrap();
+        fs::write(path.join("nvim/.config/nvim/init.lua"), "").unwrap();
+        fs::write(path.join("vim/.vimrc"), "").unwrap();
+        fs::write(path.join("vim/.stow-local-ignore"), "").unwrap();
+        fs::write(path.join("zsh/dot-zshrc"), "").unwrap();
+
+        let dotconfig = DotConfig::from_stow(&path).expect("Failed to import stow directory");
+
+        let configs = dotconfig
+            .configs
+            .iter()
+            .map(|config| {
+                (
+                    config.name.as_str(),
+                    config.path.as_str(),
+                    config.repo_path.as_deref().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            configs,
+            vec![
+                ("nvim", "~/.config/nvim", "nvim/.config/nvim"),
+                ("vim-vim", "~/.vim", "vim/.vim"),
+                ("vim-vimrc", "~/.vimrc", "vim/.vimrc"),
+                ("zsh", "~/.zshrc", "zsh/dot-zshrc"),
+            ]
+        );
+        assert_eq!(dotconfig.configs[0].conf_type, Some(ConfType::Dir));
+        assert_eq!(dotconfig.configs[2].conf_type, Some(ConfType::File));
+
+        fs::remove_dir_all(&path).unwrap();
+    }
 
     #[test]
     fn test_parse_exisiting_defconfig() {
@@ -1,13 +1,19 @@
-use digest::DynDigest;
+use crate::{stub::Stub, utils};
+use digest::{DynDigest, InvalidBufferSize};
+use rayon::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
 use std::{
-    fmt,
-    fmt::Write,
-    fs, io,
+    collections::HashSet,
+    fmt, fs, io,
     io::Read,
     marker,
-    num::NonZeroUsize,
+    os::unix::{ffi::OsStrExt, fs::MetadataExt},
     path::{Path, PathBuf},
-    string, thread,
+    str::FromStr,
+    time::UNIX_EPOCH,
 };
 
 /// HashBox is a Box<[u8]> type that implements hexadecimal formatting and
@@ -16,7 +22,7 @@ use std::{
 /// `HashBox` is a wrapper type for a boxed byte array (`Box<[u8]>`) that
 /// represents a hash.
 /// It implements the `std::fmt::LowerHex` trait for hexadecimal formatting
-/// and the `std::string::ToString` trait
+/// and the `std::fmt::Display` trait
 /// for converting the hash to a hexadecimal string.
 struct HashBox(Box<[u8]>);
 
@@ -30,51 +36,299 @@ impl fmt::LowerHex for HashBox {
     }
 }
 
-/// Implement std::string::ToString for Box<[u8]> type
-impl string::ToString for HashBox {
-    fn to_string(&self) -> String {
-        let mut hex_string = String::with_capacity(self.0.len() * 2);
-        self.0.iter().for_each(|byte| {
-            write!(hex_string, "{:02x}", byte).expect("Failed to write to string");
-        });
-        hex_string
+/// Implement std::fmt::Display for Box<[u8]> type
+///
+/// This also provides `ToString` for `HashBox` through the blanket
+/// implementation, producing the hexadecimal representation of the hash.
+impl fmt::Display for HashBox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:x}")
+    }
+}
+
+/// Hash algorithms used to produce the stored config hashes.
+///
+/// The stored hashes are prefixed with the name of the algorithm that
+/// produced them (e.g. `sha1:abcd...`), so that hashes of different
+/// algorithms are never compared with each other.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    /// SHA-1, the algorithm of unprefixed hashes.
+    #[default]
+    Sha1,
+    /// SHA-256.
+    Sha256,
+    /// BLAKE3, a lot faster than SHA-1 and SHA-256 for large configs.
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// Get the prefix of the hashes produced by the algorithm.
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+
+    /// Get the algorithm of a hash prefix, or `None` if it is unknown.
+    pub fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix {
+            "sha1" => Some(HashAlgorithm::Sha1),
+            "sha256" => Some(HashAlgorithm::Sha256),
+            "blake3" => Some(HashAlgorithm::Blake3),
+            _ => None,
+        }
+    }
+
+    /// Check if this is the default `Sha1` algorithm.
+    pub fn is_sha1(&self) -> bool {
+        matches!(self, HashAlgorithm::Sha1)
+    }
+
+    /// Create a new hasher of the algorithm.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync_dotfiles_rs::hasher::{get_file_hash, HashAlgorithm};
+    ///
+    /// let sha1 = get_file_hash("./examples/config.ron", &mut HashAlgorithm::Sha1.hasher());
+    /// let blake3 = get_file_hash("./examples/config.ron", &mut HashAlgorithm::Blake3.hasher());
+    ///
+    /// assert_eq!(sha1.unwrap().len(), 40);
+    /// assert_eq!(blake3.unwrap().len(), 64);
+    /// ```
+    pub fn hasher(&self) -> AnyHasher {
+        match self {
+            HashAlgorithm::Sha1 => AnyHasher::Sha1(Sha1::new()),
+            HashAlgorithm::Sha256 => AnyHasher::Sha256(Sha256::new()),
+            HashAlgorithm::Blake3 => AnyHasher::Blake3(Box::default()),
+        }
+    }
+}
+
+/// A hasher of any `HashAlgorithm`, created by `HashAlgorithm::hasher`.
+///
+/// The hashing functions clone the hasher to hash files in parallel, so the
+/// hashers are kept by value instead of as a `Box<dyn DynDigest>`.
+#[derive(Clone)]
+pub enum AnyHasher {
+    /// A SHA-1 hasher.
+    Sha1(Sha1),
+    /// A SHA-256 hasher.
+    Sha256(Sha256),
+    /// A BLAKE3 hasher.
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl AnyHasher {
+    /// Get the hasher of the algorithm as a `DynDigest`.
+    fn inner(&self) -> &dyn DynDigest {
+        match self {
+            AnyHasher::Sha1(hasher) => hasher,
+            AnyHasher::Sha256(hasher) => hasher,
+            AnyHasher::Blake3(hasher) => hasher.as_ref(),
+        }
+    }
+
+    /// Get the hasher of the algorithm as a mutable `DynDigest`.
+    fn inner_mut(&mut self) -> &mut dyn DynDigest {
+        match self {
+            AnyHasher::Sha1(hasher) => hasher,
+            AnyHasher::Sha256(hasher) => hasher,
+            AnyHasher::Blake3(hasher) => hasher.as_mut(),
+        }
+    }
+}
+
+impl DynDigest for AnyHasher {
+    fn update(&mut self, data: &[u8]) {
+        self.inner_mut().update(data);
+    }
+
+    fn finalize_into(mut self, buf: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        self.inner_mut().finalize_into_reset(buf)
+    }
+
+    fn finalize_into_reset(&mut self, buf: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        self.inner_mut().finalize_into_reset(buf)
+    }
+
+    fn reset(&mut self) {
+        self.inner_mut().reset();
+    }
+
+    fn output_size(&self) -> usize {
+        self.inner().output_size()
+    }
+
+    fn box_clone(&self) -> Box<dyn DynDigest> {
+        Box::new(self.clone())
+    }
+}
+
+/// Parse an algorithm from its name, which is also its hash prefix.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::hasher::HashAlgorithm;
+///
+/// assert_eq!("sha1".parse::<HashAlgorithm>().unwrap(), HashAlgorithm::Sha1);
+/// assert_eq!("blake3".parse::<HashAlgorithm>().unwrap(), HashAlgorithm::Blake3);
+/// assert!("md5".parse::<HashAlgorithm>().is_err());
+/// ```
+impl FromStr for HashAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(algorithm: &str) -> anyhow::Result<Self> {
+        HashAlgorithm::from_prefix(algorithm).ok_or_else(|| {
+            anyhow::anyhow!("Unknown hash algorithm {algorithm:?}, expected sha1, sha256 or blake3")
+        })
+    }
+}
+
+/// Prefix a hash with the name of the algorithm that produced it.
+///
+/// Empty hashes (of configs that don't exist) are left empty.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::hasher::{format_hash, HashAlgorithm};
+///
+/// assert_eq!(format_hash(HashAlgorithm::Sha1, "abcd"), "sha1:abcd");
+/// assert_eq!(format_hash(HashAlgorithm::Sha1, ""), "");
+/// ```
+pub fn format_hash(algorithm: HashAlgorithm, digest: &str) -> String {
+    if digest.is_empty() {
+        return String::new();
+    }
+
+    format!("{}:{digest}", algorithm.prefix())
+}
+
+/// Split a stored hash into its algorithm and digest.
+///
+/// Unprefixed hashes, stored by older versions, are SHA-1 hashes. The
+/// algorithm is `None` if the prefix is unknown.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::hasher::{parse_hash, HashAlgorithm};
+///
+/// assert_eq!(parse_hash("sha1:abcd"), (Some(HashAlgorithm::Sha1), "abcd"));
+/// assert_eq!(parse_hash("sha256:abcd"), (Some(HashAlgorithm::Sha256), "abcd"));
+/// assert_eq!(parse_hash("abcd"), (Some(HashAlgorithm::Sha1), "abcd"));
+/// assert_eq!(parse_hash("md5:abcd"), (None, "abcd"));
+/// ```
+pub fn parse_hash(hash: &str) -> (Option<HashAlgorithm>, &str) {
+    match hash.split_once(':') {
+        Some((prefix, digest)) => (HashAlgorithm::from_prefix(prefix), digest),
+        None => (Some(HashAlgorithm::Sha1), hash),
     }
 }
 
+/// Returns the hash of a file or directory, prefixed with its algorithm.
+///
+/// Files are hashed with `get_file_hash` and directories with
+/// `get_complete_dir_hash`, the same as the content hash of a config.
+///
+/// # Arguments
+///
+/// * `path`: The path of the file or directory to hash.
+/// * `algorithm`: The hash algorithm to use.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the prefixed hash (e.g. `sha1:...`), or an
+/// error if the path doesn't exist or there was an issue hashing it.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::hasher::{get_path_hash, HashAlgorithm};
+///
+/// let hash = get_path_hash("./examples/config.ron", HashAlgorithm::Sha1).unwrap();
+/// assert!(hash.starts_with("sha1:"));
+///
+/// let hash = get_path_hash("./examples/config.ron", HashAlgorithm::Blake3).unwrap();
+/// assert!(hash.starts_with("blake3:"));
+///
+/// assert!(get_path_hash("./examples/local_configs_dir", HashAlgorithm::Sha1).is_ok());
+/// assert!(get_path_hash("./examples/does-not-exist", HashAlgorithm::Sha1).is_err());
+/// ```
+pub fn get_path_hash<P>(path: P, algorithm: HashAlgorithm) -> Result<String, io::Error>
+where
+    P: AsRef<Path> + marker::Sync,
+{
+    let mut hasher = algorithm.hasher();
+    let digest = if path.as_ref().is_dir() {
+        get_complete_dir_hash(&path, &mut hasher)?
+    } else {
+        get_file_hash(&path, &mut hasher)?
+    };
+
+    Ok(format_hash(algorithm, &digest))
+}
+
 /// Returns a list of files in a directory.
 ///
 /// This function uses the `walkdir` crate to recursively walk the specified
 /// directory and filter out files (not directories).
 ///
+/// Symbolic links are never followed, so a link pointing back up the tree
+/// can't make the walk loop. Directories reached twice (e.g. through a bind
+/// mount of one of their parents) are reported as an error instead of being
+/// walked again.
+///
 /// # Arguments
 ///
 /// * `p`: A path to the directory to list files from.
 ///
 /// # Returns
 ///
-/// Returns a vector of `PathBuf` representing the paths to files in the
-/// directory.
+/// Returns a `Result` containing a vector of `PathBuf` representing the
+/// paths to files in the directory, or an error if the directory can't be
+/// walked or contains a cycle.
 ///
 /// # Example
 ///
 /// ```rust
 /// use sync_dotfiles_rs::hasher::list_dir_files;
 ///
-/// let files = list_dir_files("/path/to/directory");
-/// for file in files {
-///     println!("Found file: {:?}", file);
+/// match list_dir_files("/path/to/directory") {
+///     Ok(files) => files.iter().for_each(|file| println!("Found file: {:?}", file)),
+///     Err(err) => eprintln!("Error listing directory: {:?}", err),
 /// }
 /// ```
-pub fn list_dir_files<P>(p: P) -> Vec<PathBuf>
+pub fn list_dir_files<P>(p: P) -> Result<Vec<PathBuf>, io::Error>
 where
     P: AsRef<Path>,
 {
-    walkdir::WalkDir::new(p)
-        .into_iter()
-        .filter_map(|file| file.ok())
-        .filter(|normal_file| normal_file.metadata().unwrap().is_file())
-        .map(|x| x.into_path())
-        .collect::<Vec<PathBuf>>()
+    let mut visited_dirs = HashSet::new();
+    let mut files = Vec::new();
+
+    for entry in walkdir::WalkDir::new(p).follow_links(false) {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+
+        if metadata.is_dir() {
+            if !visited_dirs.insert((metadata.dev(), metadata.ino())) {
+                return Err(io::Error::other(format!(
+                    "Directory cycle detected at {:#?}",
+                    entry.path()
+                )));
+            }
+        } else if metadata.is_file() {
+            files.push(entry.into_path());
+        }
+    }
+
+    Ok(files)
 }
 
 /// Returns the hash of a single file.
@@ -109,11 +363,117 @@ where
     Hasher: DynDigest + Clone,
     P: AsRef<Path>,
 {
-    let mut file = fs::File::open(path)?;
+    let file = fs::File::open(path)?;
+
+    get_reader_hash(file, hash)
+}
+
+/// Returns the hash of a single file, ignoring a trailing newline of text
+/// files.
+///
+/// A single trailing line break (`\n` or `\r\n`) of a text file is left
+/// out of the hash, so that editors adding or removing it don't change the
+/// hash. Binary files (see `utils::is_binary`) are hashed unchanged.
+///
+/// # Arguments
+///
+/// * `path`: The path to the file to be hashed.
+/// * `hash`: A mutable reference to the hasher.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the computed hash as a `String` if
+/// successful, or an error if there was an issue reading or hashing the file.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::hasher::get_trimmed_file_hash;
+/// use sha1::{Sha1, Digest};
+///
+/// let dir = std::env::temp_dir().join("sync-dotfiles-doctest-trimmed-hash");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("a"), "set number").unwrap();
+/// std::fs::write(dir.join("b"), "set number\n").unwrap();
+/// std::fs::write(dir.join("c"), "set number\n\n").unwrap();
+///
+/// let mut hasher = Sha1::new();
+/// let hash = get_trimmed_file_hash(dir.join("a"), &mut hasher).unwrap();
+///
+/// assert_eq!(get_trimmed_file_hash(dir.join("b"), &mut hasher).unwrap(), hash);
+/// assert_ne!(get_trimmed_file_hash(dir.join("c"), &mut hasher).unwrap(), hash);
+///
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn get_trimmed_file_hash<Hasher, P>(path: P, hash: &mut Hasher) -> Result<String, io::Error>
+where
+    Hasher: DynDigest + Clone,
+    P: AsRef<Path>,
+{
+    let contents = fs::read(path)?;
+    if utils::is_binary_content(&contents) {
+        return get_reader_hash(contents.as_slice(), hash);
+    }
+
+    let trimmed = contents
+        .strip_suffix(b"\r\n")
+        .or_else(|| contents.strip_suffix(b"\n"))
+        .unwrap_or(&contents);
+
+    get_reader_hash(trimmed, hash)
+}
+
+/// Returns the hash of the target of a symlink.
+///
+/// The target path is hashed as it is stored in the link, without
+/// resolving it, so repointing the link changes the hash while changes to
+/// the contents it points to don't.
+///
+/// # Arguments
+///
+/// * `path`: The path to the symlink.
+/// * `hash`: A mutable reference to the hasher.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the computed hash as a `String` if
+/// successful, or an error if the path is not a symlink.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::hasher::get_link_target_hash;
+/// use sha1::{Sha1, Digest};
+///
+/// let mut hasher = Sha1::new();
+/// match get_link_target_hash("/path/to/link", &mut hasher) {
+///     Ok(hash) => println!("Link target hash: {}", hash),
+///     Err(err) => eprintln!("Error calculating link target hash: {:?}", err),
+/// }
+/// ```
+pub fn get_link_target_hash<Hasher, P>(path: P, hash: &mut Hasher) -> Result<String, io::Error>
+where
+    Hasher: DynDigest + Clone,
+    P: AsRef<Path>,
+{
+    let target = fs::read_link(path)?;
+
+    get_reader_hash(target.as_os_str().as_bytes(), hash)
+}
+
+/// Returns the hash of everything read from a reader.
+///
+/// This is the shared implementation of `get_file_hash`, which also allows
+/// hashing the files stored inside an archive without extracting them.
+fn get_reader_hash<Hasher, R>(mut reader: R, hash: &mut Hasher) -> Result<String, io::Error>
+where
+    Hasher: DynDigest + Clone,
+    R: Read,
+{
     let mut buf = [0u8; 4096];
 
     loop {
-        let i = file.read(&mut buf)?;
+        let i = reader.read(&mut buf)?;
         hash.update(&buf[..i]);
 
         if i == 0 {
@@ -128,6 +488,21 @@ where
 /// This function parallelizes the hash calculation of multiple files
 /// using Rayon.
 ///
+/// Every file is hashed independently with its own clone of the provided
+/// hasher, and the resulting file hashes are then fed into the provided
+/// hasher in the order of `files` to produce the combined hash.
+///
+/// A stub of a large file (see `Stub`) contributes the hash of the file it
+/// points to instead of its own.
+///
+/// # Threading model
+///
+/// The files are hashed on the global Rayon thread pool, which is sized to
+/// the available parallelism. Callers that are already running on that pool
+/// (e.g. a `par_iter` over all the configs) share the same worker threads,
+/// so nesting the per-config and per-file parallelism never spawns more
+/// threads than the pool has.
+///
 /// # Arguments
 ///
 /// * `files`: A slice of file paths to be hashed.
@@ -156,34 +531,78 @@ where
 pub fn get_files_hash<Hasher, P>(files: &[P], hash: &mut Hasher) -> Result<String, io::Error>
 where
     P: AsRef<Path> + marker::Sync,
-    Hasher: DynDigest + marker::Send + Clone,
+    Hasher: DynDigest + marker::Send + marker::Sync + Clone,
+{
+    files_hash_with(files, hash, |file, hash| get_file_hash(file, hash))
+}
+
+/// Returns the combined hash of a list of files, ignoring a trailing
+/// newline of the text files.
+///
+/// This is `get_files_hash` hashing every file with
+/// `get_trimmed_file_hash`.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::hasher::get_trimmed_files_hash;
+/// use sha1::{Sha1, Digest};
+///
+/// let mut hasher = Sha1::new();
+/// let files = vec!["/path/to/file1.txt", "/path/to/file2.txt"];
+///
+/// match get_trimmed_files_hash(&files, &mut hasher) {
+///     Ok(hash) => println!("Combined files hash: {}", hash),
+///     Err(err) => eprintln!("Error calculating combined files hash: {:?}", err),
+/// }
+/// ```
+pub fn get_trimmed_files_hash<Hasher, P>(
+    files: &[P],
+    hash: &mut Hasher,
+) -> Result<String, io::Error>
+where
+    P: AsRef<Path> + marker::Sync,
+    Hasher: DynDigest + marker::Send + marker::Sync + Clone,
+{
+    files_hash_with(files, hash, |file, hash| get_trimmed_file_hash(file, hash))
+}
+
+/// Combines the hashes of a list of files, each calculated with
+/// `file_hash`, see `get_files_hash`.
+fn files_hash_with<Hasher, P>(
+    files: &[P],
+    hash: &mut Hasher,
+    file_hash: fn(&Path, &mut Hasher) -> Result<String, io::Error>,
+) -> Result<String, io::Error>
+where
+    P: AsRef<Path> + marker::Sync,
+    Hasher: DynDigest + marker::Send + marker::Sync + Clone,
 {
     if files.is_empty() {
         return Ok(String::new());
     }
 
-    let threads = thread::available_parallelism()
-        .unwrap_or(NonZeroUsize::MIN)
-        .get();
-
-    let pool = rayon::ThreadPoolBuilder::new()
-        .num_threads(threads)
-        .build()
-        .unwrap();
-
-    let mut jobs: Vec<_> = Vec::with_capacity(files.len());
+    let file_hashes = files
+        .par_iter()
+        .map(|file| match Stub::read(file.as_ref())? {
+            Some(stub) => Ok(stub.digest().to_string()),
+            None => file_hash(file.as_ref(), &mut hash.clone()),
+        })
+        .collect::<Result<Vec<String>, io::Error>>()?;
 
-    files.iter().for_each(|file| {
-        jobs.push(pool.install(|| -> Result<(), io::Error> {
-            let filehash = get_file_hash(file, hash)?;
-            hash.update(filehash.as_bytes());
-            Ok(())
-        }))
-    });
+    Ok(combine_hashes(&file_hashes, hash))
+}
 
-    let final_hash = HashBox(hash.finalize_reset()).to_string();
+/// Combines a list of file hashes into a single hash, in the given order.
+fn combine_hashes<Hasher>(file_hashes: &[String], hash: &mut Hasher) -> String
+where
+    Hasher: DynDigest,
+{
+    file_hashes
+        .iter()
+        .for_each(|filehash| hash.update(filehash.as_bytes()));
 
-    Ok(final_hash)
+    HashBox(hash.finalize_reset()).to_string()
 }
 
 /// Returns the combined hash of all files in the specified directories.
@@ -219,14 +638,169 @@ where
 /// ```
 pub fn get_complete_dir_hash<Hasher, P>(dir_path: P, hash: &mut Hasher) -> Result<String, io::Error>
 where
-    Hasher: DynDigest + Clone + marker::Send,
+    Hasher: DynDigest + Clone + marker::Send + marker::Sync,
     P: AsRef<Path> + marker::Sync,
 {
-    let dirs = list_dir_files(dir_path);
-    let mut paths: Vec<PathBuf> = vec![];
-
-    dirs.iter()
-        .for_each(|dir| paths.append(&mut list_dir_files(dir)));
+    // Sort the files so that the combined hash doesn't depend on the order
+    // in which the filesystem returns the directory entries
+    let mut paths = list_dir_files(dir_path)?;
+    paths.sort();
 
     get_files_hash(&paths, hash)
 }
+
+/// Returns the combined hash of all files in a zstd compressed tar archive.
+///
+/// The files are hashed straight from the archive, in the sorted order of
+/// their paths, so the result matches the `get_complete_dir_hash` of the
+/// directory the archive was created from (excluding the entries skipped
+/// while compressing it).
+///
+/// # Arguments
+///
+/// * `archive_path`: The path of the `.tar.zst` archive.
+/// * `hash`: A mutable reference to the hasher.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the combined hash of all files in the
+/// archive as a `String` if successful, or an error if there was an issue
+/// reading the archive.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::hasher::get_archive_hash;
+/// use sha1::{Sha1, Digest};
+///
+/// let mut hasher = Sha1::new();
+///
+/// match get_archive_hash("/path/to/nvim.tar.zst", &mut hasher) {
+///     Ok(hash) => println!("Combined archive files hash: {}", hash),
+///     Err(err) => eprintln!("Error calculating combined archive files hash: {:?}", err),
+/// }
+/// ```
+pub fn get_archive_hash<Hasher, P>(archive_path: P, hash: &mut Hasher) -> Result<String, io::Error>
+where
+    Hasher: DynDigest + Clone,
+    P: AsRef<Path>,
+{
+    let file = fs::File::open(archive_path)?;
+    let mut archive = tar::Archive::new(zstd::Decoder::new(file)?);
+
+    let mut file_hashes = Vec::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let path = entry.path()?.into_owned();
+        file_hashes.push((path, get_reader_hash(entry, &mut hash.clone())?));
+    }
+
+    if file_hashes.is_empty() {
+        return Ok(String::new());
+    }
+
+    file_hashes.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let file_hashes = file_hashes
+        .into_iter()
+        .map(|(_, filehash)| filehash)
+        .collect::<Vec<String>>();
+
+    Ok(combine_hashes(&file_hashes, hash))
+}
+
+/// Returns the hash of the metadata stamps of a file or of all files in a
+/// directory.
+///
+/// Instead of reading the contents, the relative path, length and
+/// modification time of every file are hashed, in the sorted order of their
+/// paths. This is a lot faster for large directories, but misses edits that
+/// preserve both the length and the modification time of a file.
+///
+/// # Arguments
+///
+/// * `path`: The path to the file or directory to be stamped.
+/// * `hash`: A mutable reference to the hasher.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the hash of the metadata stamps as a
+/// `String` if successful, or an error if there was an issue reading the
+/// metadata of the files.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::hasher::get_metadata_stamp_hash;
+/// use sha1::{Sha1, Digest};
+///
+/// let mut hasher = Sha1::new();
+///
+/// match get_metadata_stamp_hash("/path/to/directory", &mut hasher) {
+///     Ok(hash) => println!("Metadata stamp hash: {}", hash),
+///     Err(err) => eprintln!("Error calculating metadata stamp hash: {:?}", err),
+/// }
+/// ```
+pub fn get_metadata_stamp_hash<Hasher, P>(path: P, hash: &mut Hasher) -> Result<String, io::Error>
+where
+    Hasher: DynDigest,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+
+    let mut files = if path.is_file() {
+        vec![path.to_path_buf()]
+    } else {
+        list_dir_files(path)?
+    };
+    files.sort();
+
+    get_files_metadata_stamp_hash(path, &files, hash)
+}
+
+/// Returns the hash of the metadata stamps of the given files of a
+/// directory, see `get_metadata_stamp_hash`.
+///
+/// The files are hashed in the given order, by their path relative to the
+/// directory.
+///
+/// # Arguments
+///
+/// * `path`: The path to the directory containing the files.
+/// * `files`: A slice of the paths of the files to be stamped.
+/// * `hash`: A mutable reference to the hasher.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the hash of the metadata stamps as a
+/// `String` if successful, or an error if there was an issue reading the
+/// metadata of the files.
+pub fn get_files_metadata_stamp_hash<Hasher, P>(
+    path: &Path,
+    files: &[P],
+    hash: &mut Hasher,
+) -> Result<String, io::Error>
+where
+    Hasher: DynDigest,
+    P: AsRef<Path>,
+{
+    for file in files {
+        let file = file.as_ref();
+        let metadata = fs::metadata(file)?;
+        let mtime = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        let relative_path = file.strip_prefix(path).unwrap_or(file);
+        hash.update(relative_path.to_string_lossy().as_bytes());
+        hash.update(&metadata.len().to_le_bytes());
+        hash.update(&mtime.as_secs().to_le_bytes());
+        hash.update(&mtime.subsec_nanos().to_le_bytes());
+    }
+
+    Ok(HashBox(hash.finalize_reset()).to_string())
+}
@@ -5,13 +5,15 @@
 //! The library consists of the following modules:
 //!
 //! - `config`: Provides support to represent and manipulate the config file
-//! data using a structure.
+//!   data using a structure.
 //! - `dotconfig`: Provides support to store the list of the config files with
-//! their path in the config file.
+//!   their path in the config file.
 //! - `hasher`: Contains various hashing functionality used to calculate file
-//! and directory hashes.
+//!   and directory hashes.
 //! - `utils`: Contains various utility functions used for path manipulation
-//! and directory copying.
+//!   and directory copying.
+//! - `options`: Contains the runtime options set from the command line.
+//! - `stub`: Contains the stubs stored instead of large files.
 //!
 //! This library can be used to create, update, and synchronize configuration
 //! files between a central repository
@@ -55,3 +57,9 @@ pub mod utils;
 
 /// Various hashing functions for calculating file and directory hashes.
 pub mod hasher;
+
+/// Runtime options set from the command line.
+pub mod options;
+
+/// Stubs stored in the dotconfigs directory instead of large files.
+pub mod stub;
@@ -4,42 +4,261 @@ pub use ron::{
     ser::{to_string_pretty, PrettyConfig},
     Options,
 };
-use std::{path::PathBuf, process};
+use std::{
+    io::{self, IsTerminal},
+    path::PathBuf,
+    process,
+    time::Instant,
+};
 use sync_dotfiles_rs::{
-    dotconfig::DotConfig,
-    utils::{self, FixPath},
+    dotconfig::{ConfigNotFound, DotConfig, SyncState, SyncSummary},
+    hasher::{self, HashAlgorithm},
+    info,
+    options::{self, SyncOptions},
+    utils::{self, FileLock, FixPath},
 };
 mod args;
 use args::{get_env_args, Commands::*};
 
 fn main() -> Result<()> {
+    let started = Instant::now();
     let args = get_env_args();
     let mut dotconfig;
 
-    dotconfig = DotConfig::parse_dotconfig(&args.config_path)
-        .context("Failed to parse custom config file")?;
+    let home = args
+        .home
+        .as_ref()
+        .map(std::path::absolute)
+        .transpose()
+        .context("Failed to resolve the home directory")?;
+
+    // A single thread runs all the parallel iterations serially, in order
+    let jobs = if args.parallel { args.jobs } else { Some(1) };
+    if let Some(jobs) = jobs {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build_global()
+            .context("Failed to set the number of jobs")?;
+    }
+
+    let (name, exclude_config) = match &args.command {
+        Some(
+            ForcePull(filter)
+            | ForcePush(filter)
+            | Pull(args::PullArgs { filter, .. })
+            | Push(filter)
+            | Repair(filter),
+        ) => (filter.name.clone(), filter.exclude_config.clone()),
+        _ => (None, Vec::new()),
+    };
+
+    options::set(SyncOptions {
+        make_writable: args.make_writable,
+        home,
+        profile: args.profile.clone(),
+        name,
+        exclude_config,
+        // Keep the printed config clean for pipelines
+        quiet: args.quiet || matches!(args.command, Some(Cat(_) | Resolve(_))),
+        strict_hash: args.strict_hash,
+        verify_after: args.verify_after,
+        config_format: args
+            .config_format
+            .as_deref()
+            .map(str::parse)
+            .transpose()
+            .context("Failed to parse the config format")?,
+        show_config_diff: args.show_config_diff,
+        compress_config: args.compress_config,
+        explicit_some: args.explicit_some,
+        no_metadata_update: args.no_metadata_update,
+        expand_globs_in_config: args.expand_globs_in_config,
+        fail_fast: args.fail_fast,
+        dry_run: args.dry_run,
+    });
+
+    // A config directory is selected like a config file
+    let config_path = args.config_path.clone().or(args.config_dir.clone());
+
+    if args.print_config_path {
+        let config_path =
+            DotConfig::config_file_path(&config_path).context("Failed to find the config file")?;
+
+        println!("{}", config_path.display());
+
+        process::exit(0);
+    }
+
+    let Some(command) = args.command else {
+        args::missing_subcommand();
+    };
+
+    // Handle the commands that don't use the config file
+    match &command {
+        PrintNew(args::PrintNewArgs { autodetect }) => {
+            let dotconfig = if *autodetect {
+                DotConfig::autodetect().context("Failed to detect the dotfiles")?
+            } else {
+                DotConfig::new()
+            };
+
+            let config = Options::default()
+                .with_default_extension(Extensions::IMPLICIT_SOME)
+                .to_string_pretty(&dotconfig, utils::get_ron_formatter())
+                .context("Failed to print the new config")?;
+
+            dotconfig
+                .configs
+                .iter()
+                .filter(|config| !config.is_placeholder())
+                .for_each(|config| println!("// Detected {}: {}", config.name, config.path));
+
+            println!("{config}");
+
+            process::exit(0);
+        }
+
+        Schema => {
+            let schema = DotConfig::json_schema().context("Failed to generate the JSON schema")?;
+
+            println!("{schema}");
+
+            process::exit(0);
+        }
+
+        ImportStow(args::ImportStowArgs { dir }) => {
+            let config = Options::default()
+                .with_default_extension(Extensions::IMPLICIT_SOME)
+                .to_string_pretty(
+                    &DotConfig::from_stow(dir).context("Failed to import the stow directory")?,
+                    utils::get_ron_formatter(),
+                )
+                .context("Failed to print the imported config")?;
+
+            println!("{config}");
+
+            process::exit(0);
+        }
+
+        Hash(args::HashArgs { path, algo }) => {
+            let algorithm = algo
+                .as_deref()
+                .map(str::parse::<HashAlgorithm>)
+                .transpose()
+                .context("Failed to parse the hash algorithm")?
+                .unwrap_or_default();
+
+            let hash =
+                hasher::get_path_hash(path.fix_path().unwrap_or(PathBuf::from(path)), algorithm)
+                    .with_context(|| format!("Failed to hash {path:?}"))?;
+
+            println!("{hash}  {path}");
+
+            process::exit(0);
+        }
+
+        _ => {}
+    }
+
+    // Config paths starting with ~ can't be resolved without it
+    utils::home_dir().context("Failed to find the home directory")?;
+
+    dotconfig = match DotConfig::parse_dotconfig(&config_path) {
+        Ok(dotconfig) => dotconfig,
+        Err(err) => match err.downcast::<ConfigNotFound>() {
+            Ok(not_found) => match offer_new_config(&not_found, args.assume_yes)? {
+                true => process::exit(0),
+                false => process::exit(1),
+            },
+            Err(err) => return Err(err.context("Failed to parse custom config file")),
+        },
+    };
+
+    if let Some(dotconfigs_path) = &args.dotconfigs_path {
+        dotconfig.override_dotconfigs_path(
+            dotconfigs_path
+                .parse()
+                .context("Failed to parse the dotconfigs path")?,
+        );
+    }
+
+    if let Some(hash_algo) = &args.hash_algo {
+        dotconfig.set_hash_algorithm(
+            hash_algo
+                .parse()
+                .context("Failed to parse the hash algorithm")?,
+        );
+    }
+
+    if args.dry_run {
+        info!("Dry run, printing the changes instead of making them");
+    }
+
+    if matches!(command, Pull(_) | ForcePull(_)) {
+        dotconfig
+            .sync_remote()
+            .context("Failed to update the dotconfigs repository")?;
+    }
 
-    match args.command {
-        Add(args::AddArgs { name, path }) => {
+    if matches!(
+        command,
+        ForcePush(_)
+            | ForcePull(_)
+            | Pull(_)
+            | Push(_)
+            | Sync
+            | Add(_)
+            | Rename(_)
+            | Status
+            | Repair(_)
+            | Clean
+            | Cat(_)
+            | Resolve(_)
+    ) {
+        dotconfig
+            .ensure_github_cloned()
+            .context("Failed to clone the dotconfigs repository")?;
+    }
+
+    if args.verbose {
+        eprintln!("{}", dotconfig.runtime_summary());
+    }
+
+    match command {
+        Add(args::AddArgs {
+            name,
+            path,
+            overwrite,
+            conf_type,
+        }) => {
+            let conf_type = conf_type
+                .as_deref()
+                .map(str::parse)
+                .transpose()
+                .context("Failed to parse the config type")?;
             let path = path.fix_path().unwrap_or(PathBuf::from(path));
             dotconfig
-                .add_config(&name, path)
+                .add_config(&name, path, overwrite, conf_type)
                 .context("Failed to insert config")?;
 
-            dotconfig
+            let (_, summary) = dotconfig
                 .pull_updated_configs()
                 .context("Failed to sync the newly inserted config")?;
+            print_report(&summary, started);
 
             dotconfig
                 .save_configs()
                 .context("Failed to save config file")?;
 
+            exit_on_errors(&summary);
             println!("Successfully added {name:?} to the config file");
 
             process::exit(0);
         }
 
         Clean => {
+            confirm_destructive(&dotconfig, "clean", args.assume_yes)?;
+
             dotconfig
                 .clean_dotconfigs_dir()
                 .context("Failed to clean all the configs inside the dotconfig directory")?;
@@ -52,16 +271,23 @@ fn main() -> Result<()> {
             process::exit(0);
         }
 
-        ClearMetadata => {
-            dotconfig
-                .clean_metadata_from_configs()
-                .context("Failed to clear the metadata from the config file")?;
+        ClearMetadata(args::ClearMetadataArgs { name }) => {
+            match &name {
+                Some(name) => dotconfig.clear_metadata_for(name),
+                None => dotconfig.clean_metadata_from_configs(),
+            }
+            .context("Failed to clear the metadata from the config file")?;
 
             dotconfig
                 .save_configs()
                 .context("Failed to save config file")?;
 
-            println!("Successfully cleared the metadata from the config file");
+            match name {
+                Some(name) => {
+                    println!("Successfully cleared the metadata of {name:?} from the config file")
+                }
+                None => println!("Successfully cleared the metadata from the config file"),
+            }
 
             process::exit(0);
         }
@@ -80,24 +306,42 @@ fn main() -> Result<()> {
             process::exit(0);
         }
 
-        ForcePull => {
-            dotconfig
-                .clean_dotconfigs_dir()
-                .context("Failed to clean all the configs inside the dotconfig directory")?;
+        ForcePull(args::FilterArgs {
+            name,
+            exclude_config,
+        }) => {
+            let _lock = lock_configs()?;
+            check_free_space(&dotconfig, args.no_space_check)?;
 
-            dotconfig
+            // Cleaning would also remove the configs of the other profiles
+            // and of the other configs than the selected ones
+            if args.profile.is_none() && name.is_none() && exclude_config.is_empty() {
+                confirm_destructive(&dotconfig, "force pull", args.assume_yes)?;
+
+                dotconfig
+                    .clean_dotconfigs_dir()
+                    .context("Failed to clean all the configs inside the dotconfig directory")?;
+            }
+
+            let summary = dotconfig
                 .force_pull_configs()
                 .context("Failed to force pull configs")?;
+            print_report(&summary, started);
+            exit_on_errors(&summary);
 
             println!("Successfully force pulled the configs");
 
             process::exit(0);
         }
 
-        ForcePush => {
-            dotconfig
+        ForcePush(_) => {
+            let _lock = lock_configs()?;
+
+            let summary = dotconfig
                 .force_push_configs()
                 .context("Failed to force push configs")?;
+            print_report(&summary, started);
+            exit_on_errors(&summary);
 
             println!("Successfully force pushed the configs");
 
@@ -110,41 +354,222 @@ fn main() -> Result<()> {
             process::exit(0);
         }
 
-        PrintNew => {
-            let config = Options::default()
-                .with_default_extension(Extensions::IMPLICIT_SOME)
-                .to_string_pretty(&DotConfig::new(), utils::get_ron_formatter())
-                .context("Failed to print the new config")?;
+        Pull(args::PullArgs { commit, .. }) => {
+            let _lock = lock_configs()?;
+            check_free_space(&dotconfig, args.no_space_check)?;
 
-            println!("{config}");
-
-            process::exit(0);
-        }
-
-        Pull => {
-            dotconfig
+            let (_, summary) = dotconfig
                 .pull_updated_configs()
                 .context("Failed to pull updated configs")?;
+            print_report(&summary, started);
 
             dotconfig
                 .save_configs()
                 .context("Failed to save config file")?;
 
+            exit_on_errors(&summary);
+
+            if commit {
+                dotconfig
+                    .commit_and_push(None)
+                    .context("Failed to publish the pulled configs")?;
+            }
             println!("Successfully updated the config file");
 
             process::exit(0);
         }
 
-        Push => {
-            dotconfig
+        Push(_) => {
+            let _lock = lock_configs()?;
+
+            let summary = dotconfig
                 .push_updated_configs()
                 .context("Failed to push configs")?;
+            print_report(&summary, started);
+            exit_on_errors(&summary);
 
             println!("Successfully pushed the updated configs");
 
             process::exit(0);
         }
 
+        Status => {
+            let states = dotconfig
+                .status()
+                .context("Failed to compare the configs with the dotconfigs directory")?;
+            print_status(&states);
+
+            process::exit(0);
+        }
+
+        Rename(args::RenameArgs { old, new }) => {
+            let _lock = lock_configs()?;
+
+            dotconfig
+                .rename_config(&old, &new)
+                .context("Failed to rename the config")?;
+
+            dotconfig
+                .save_configs()
+                .context("Failed to save config file")?;
+
+            println!("Successfully renamed {old:?} to {new:?}");
+
+            process::exit(0);
+        }
+
+        Repair(_) => {
+            let _lock = lock_configs()?;
+            check_free_space(&dotconfig, args.no_space_check)?;
+
+            let summary = dotconfig
+                .repair()
+                .context("Failed to repair the dotconfigs directory")?;
+            print_report(&summary, started);
+
+            dotconfig
+                .save_configs()
+                .context("Failed to save config file")?;
+
+            exit_on_errors(&summary);
+            println!("Successfully repaired the dotconfigs directory");
+
+            process::exit(0);
+        }
+
+        Transfer(args::TransferArgs { from, to }) => {
+            let from = from.fix_path().unwrap_or(PathBuf::from(from));
+            let to = to.fix_path().unwrap_or(PathBuf::from(to));
+            let summary = dotconfig
+                .transfer(&from, &to)
+                .with_context(|| format!("Failed to transfer the configs to {to:?}"))?;
+            print_report(&summary, started);
+
+            exit_on_errors(&summary);
+            println!("Successfully transferred the configs to {}", to.display());
+
+            process::exit(0);
+        }
+
+        Sync => {
+            let _lock = lock_configs()?;
+
+            let summary = dotconfig.sync_configs().context("Failed to sync configs")?;
+
+            dotconfig
+                .save_configs()
+                .context("Failed to save config file")?;
+
+            print_report(&summary, started);
+            println!("{summary}");
+
+            process::exit(summary.exit_code());
+        }
+
+        Doctor(args::DoctorArgs { stale_after }) => {
+            let config_path = DotConfig::config_file_path(&config_path)
+                .context("Failed to find the config file")?;
+            println!("Config file: {}", config_path.display());
+
+            let warnings = dotconfig
+                .validate()
+                .context("Failed to validate the config file")?;
+
+            // Stale configs are only reported, and don't fail the check
+            if let Some(stale_after) = stale_after {
+                let threshold = utils::parse_duration(&stale_after)
+                    .context("Failed to parse the --stale-after duration")?;
+
+                let now = utils::unix_now();
+                dotconfig
+                    .stale_configs(threshold)
+                    .iter()
+                    .for_each(|config| {
+                        let days = now.saturating_sub(config.last_sync.unwrap_or(now)) / 86400;
+                        println!("Note: {:#?} not synced in {days} days", config.name);
+                    });
+            }
+
+            if warnings.is_empty() {
+                println!("No problems found in the config file");
+                process::exit(0);
+            }
+
+            warnings
+                .iter()
+                .for_each(|warning| println!("Warning: {warning}"));
+
+            process::exit(1);
+        }
+
+        DiffConfigs(args::DiffConfigsArgs { other, json }) => {
+            let other = DotConfig::from_file(&other)
+                .with_context(|| format!("Failed to parse the config file {other:?}"))?;
+            let diff = dotconfig.diff(&other);
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&diff).context("Failed to serialize the diff")?
+                );
+            } else if diff.is_empty() {
+                println!("No differences between the config files");
+            } else {
+                print!("{diff}");
+            }
+
+            process::exit(0);
+        }
+
+        Cat(args::CatArgs { name }) => {
+            dotconfig
+                .cat_config(&name, &mut std::io::stdout().lock())
+                .with_context(|| format!("Failed to print {name:?}"))?;
+
+            process::exit(0);
+        }
+
+        Resolve(args::ResolveArgs { name }) => {
+            let path = dotconfig
+                .resolve_repo_path(&name)
+                .with_context(|| format!("Failed to resolve {name:?}"))?;
+
+            println!("{}", path.display());
+
+            process::exit(0);
+        }
+
+        DumpResolved => {
+            let rows = dotconfig
+                .debug_resolution()
+                .into_iter()
+                .map(|entry| {
+                    [
+                        entry.name,
+                        entry.path,
+                        entry.resolved_path.display().to_string(),
+                        entry
+                            .dotconfigs_path
+                            .map_or(String::from("-"), |path| path.display().to_string()),
+                        entry
+                            .conf_type
+                            .map_or(String::from("-"), |conf_type| format!("{conf_type:?}")),
+                    ]
+                })
+                .collect::<Vec<[String; 5]>>();
+
+            print_table(
+                ["NAME", "PATH", "RESOLVED", "DOTCONFIGS", "TYPE"].map(String::from),
+                &rows,
+            );
+
+            process::exit(0);
+        }
+
+        PrintNew(_) | Schema | ImportStow(_) | Hash(_) => {
+            unreachable!("handled before parsing the config file")
+        }
+
         Edit => {
             dotconfig
                 .edit_config_file()
@@ -154,3 +579,181 @@ fn main() -> Result<()> {
         }
     }
 }
+
+/// Print the errors of a sync operation, followed by the configs grouped by
+/// their outcome and the size of the copied files along with the time the
+/// command took.
+fn print_report(summary: &SyncSummary, started: Instant) {
+    summary
+        .errors
+        .iter()
+        .for_each(|(name, error)| eprintln!("Failed to sync {name:?}: {error}"));
+
+    let report = summary.grouped_report();
+    if !report.is_empty() {
+        info!("{report}");
+    }
+
+    let unchanged = match summary.copied.files_unchanged {
+        0 => String::new(),
+        files => format!(", {files} unchanged"),
+    };
+    info!(
+        "Copied {} ({} files{unchanged}) in {:.1}s",
+        utils::format_bytes(summary.copied.bytes),
+        summary.copied.files_copied,
+        started.elapsed().as_secs_f64()
+    );
+}
+
+/// Print the state of every config, colored when printing to a terminal
+/// (unless `NO_COLOR` is set).
+fn print_status(states: &[(String, SyncState)]) {
+    let color = io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    let width = states
+        .iter()
+        .map(|(_, state)| state.to_string().len())
+        .max()
+        .unwrap_or(0);
+
+    for (name, state) in states {
+        let code = match state {
+            SyncState::UpToDate => "32",
+            SyncState::LocalNewer => "33",
+            SyncState::RepoNewer => "36",
+            SyncState::Missing => "90",
+            SyncState::Conflict => "31",
+        };
+        let state = format!("{:<width$}", state.to_string());
+
+        match color {
+            true => println!("\x1b[{code}m{state}\x1b[0m  {name}"),
+            false => println!("{state}  {name}"),
+        }
+    }
+}
+
+/// Check that there is enough free space to pull the configs, unless the
+/// check is skipped with `--no-space-check`.
+fn check_free_space(dotconfig: &DotConfig, skip: bool) -> Result<()> {
+    if skip {
+        return Ok(());
+    }
+
+    dotconfig
+        .check_free_space()
+        .context("Failed to check the free space of the dotconfigs directory")?;
+
+    Ok(())
+}
+
+/// Exit with the exit code of a sync operation if any config failed to sync.
+fn exit_on_errors(summary: &SyncSummary) {
+    if !summary.errors.is_empty() {
+        process::exit(summary.exit_code());
+    }
+}
+
+/// Print rows as a table with left-aligned columns.
+fn print_table<const N: usize>(header: [String; N], rows: &[[String; N]]) {
+    let mut widths = header.clone().map(|column| column.len());
+    rows.iter().for_each(|row| {
+        row.iter()
+            .zip(widths.iter_mut())
+            .for_each(|(column, width)| *width = (*width).max(column.len()));
+    });
+
+    std::iter::once(&header).chain(rows).for_each(|row| {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(column, width)| format!("{column:width$}"))
+            .collect::<Vec<String>>()
+            .join("  ");
+
+        println!("{}", line.trim_end());
+    });
+}
+
+/// Lock the config file against other sync-dotfiles processes.
+///
+/// Exits with code 3 if the lock is held by another process.
+fn lock_configs() -> Result<FileLock> {
+    match DotConfig::try_lock().context("Failed to lock the config file")? {
+        Some(lock) => Ok(lock),
+        None => {
+            eprintln!("Another sync-dotfiles process is running, try again later");
+            process::exit(3);
+        }
+    }
+}
+
+/// Print a summary of the dotconfigs directory and ask the user to confirm
+/// the destructive `action` before it wipes the directory.
+///
+/// Exits the process without doing anything if the user declines.
+fn confirm_destructive(dotconfig: &DotConfig, action: &str, assume_yes: bool) -> Result<()> {
+    let (files, bytes) = dotconfig
+        .summarize_dotconfigs()
+        .context("Failed to summarize the dotconfigs directory")?;
+
+    let prompt = format!(
+        "About to {action} {:?}: removing {files} files, {}. Continue?",
+        dotconfig.dotconfigs_path,
+        utils::format_bytes(bytes)
+    );
+
+    if assume_yes {
+        println!("{prompt} [y/N] y");
+        return Ok(());
+    }
+
+    if !utils::confirm(&prompt)? {
+        println!("Aborted {action}");
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Offer to create a new config file when none is found on the first run.
+///
+/// The user is asked for confirmation unless `-y` is given. When the
+/// standard input isn't a terminal, the command creating the config file is
+/// printed instead.
+///
+/// Returns `true` if the config file was created.
+fn offer_new_config(not_found: &ConfigNotFound, assume_yes: bool) -> Result<bool> {
+    let path = &not_found.path;
+
+    if !assume_yes && !io::stdin().is_terminal() {
+        eprintln!("{not_found}");
+        eprintln!("Create one with:");
+        match path.parent().filter(|parent| !parent.exists()) {
+            Some(parent) => eprintln!(
+                "  mkdir -p {} && sync-dotfiles-rs new > {}",
+                parent.display(),
+                path.display()
+            ),
+            None => eprintln!("  sync-dotfiles-rs new > {}", path.display()),
+        }
+        return Ok(false);
+    }
+
+    let prompt = format!("No config found. Create one at {}?", path.display());
+    if assume_yes {
+        println!("{prompt} [y/N] y");
+    } else if !utils::confirm(&prompt)? {
+        eprintln!("{not_found}");
+        return Ok(false);
+    }
+
+    DotConfig::create_config_file(path).context("Failed to create the config file")?;
+
+    println!(
+        "Created {}, add your configs with `sync-dotfiles-rs add` and rerun the command",
+        path.display()
+    );
+
+    Ok(true)
+}
@@ -0,0 +1,86 @@
+use crate::dotconfig::ConfigFormat;
+use lazy_static::lazy_static;
+use std::{path::PathBuf, sync::RwLock};
+
+/// Runtime options controlling how configs are synced.
+///
+/// The `SyncOptions` struct holds the options that are set from the command
+/// line for a single run and are not stored in the config file.
+/// The options are stored globally so that they are accessible from the
+/// config syncing functions without passing them through every call.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::options::{self, SyncOptions};
+///
+/// options::set(SyncOptions {
+///     make_writable: true,
+///     ..Default::default()
+/// });
+///
+/// assert!(options::get().make_writable);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SyncOptions {
+    /// Clear the read-only bit of destination files on push, and restore it
+    /// after the files are written.
+    pub make_writable: bool,
+    /// Override of the home directory used to resolve the config paths.
+    pub home: Option<PathBuf>,
+    /// Name of the profile selecting the configs to be synced.
+    pub profile: Option<String>,
+    /// Name of the single config to be synced.
+    pub name: Option<String>,
+    /// Names of the configs skipped when syncing, even if they are selected
+    /// by the profile or name.
+    pub exclude_config: Vec<String>,
+    /// Only print errors, warnings and summaries.
+    pub quiet: bool,
+    /// Report a missing config path as an error when hashing it, instead of
+    /// hashing it as an empty string.
+    pub strict_hash: bool,
+    /// Hash every copied config again after it is written, and report a
+    /// mismatch with its source as an error.
+    pub verify_after: bool,
+    /// Format of the config file, overriding the detection from its
+    /// extension.
+    pub config_format: Option<ConfigFormat>,
+    /// Print the changes of the config file as a diff before it is saved.
+    pub show_config_diff: bool,
+    /// Save the config file gzip compressed, replacing an uncompressed
+    /// config file.
+    pub compress_config: bool,
+    /// Write optional values of the RON config file as `Some(...)` instead
+    /// of using the `implicit_some` extension.
+    pub explicit_some: bool,
+    /// Copy the changed configs on pull without updating their metadata.
+    pub no_metadata_update: bool,
+    /// Expand the config paths containing wildcards into the files they
+    /// match when syncing.
+    pub expand_globs_in_config: bool,
+    /// Stop at the first config that fails to sync, instead of syncing the
+    /// other configs and reporting all the failures at the end.
+    pub fail_fast: bool,
+    /// Print the files that would be copied, moved or removed (and the
+    /// config file that would be saved) instead of changing them.
+    pub dry_run: bool,
+}
+
+lazy_static! {
+    /// RwLock-protected global sync options.
+    ///
+    /// This static variable stores the options of the current run and allows
+    /// them to be accessed safely from multiple threads.
+    static ref OPTIONS: RwLock<SyncOptions> = RwLock::new(SyncOptions::default());
+}
+
+/// Get a copy of the current sync options.
+pub fn get() -> SyncOptions {
+    OPTIONS.read().unwrap().clone()
+}
+
+/// Replace the current sync options.
+pub fn set(options: SyncOptions) {
+    *OPTIONS.write().unwrap() = options;
+}
@@ -0,0 +1,175 @@
+use crate::hasher::{self, HashAlgorithm};
+use anyhow::{anyhow, Result};
+use std::{
+    fs,
+    io::{self, Read},
+    path::Path,
+};
+
+/// First line of every stub file.
+const STUB_HEADER: &str = "sync-dotfiles stub v1";
+
+/// Maximum size of a stub file, larger files are never read as stubs.
+const MAX_STUB_SIZE: u64 = 4096;
+
+/// A pointer stored in the dotconfigs directory instead of a large file.
+///
+/// Files of a directory config larger than the `lfs_threshold` are pulled as
+/// a small stub recording their hash, size and source, instead of their
+/// contents. When hashing a directory, a stub is hashed as the file it
+/// points to, so the stubbed copy matches the original.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::{hasher::HashAlgorithm, stub::Stub};
+///
+/// let path = format!("{}/examples/config.ron", env!("CARGO_MANIFEST_DIR"));
+/// let stub = Stub::from_file(path.as_ref(), HashAlgorithm::Sha1).expect("Failed to create stub");
+///
+/// assert_eq!(stub.size, std::fs::metadata(&path).unwrap().len());
+/// assert!(stub.hash.starts_with("sha1:"));
+/// ```
+#[derive(Debug, PartialEq, Eq)]
+pub struct Stub {
+    /// Hash of the original file, prefixed with its algorithm.
+    pub hash: String,
+    /// Size of the original file in bytes.
+    pub size: u64,
+    /// Path the original file was pulled from.
+    pub source: String,
+}
+
+impl Stub {
+    /// Create the stub of a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file to be stubbed.
+    /// * `algorithm` - The hash algorithm of the config the file belongs to.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the stub, or an error if the file can't be read.
+    pub fn from_file(path: &Path, algorithm: HashAlgorithm) -> io::Result<Self> {
+        let digest = hasher::get_file_hash(path, &mut algorithm.hasher())?;
+
+        Ok(Stub {
+            hash: hasher::format_hash(algorithm, &digest),
+            size: fs::metadata(path)?.len(),
+            source: path.display().to_string(),
+        })
+    }
+
+    /// Read a stub file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file to be read.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the stub, `None` if the file is not a stub, or an
+    /// error if the file can't be read.
+    pub fn read(path: &Path) -> io::Result<Option<Self>> {
+        if fs::metadata(path)?.len() > MAX_STUB_SIZE {
+            return Ok(None);
+        }
+
+        let mut content = String::new();
+        if fs::File::open(path)?.read_to_string(&mut content).is_err() {
+            // Binary files are not stubs
+            return Ok(None);
+        }
+
+        let mut lines = content.lines();
+        if lines.next() != Some(STUB_HEADER) {
+            return Ok(None);
+        }
+
+        let (mut hash, mut size, mut source) = (None, None, None);
+        for line in lines {
+            match line.split_once(' ') {
+                Some(("hash", value)) => hash = Some(value.to_string()),
+                Some(("size", value)) => size = value.parse().ok(),
+                Some(("source", value)) => source = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        match (hash, size, source) {
+            (Some(hash), Some(size), Some(source)) => Ok(Some(Stub { hash, size, source })),
+            _ => Err(io::Error::other(format!("Invalid stub file: {:#?}", path))),
+        }
+    }
+
+    /// Write the stub to a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the stub file to be written.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or an error if the file can't be written.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        fs::write(
+            path,
+            format!(
+                "{STUB_HEADER}\nhash {}\nsize {}\nsource {}\n",
+                self.hash, self.size, self.source
+            ),
+        )
+    }
+
+    /// Get the unprefixed digest of the original file.
+    pub fn digest(&self) -> &str {
+        hasher::parse_hash(&self.hash).1
+    }
+}
+
+/// Replace the stubs inside a pushed directory with the original files.
+///
+/// The original files are taken from the current directory of the config,
+/// since the dotconfigs directory only stores their stubs.
+///
+/// # Arguments
+///
+/// * `dir` - The pushed directory, which may contain stubs.
+/// * `originals` - The directory containing the original files.
+///
+/// # Returns
+///
+/// A Result indicating success, or an error if the original of a stub is
+/// missing or its contents differ from the stubbed file.
+pub fn restore_stubs(dir: &Path, originals: &Path) -> Result<()> {
+    hasher::list_dir_files(dir)?.into_iter().try_for_each(|file| {
+        let Some(stub) = Stub::read(&file)? else {
+            return Ok(());
+        };
+
+        let relative_path = file.strip_prefix(dir)?;
+        let original = originals.join(relative_path);
+
+        // The stub is checked with the algorithm it was created with
+        let available = original.is_file()
+            && match hasher::parse_hash(&stub.hash) {
+                (Some(algorithm), digest) => {
+                    hasher::get_file_hash(&original, &mut algorithm.hasher())? == digest
+                }
+                (None, _) => false,
+            };
+        if !available {
+            return Err(anyhow!(
+                "{:#?} is stored as a stub of {} bytes from {:#?}, and its content is not available at {:#?}",
+                relative_path,
+                stub.size,
+                stub.source,
+                original
+            ));
+        }
+
+        fs::copy(&original, &file)?;
+        Ok(())
+    })
+}
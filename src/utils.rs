@@ -1,31 +1,145 @@
+use crate::options;
 use anyhow::{anyhow, Result};
+use lazy_static::lazy_static;
+use rayon::prelude::*;
 use ron::{extensions::Extensions, ser::PrettyConfig};
 use std::process::Command;
-use std::{env, path::PathBuf};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    env, fs,
+    io::{self, Read, Write},
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{MetadataExt, PermissionsExt},
+    },
+    path::{Path, PathBuf},
+    sync::RwLock,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Environment variable overriding the home directory, meant for tests.
+pub const TEST_HOME_ENV: &str = "SYNC_DOTFILES_TEST_HOME";
+
+/// Variables expanding to the local dotconfigs directory in config paths.
+const DOTFILES_VARS: [&str; 2] = ["$DOTFILES", "$DOTCONFIGS"];
+
+lazy_static! {
+    /// RwLock-protected local dotconfigs directory of the loaded config.
+    ///
+    /// This static variable stores the directory that the `$DOTFILES` and
+    /// `$DOTCONFIGS` variables of the config paths expand to.
+    static ref DOTFILES_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+/// Get the home directory used to resolve paths.
+///
+/// The `--home` option takes precedence, followed by the
+/// `SYNC_DOTFILES_TEST_HOME` environment variable and finally the `HOME`
+/// environment variable, read at runtime.
+///
+/// # Returns
+///
+/// A Result containing the home directory, or an error if none of them is
+/// set.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::fix_path;
+/// use sync_dotfiles_rs::options::{self, SyncOptions};
+/// use sync_dotfiles_rs::utils::{home_dir, FixPath};
+/// use std::path::PathBuf;
+///
+/// options::set(SyncOptions {
+///     home: Some(PathBuf::from("/tmp/home")),
+///     ..Default::default()
+/// });
+///
+/// assert_eq!(home_dir().unwrap(), PathBuf::from("/tmp/home"));
+/// assert_eq!(fix_path!("~/.vimrc"), PathBuf::from("/tmp/home/.vimrc"));
+/// ```
+pub fn home_dir() -> Result<PathBuf> {
+    if let Some(home) = options::get().home {
+        return Ok(home);
+    }
+
+    [TEST_HOME_ENV, "HOME"]
+        .into_iter()
+        .filter_map(env::var_os)
+        .find(|home| !home.is_empty())
+        .map(PathBuf::from)
+        .ok_or_else(|| anyhow!("The HOME environment variable is not set, use --home to set it"))
+}
+
+/// Get the dotconfigs directory that `$DOTFILES` and `$DOTCONFIGS` expand
+/// to in config paths.
+///
+/// The directory is set when the config file is loaded, and is `None` if the
+/// dotconfigs path is not a local directory.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::fix_path;
+/// use sync_dotfiles_rs::utils::{dotfiles_dir, set_dotfiles_dir, FixPath};
+/// use std::path::PathBuf;
+///
+/// set_dotfiles_dir(Some(PathBuf::from("/tmp/dotfiles")));
+///
+/// assert_eq!(dotfiles_dir(), Some(PathBuf::from("/tmp/dotfiles")));
+/// assert_eq!(fix_path!("$DOTFILES/bin"), PathBuf::from("/tmp/dotfiles/bin"));
+/// assert_eq!(fix_path!("$DOTCONFIGS"), PathBuf::from("/tmp/dotfiles"));
+/// ```
+pub fn dotfiles_dir() -> Option<PathBuf> {
+    DOTFILES_DIR.read().unwrap().clone()
+}
+
+/// Set the dotconfigs directory that `$DOTFILES` and `$DOTCONFIGS` expand
+/// to, see `dotfiles_dir`.
+pub fn set_dotfiles_dir(dir: Option<PathBuf>) {
+    *DOTFILES_DIR.write().unwrap() = dir;
+}
+
+/// Expand a leading `$DOTFILES` or `$DOTCONFIGS` variable of a path to the
+/// dotconfigs directory.
+///
+/// Returns `None` if the path doesn't start with a variable, or if the
+/// dotconfigs directory is unknown.
+fn expand_dotfiles_var(path: &str) -> Option<PathBuf> {
+    let dir = dotfiles_dir()?;
+
+    DOTFILES_VARS.iter().find_map(|var| {
+        let rest = path.strip_prefix(var)?;
+        if rest.is_empty() {
+            Some(dir.clone())
+        } else {
+            rest.strip_prefix('/').map(|rest| dir.join(rest))
+        }
+    })
+}
 
 /// A macro that fixes a path to ensure it is absolute and not relative.
 ///
-/// The `fix_path` macro takes two expressions as arguments: `path` and
-/// `alt_path`. It evaluates `path` and returns a modified version of it that
-/// is guaranteed to be an absolute path.
+/// The `fix_path` macro takes a single expression as argument: `path`.
+/// It evaluates `path` and returns a modified version of it that is
+/// guaranteed to be an absolute path.
 ///
 /// If `path` is already an absolute path, it remains unchanged. However,
 /// if `path` is a relative path, it is converted to an absolute path based on
-/// the current working directory or the user's home directory.
+/// the current working directory or the user's home directory. A leading
+/// `$DOTFILES` or `$DOTCONFIGS` variable is expanded to the dotconfigs
+/// directory.
 ///
-/// If `path` cannot be converted to an absolute path or is already an
-/// absolute, the macro falls back to `alt_path`.
+/// If `path` does not need to be fixed, the macro falls back to `path`
+/// itself converted into a `PathBuf`.
 ///
 /// # Arguments
 ///
 /// - `$path:expr`: An expression that represents the path to be fixed.
-/// - `$alt_path:expr`: An expression that represents a fallback path to be
-///   used if the conversion to an absolute path fails.
 ///
 /// # Returns
 ///
-/// The macro returns an expression that represents the fixed path, which is
-/// guaranteed to be an absolute path or the provided fallback path.
+/// The macro returns an expression that represents the fixed path.
 ///
 /// # Examples
 ///
@@ -34,12 +148,12 @@ use std::{env, path::PathBuf};
 /// use crate::sync_dotfiles_rs::utils::FixPath;
 /// use std::path::PathBuf;
 ///
-/// let absolute_path = fix_path!("./absolute/path", "/fallback/path".into());
+/// let absolute_path = fix_path!("./absolute/path");
 /// assert_eq!(absolute_path,
 ///     PathBuf::from(
 ///         format!("{}/{}", env!("CARGO_MANIFEST_DIR"), "/absolute/path")));
 ///
-/// let relative_path = fix_path!("~/relative/path", "/fallback/path".into());
+/// let relative_path = fix_path!("~/relative/path");
 /// assert_eq!(relative_path,
 ///     PathBuf::from(format!("{}/{}", env!("HOME"), "/relative/path")));
 /// ```
@@ -55,6 +169,29 @@ macro_rules! fix_path {
     };
 }
 
+/// A macro that prints a progress message, unless the `--quiet` option is
+/// set.
+///
+/// It takes the same arguments as `println!`. Errors, warnings and
+/// summaries should keep using `println!`/`eprintln!` so they are shown in
+/// the quiet mode as well.
+///
+/// # Examples
+///
+/// ```rust
+/// use sync_dotfiles_rs::info;
+///
+/// info!("Updating {:#?}.", "nvim");
+/// ```
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if !$crate::options::get().quiet {
+            println!($($arg)*);
+        }
+    };
+}
+
 /// A trait for fixing paths to ensure they are absolute and not relative
 /// For example, ~/Downloads will be converted to /home/username/Downloads
 ///
@@ -105,7 +242,13 @@ pub trait FixPath<T> {
 impl FixPath<PathBuf> for PathBuf {
     /// Fix the path to be absolute and not relative for PathBuf type
     fn fix_path(&self) -> Option<PathBuf> {
-        let home_dir = PathBuf::from(env!("HOME"));
+        if let Some(path) = self.to_str().and_then(expand_dotfiles_var) {
+            return Some(path);
+        }
+
+        let Ok(home_dir) = home_dir() else {
+            return None;
+        };
 
         // Check if the path starts with ./ replace it with the current directory
         // and if it starts with ~/ then replace it with the home directory
@@ -123,16 +266,9 @@ impl FixPath<PathBuf> for PathBuf {
             );
         } else if self.starts_with("/home/") {
             // check if the username is the same as the current user
-            if self.components().nth(2).unwrap().as_os_str()
-                != home_dir.components().nth(2).unwrap().as_os_str()
-            {
+            if self.components().nth(2) != home_dir.components().nth(2) {
                 // Remove the /home/username/ part from the path
-                return Some(
-                    self.strip_prefix("/home/")
-                        .map(|p| p.strip_prefix(p.components().next().unwrap()).unwrap())
-                        .expect("Failed to strip prefix")
-                        .into(),
-                );
+                return Some(self.components().skip(3).collect());
             }
         }
 
@@ -180,7 +316,13 @@ impl FixPath<String> for String {
             return Some(std::path::PathBuf::new());
         }
 
-        let home_dir = PathBuf::from(env!("HOME"));
+        if let Some(path) = expand_dotfiles_var(self) {
+            return Some(path);
+        }
+
+        let Ok(home_dir) = home_dir() else {
+            return None;
+        };
 
         // Check if the path starts with ./ replace it with the current directory
         // and if it starts with ~/ then replace it with the home directory
@@ -247,7 +389,13 @@ impl FixPath<&str> for &str {
             return Some(std::path::PathBuf::new());
         }
 
-        let home_dir = PathBuf::from(env!("HOME"));
+        if let Some(path) = expand_dotfiles_var(self) {
+            return Some(path);
+        }
+
+        let Ok(home_dir) = home_dir() else {
+            return None;
+        };
 
         // Check if the path starts with ./ replace it with the current directory
         // and if it starts with ~/ then replace it with the home directory
@@ -278,6 +426,13 @@ impl FixPath<&str> for &str {
 /// If the destination directory exists, it will be removed and recreated to
 /// ensure a clean copy.
 ///
+/// The entries of every directory are copied in parallel on the global Rayon
+/// thread pool (whose size is set by the `--jobs` option). A directory is
+/// always created before its entries are copied into it.
+///
+/// Symlinks are recreated at the destination with the same target, see
+/// `copy_dir_with` to copy what they point to instead.
+///
 /// # Arguments
 ///
 /// * `from`: The source directory or file path to be copied.
@@ -302,44 +457,165 @@ pub fn copy_dir<T>(from: T, to: T) -> Result<()>
 where
     T: AsRef<std::path::Path>,
 {
-    let from = from.as_ref();
+    copy_dir_with(from, to, false)
+}
+
+/// Recursively copy a directory and its contents to another location,
+/// choosing how symlinks are copied, see `copy_dir`.
+///
+/// Without `follow_symlinks`, the symlinks are recreated at the destination
+/// with the same target, verbatim (even if it is relative or doesn't exist).
+/// With it, the files and directories they point to are copied instead, and
+/// broken symlinks are skipped. A symlink pointing to one of the directories
+/// being copied is recreated instead of being followed, so cyclic symlinks
+/// don't recurse forever.
+///
+/// # Arguments
+///
+/// * `from`: The source directory to be copied.
+/// * `to`: The destination directory where the source will be copied to.
+/// * `follow_symlinks`: Whether to copy what the symlinks point to instead
+///   of the symlinks themselves.
+///
+/// # Returns
+///
+/// Returns a `Result` indicating success or an error if the copy operation
+/// fails.
+///
+/// # Example
+///
+/// ```rust
+/// use std::{fs, os::unix::fs::symlink, path::Path};
+/// use sync_dotfiles_rs::utils::copy_dir_with;
+///
+/// let dir = std::env::temp_dir().join("sync-dotfiles-doctest-copy-symlinks");
+/// let _ = fs::remove_dir_all(&dir);
+/// fs::create_dir_all(dir.join("from/themes")).unwrap();
+/// fs::write(dir.join("from/themes/dark.toml"), "dark").unwrap();
+/// symlink("themes/dark.toml", dir.join("from/theme.toml")).unwrap();
+/// // A cycle, pointing back to the copied directory
+/// symlink("..", dir.join("from/themes/parent")).unwrap();
+///
+/// copy_dir_with(dir.join("from"), dir.join("links"), false).unwrap();
+/// assert_eq!(
+///     fs::read_link(dir.join("links/theme.toml")).unwrap(),
+///     Path::new("themes/dark.toml")
+/// );
+///
+/// copy_dir_with(dir.join("from"), dir.join("files"), true).unwrap();
+/// assert!(!dir.join("files/theme.toml").is_symlink());
+/// assert_eq!(fs::read_to_string(dir.join("files/theme.toml")).unwrap(), "dark");
+/// assert!(dir.join("files/themes/parent").is_symlink());
+///
+/// fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn copy_dir_with<T>(from: T, to: T, follow_symlinks: bool) -> Result<()>
+where
+    T: AsRef<std::path::Path>,
+{
+    let (from, to) = (from.as_ref(), to.as_ref());
 
     if !from.exists() {
         return Err(anyhow!(format!("Path does not exist: {:#?}", from)));
     }
 
-    if to.as_ref().exists() {
-        if let Err(e) = std::fs::remove_dir_all(&to) {
-            match e.kind() {
-                std::io::ErrorKind::PermissionDenied => {
-                    escape_privilege()?;
-                    std::fs::remove_dir_all(&to).expect("PermissionDenied removing directory");
-                }
+    if options::get().dry_run {
+        println!("Would copy {:#?} -> {:#?}", from, to);
+        return Ok(());
+    }
 
-                _ => println!("Error removing directory: {e}"),
-            }
-        }
+    // Escalate once up front, since the entries are copied in parallel
+    if !is_writable(to) {
+        escape_privilege()?;
     }
-    if let Err(e) = std::fs::create_dir_all(&to) {
-        match e.kind() {
-            std::io::ErrorKind::PermissionDenied => {
-                escape_privilege()?;
-                std::fs::create_dir_all(&to).expect("PermissionDenied creating directory");
-            }
 
-            _ => println!("Error creating directory: {e}"),
-        }
+    copy_dir_entries(from, to, follow_symlinks, &[])
+}
+
+/// Check if the current user can replace a path, i.e. write to it and to
+/// its parent, or to its nearest existing ancestor if it doesn't exist yet.
+fn is_writable(path: &Path) -> bool {
+    // Relative paths end in the current directory
+    let path = &Path::new(".").join(path);
+    let Some(existing) = path
+        .ancestors()
+        .find(|ancestor| fs::symlink_metadata(ancestor).is_ok())
+    else {
+        return false;
+    };
+
+    match existing.parent() {
+        Some(parent) if existing == path => can_write(path) && can_write(parent),
+        _ => can_write(existing),
     }
+}
+
+/// Check if the current user has write access to an existing path, which
+/// also takes the owner of the path and immutable files into account,
+/// unlike its permission bits.
+fn can_write(path: &Path) -> bool {
+    std::ffi::CString::new(path.as_os_str().as_bytes())
+        .is_ok_and(|c_path| unsafe { libc::access(c_path.as_ptr(), libc::W_OK) == 0 })
+}
+
+/// Copy a directory into `to`, see `copy_dir_with`.
+///
+/// `ancestors` are the device and inode numbers of the source directories
+/// containing `from`, used to detect cyclic symlinks.
+fn copy_dir_entries(
+    from: &Path,
+    to: &Path,
+    follow_symlinks: bool,
+    ancestors: &[(u64, u64)],
+) -> Result<()> {
+    let metadata = fs::metadata(from)?;
+    let mut ancestors = ancestors.to_vec();
+    ancestors.push((metadata.dev(), metadata.ino()));
+
+    if to.exists() {
+        fs::remove_dir_all(to).map_err(|e| anyhow!("Error removing directory {:#?}: {e}", to))?;
+    }
+    fs::create_dir_all(to).map_err(|e| anyhow!("Error creating directory {:#?}: {e}", to))?;
 
     std::fs::read_dir(from)?
         .filter_map(|e| e.ok())
-        .for_each(|entry| {
-            let filetype = entry.file_type().expect("Failed to read file type");
-            if filetype.is_dir() {
-                copy_dir(entry.path(), to.as_ref().join(entry.file_name()))
-                    .expect("Failed to copy directory");
+        .par_bridge()
+        .try_for_each(|entry| {
+            let mut filetype = entry.file_type()?;
+            if filetype.is_symlink() && follow_symlinks {
+                match fs::metadata(entry.path()) {
+                    Ok(metadata)
+                        if !(metadata.is_dir()
+                            && ancestors.contains(&(metadata.dev(), metadata.ino()))) =>
+                    {
+                        filetype = metadata.file_type();
+                    }
+                    // Following a cyclic symlink would copy forever
+                    Ok(_) => {}
+                    Err(_) => {
+                        info!("Skipping broken symlink: {:#?}", entry.path().display());
+                        return Ok(());
+                    }
+                }
+            }
+
+            if filetype.is_symlink() {
+                let target = fs::read_link(entry.path())?;
+                std::os::unix::fs::symlink(&target, to.join(entry.file_name())).map_err(|e| {
+                    anyhow!(
+                        "Error creating symlink {:#?}: {e}",
+                        to.join(entry.file_name())
+                    )
+                })?;
+            } else if filetype.is_dir() {
+                copy_dir_entries(
+                    &entry.path(),
+                    &to.join(entry.file_name()),
+                    follow_symlinks,
+                    &ancestors,
+                )?;
             } else if filetype.is_file() {
-                if let Err(e) = std::fs::copy(entry.path(), to.as_ref().join(entry.file_name())) {
+                if let Err(e) = std::fs::copy(entry.path(), to.join(entry.file_name())) {
                     match e.kind() {
                         std::io::ErrorKind::AlreadyExists => {
                             println!(
@@ -347,27 +623,731 @@ where
                                 entry.path().display()
                             )
                         }
-                        std::io::ErrorKind::PermissionDenied => {
-                            escape_privilege().expect("Failed to escape privilege");
-                            std::fs::copy(entry.path(), to.as_ref().join(entry.file_name()))
-                                .expect("PermissionDenied copying file");
-                        }
-                        _ => panic!("Error copying file: {e}"),
+                        _ => return Err(anyhow!("Error copying file {:#?}: {e}", entry.path())),
                     }
                 }
             } else {
-                println!("Skipping symlinks file: {:#?}", entry.path().display());
+                info!("Skipping special file: {:#?}", entry.path().display());
+            }
+
+            Ok(())
+        })?;
+
+    // Set the mode of the directory last, so a read-only directory can
+    // still be filled
+    copy_permissions(from, to)?;
+
+    Ok(())
+}
+
+/// Copy the permissions (e.g. the mode bits on Unix) of a file or directory
+/// to another one.
+///
+/// `fs::copy` already copies the permissions of files, but directories are
+/// created with the default permissions, so they are copied explicitly.
+/// Nothing is done on other platforms.
+///
+/// # Arguments
+///
+/// * `from`: The file or directory whose permissions are copied.
+/// * `to`: The file or directory whose permissions are set.
+///
+/// # Example
+///
+/// ```rust
+/// use std::fs;
+/// use std::os::unix::fs::PermissionsExt;
+/// use sync_dotfiles_rs::utils::copy_permissions;
+///
+/// let dir = std::env::temp_dir().join("sync-dotfiles-doctest-permissions");
+/// let _ = fs::remove_dir_all(&dir);
+/// fs::create_dir_all(dir.join("from")).unwrap();
+/// fs::create_dir_all(dir.join("to")).unwrap();
+/// fs::set_permissions(dir.join("from"), fs::Permissions::from_mode(0o700)).unwrap();
+///
+/// copy_permissions(dir.join("from"), dir.join("to")).unwrap();
+/// let mode = fs::metadata(dir.join("to")).unwrap().permissions().mode();
+/// assert_eq!(mode & 0o777, 0o700);
+///
+/// fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[cfg(unix)]
+pub fn copy_permissions<P, Q>(from: P, to: Q) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    fs::set_permissions(to, fs::metadata(from)?.permissions())
+}
+
+/// Copy the permissions of a file or directory to another one, see the
+/// Unix version.
+#[cfg(not(unix))]
+pub fn copy_permissions<P, Q>(_from: P, _to: Q) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    Ok(())
+}
+
+/// Compress a directory into a zstd compressed tar archive.
+///
+/// The entries are added in a sorted order with deterministic headers, so
+/// compressing unchanged contents produces the same archive. Like the
+/// uncompressed pull, entries belonging to git are skipped.
+///
+/// # Arguments
+///
+/// * `dir`: The directory to be compressed.
+/// * `archive`: The path of the `.tar.zst` archive to be created.
+///
+/// # Returns
+///
+/// Returns a `Result` indicating success or an error if the archive can't be
+/// written.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::utils::compress_dir;
+///
+/// match compress_dir("/path/to/directory", "/path/to/directory.tar.zst") {
+///     Ok(()) => println!("Compression successful"),
+///     Err(err) => eprintln!("Error compressing directory: {:?}", err),
+/// }
+/// ```
+pub fn compress_dir<P, Q>(dir: P, archive: Q) -> Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let dir = dir.as_ref();
+
+    let file = fs::File::create(archive.as_ref())
+        .map_err(|e| anyhow!("Failed to create archive {:#?}: {e}", archive.as_ref()))?;
+
+    let mut builder = tar::Builder::new(zstd::Encoder::new(file, 0)?);
+    builder.mode(tar::HeaderMode::Deterministic);
+    builder.follow_symlinks(false);
+
+    for entry in walkdir::WalkDir::new(dir)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let name = entry.path().strip_prefix(dir)?;
+        if is_git_path(name) {
+            continue;
+        }
+        builder.append_path_with_name(entry.path(), name)?;
+    }
+
+    builder.into_inner()?.finish()?;
+
+    Ok(())
+}
+
+/// Extract a zstd compressed tar archive into a directory.
+///
+/// If the destination directory exists, it will be removed and recreated to
+/// ensure a clean extraction, the same way `copy_dir` does.
+///
+/// # Arguments
+///
+/// * `archive`: The path of the `.tar.zst` archive to be extracted.
+/// * `dir`: The destination directory.
+///
+/// # Returns
+///
+/// Returns a `Result` indicating success or an error if the archive can't be
+/// read or extracted.
+pub fn extract_archive<P, Q>(archive: P, dir: Q) -> Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let dir = dir.as_ref();
+
+    let file = fs::File::open(archive.as_ref())
+        .map_err(|e| anyhow!("Failed to open archive {:#?}: {e}", archive.as_ref()))?;
+
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    fs::create_dir_all(dir)?;
+
+    // The archive headers are deterministic and don't carry the real mtimes
+    let mut unpacker = tar::Archive::new(zstd::Decoder::new(file)?);
+    unpacker.set_preserve_mtime(false);
+
+    unpacker
+        .unpack(dir)
+        .map_err(|e| anyhow!("Failed to extract {:#?}: {e}", archive.as_ref()))?;
+
+    Ok(())
+}
+
+/// An exclusive advisory lock on a file, released when dropped.
+pub struct FileLock {
+    _file: fs::File,
+}
+
+/// Try to acquire an exclusive advisory lock on a file.
+///
+/// The lock file is created if it doesn't exist, and is kept around after
+/// the lock is released.
+///
+/// # Arguments
+///
+/// * `path`: The path of the lock file.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the lock, `None` if the lock is held by
+/// another process, or an error if the lock file can't be opened.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::utils::try_lock_file;
+///
+/// let path = std::env::temp_dir().join("sync-dotfiles-doctest.lock");
+///
+/// let lock = try_lock_file(&path).unwrap();
+/// assert!(lock.is_some());
+/// ```
+pub fn try_lock_file<P>(path: P) -> Result<Option<FileLock>>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .map_err(|e| anyhow!("Failed to open lock file {:#?}: {e}", path))?;
+
+    match file.try_lock() {
+        Ok(()) => Ok(Some(FileLock { _file: file })),
+        Err(fs::TryLockError::WouldBlock) => Ok(None),
+        Err(fs::TryLockError::Error(e)) => Err(anyhow!("Failed to lock {:#?}: {e}", path)),
+    }
+}
+
+/// Find the entries whose names differ only in case within the same directory.
+///
+/// Such entries are distinct on case-sensitive filesystems, but collide and
+/// overwrite each other on case-insensitive ones (e.g. on macOS or Windows).
+///
+/// # Arguments
+///
+/// * `paths`: The paths to be checked.
+///
+/// # Returns
+///
+/// Returns a vector of the colliding pairs of paths, in the order they were
+/// found.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::utils::case_collisions;
+/// use std::path::PathBuf;
+///
+/// let paths = [
+///     PathBuf::from("nvim/init.lua"),
+///     PathBuf::from("nvim/Init.lua"),
+///     PathBuf::from("vim/init.lua"),
+/// ];
+///
+/// assert_eq!(
+///     case_collisions(&paths),
+///     vec![(PathBuf::from("nvim/init.lua"), PathBuf::from("nvim/Init.lua"))]
+/// );
+/// ```
+pub fn case_collisions(paths: &[PathBuf]) -> Vec<(PathBuf, PathBuf)> {
+    let mut seen: HashMap<(Option<&Path>, String), &PathBuf> = HashMap::new();
+
+    paths
+        .iter()
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().to_lowercase();
+
+            match seen.entry((path.parent(), name)) {
+                Entry::Occupied(entry) => Some((entry.get().to_path_buf(), path.clone())),
+                Entry::Vacant(entry) => {
+                    entry.insert(path);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Find the read-only entries that prevent overwriting a path.
+///
+/// For a file, this is the file itself if it is read-only. For a directory,
+/// these are the read-only directories inside it (including itself), since
+/// their entries can't be removed or replaced. A path is read-only if its
+/// permissions are, or if the current user can't write to it, e.g. because
+/// it is immutable or owned by another user.
+///
+/// # Arguments
+///
+/// * `path`: The path to be overwritten.
+///
+/// # Returns
+///
+/// Returns a vector of the read-only paths, which is empty if the path can be
+/// overwritten or does not exist.
+pub fn readonly_entries<P>(path: P) -> Vec<PathBuf>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let is_readonly = |path: &Path| {
+        fs::metadata(path).is_ok_and(|metadata| metadata.permissions().readonly())
+            || !can_write(path)
+    };
+
+    if path.is_file() {
+        return is_readonly(path)
+            .then(|| path.to_path_buf())
+            .into_iter()
+            .collect();
+    }
+
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir() && is_readonly(e.path()))
+        .map(|e| e.into_path())
+        .collect()
+}
+
+/// Make the given paths writable by their owner.
+///
+/// # Arguments
+///
+/// * `paths`: The paths to make writable.
+///
+/// # Returns
+///
+/// Returns the original permissions of the paths, which can be restored with
+/// `restore_permissions`, or an error if the permissions can't be changed.
+pub fn make_writable(paths: &[PathBuf]) -> Result<Vec<(PathBuf, fs::Permissions)>> {
+    paths
+        .iter()
+        .map(|path| {
+            let permissions = fs::metadata(path)?.permissions();
+
+            let mut writable = permissions.clone();
+            writable.set_mode(permissions.mode() | 0o200);
+            fs::set_permissions(path, writable)
+                .map_err(|e| anyhow!("Failed to make {:#?} writable: {e}", path))?;
+
+            Ok((path.clone(), permissions))
+        })
+        .collect()
+}
+
+/// Restore the permissions saved by `make_writable`.
+///
+/// Paths that no longer exist are skipped, and failures are reported without
+/// aborting the restoration of the remaining paths.
+pub fn restore_permissions(saved: Vec<(PathBuf, fs::Permissions)>) {
+    saved
+        .into_iter()
+        .filter(|(path, _)| path.exists())
+        .for_each(|(path, permissions)| {
+            if let Err(e) = fs::set_permissions(&path, permissions) {
+                println!("Failed to restore permissions of {:#?}: {e}", path);
             }
         });
+}
+
+/// Get the space available to the current user on the filesystem of a
+/// path.
+///
+/// The path doesn't need to exist yet, the space of the filesystem of its
+/// nearest existing ancestor is returned instead.
+///
+/// # Arguments
+///
+/// * `path`: The path on the filesystem.
+///
+/// # Returns
+///
+/// Returns a Result containing the available space in bytes, or an error
+/// if the filesystem can't be queried.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::utils::available_space;
+///
+/// let space = available_space(std::env::temp_dir().join("does-not-exist/yet")).unwrap();
+/// println!("{space} bytes available");
+/// ```
+pub fn available_space<P>(path: P) -> Result<u64>
+where
+    P: AsRef<Path>,
+{
+    let path = path
+        .as_ref()
+        .ancestors()
+        .find(|path| path.exists())
+        .unwrap_or(Path::new("/"));
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(anyhow!(
+            "Failed to get the free space of {:#?}: {}",
+            path,
+            io::Error::last_os_error()
+        ));
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Get the total size of the files of a file or directory, skipping git
+/// entries the same way they are skipped on pull.
+///
+/// Entries that can't be read count as empty, and a missing path has a
+/// size of zero.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::utils::path_size;
+///
+/// assert!(path_size("./examples/config.ron") > 0);
+/// assert_eq!(path_size("./examples/does-not-exist"), 0);
+/// ```
+pub fn path_size<P>(path: P) -> u64
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| !is_git_path(e.path().strip_prefix(path).unwrap_or(e.path())))
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.metadata().map_or(0, |m| m.len()))
+        .sum()
+}
+
+/// Check if a path, relative to a config or dotconfigs directory, is a git
+/// directory or inside one, i.e. one of its components is `.git`.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::utils::is_git_path;
+///
+/// assert!(is_git_path(".git"));
+/// assert!(is_git_path("nvim/.git/HEAD"));
+/// assert!(!is_git_path(".gitconfig"));
+/// assert!(!is_git_path(".github/workflows/ci.yml"));
+/// assert!(!is_git_path("foo.github-cfg/init.lua"));
+/// ```
+pub fn is_git_path<P>(relative_path: P) -> bool
+where
+    P: AsRef<Path>,
+{
+    relative_path
+        .as_ref()
+        .components()
+        .any(|c| c.as_os_str() == ".git")
+}
+
+/// Replace a path with a symlink pointing to the given target.
+///
+/// An existing file or symlink at the path is replaced, and missing parent
+/// directories are created. A directory is never replaced, so its contents
+/// aren't lost.
+///
+/// The symlink is created at a temporary path next to it and renamed over
+/// the existing path, which is atomic, so programs reading the path while
+/// it is replaced never find it missing.
+///
+/// # Arguments
+///
+/// * `target`: The target of the symlink, stored as it is.
+/// * `link`: The path of the symlink.
+///
+/// # Returns
+///
+/// Returns a Result indicating success, or an error if the path is a
+/// directory or the symlink can't be created.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::utils::replace_with_symlink;
+/// use std::fs;
+///
+/// let path = std::env::temp_dir().join("sync-dotfiles-doctest-replace-symlink");
+/// let _ = fs::remove_dir_all(&path);
+/// fs::create_dir_all(&path).unwrap();
+/// fs::write(path.join("link"), "file").unwrap();
+///
+/// replace_with_symlink("target", path.join("link")).unwrap();
+/// assert_eq!(fs::read_link(path.join("link")).unwrap().to_str(), Some("target"));
+///
+/// fs::create_dir(path.join("dir")).unwrap();
+/// assert!(replace_with_symlink("target", path.join("dir")).is_err());
+///
+/// fs::remove_dir_all(&path).unwrap();
+/// ```
+///
+/// ```rust
+/// use sync_dotfiles_rs::utils::replace_with_symlink;
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::{fs, thread};
+///
+/// let path = std::env::temp_dir().join("sync-dotfiles-doctest-swap-symlink");
+/// let _ = fs::remove_dir_all(&path);
+/// fs::create_dir_all(&path).unwrap();
+/// let link = path.join("theme.toml");
+/// replace_with_symlink("light.toml", &link).unwrap();
+///
+/// // The link never goes missing while it is swapped
+/// let done = AtomicBool::new(false);
+/// thread::scope(|scope| {
+///     let reader = scope.spawn(|| {
+///         while !done.load(Ordering::Relaxed) {
+///             assert!(fs::symlink_metadata(&link).is_ok(), "The link went missing");
+///         }
+///     });
+///
+///     for target in ["dark.toml", "light.toml"].iter().cycle().take(500) {
+///         replace_with_symlink(target, &link).unwrap();
+///     }
+///     done.store(true, Ordering::Relaxed);
+///     reader.join().unwrap();
+/// });
+///
+/// assert_eq!(fs::read_link(&link).unwrap().to_str(), Some("light.toml"));
+/// assert_eq!(fs::read_dir(&path).unwrap().count(), 1);
+///
+/// fs::remove_dir_all(&path).unwrap();
+/// ```
+pub fn replace_with_symlink<T, P>(target: T, link: P) -> Result<()>
+where
+    T: AsRef<Path>,
+    P: AsRef<Path>,
+{
+    let link = link.as_ref();
+
+    match fs::symlink_metadata(link) {
+        Ok(metadata) if metadata.is_dir() => {
+            return Err(anyhow!(
+                "Cannot replace the directory {:#?} with a symlink",
+                link
+            ));
+        }
+        Ok(_) => {}
+        Err(_) => {
+            if let Some(parent) = link.parent() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+    }
+
+    let Some(file_name) = link.file_name() else {
+        return Err(anyhow!("Invalid symlink path: {:#?}", link));
+    };
+    let temp_link = link.with_file_name(format!(
+        ".{}.sync-dotfiles-link",
+        file_name.to_string_lossy()
+    ));
+
+    // Remove the leftover of an interrupted swap
+    if fs::symlink_metadata(&temp_link).is_ok() {
+        fs::remove_file(&temp_link)?;
+    }
+
+    std::os::unix::fs::symlink(target, &temp_link)?;
+    if let Err(e) = fs::rename(&temp_link, link) {
+        let _ = fs::remove_file(&temp_link);
+        return Err(e.into());
+    }
+
     Ok(())
 }
 
+/// Get the host name of the machine.
+///
+/// # Returns
+///
+/// Returns a Result containing the host name, or an error if it can't be
+/// queried.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::utils::hostname;
+///
+/// assert!(!hostname().unwrap().is_empty());
+/// ```
+pub fn hostname() -> Result<String> {
+    let mut name = [0u8; 256];
+    if unsafe { libc::gethostname(name.as_mut_ptr().cast(), name.len()) } != 0 {
+        return Err(anyhow!(
+            "Failed to get the host name: {}",
+            io::Error::last_os_error()
+        ));
+    }
+
+    let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+    Ok(String::from_utf8_lossy(&name[..len]).into_owned())
+}
+
+/// Check if the current process runs with root privileges.
+pub fn is_privileged() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// Resolve a user name or a numeric user id to a user id.
+///
+/// # Arguments
+///
+/// * `user`: The user name (e.g. "root") or numeric user id (e.g. "0").
+///
+/// # Returns
+///
+/// Returns the user id, or an error if no such user exists.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::utils::resolve_uid;
+///
+/// assert_eq!(resolve_uid("root").unwrap(), 0);
+/// assert_eq!(resolve_uid("1000").unwrap(), 1000);
+/// ```
+pub fn resolve_uid(user: &str) -> Result<u32> {
+    if let Ok(uid) = user.parse::<u32>() {
+        return Ok(uid);
+    }
+
+    let name = std::ffi::CString::new(user)?;
+    let mut buf = vec![0; 16384];
+    let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut result = std::ptr::null_mut();
+
+    let ret = unsafe {
+        libc::getpwnam_r(
+            name.as_ptr(),
+            &mut passwd,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut result,
+        )
+    };
+
+    if ret != 0 || result.is_null() {
+        return Err(anyhow!("Unknown user: {user}"));
+    }
+
+    Ok(passwd.pw_uid)
+}
+
+/// Resolve a group name or a numeric group id to a group id.
+///
+/// # Arguments
+///
+/// * `group`: The group name (e.g. "root") or numeric group id (e.g. "0").
+///
+/// # Returns
+///
+/// Returns the group id, or an error if no such group exists.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::utils::resolve_gid;
+///
+/// assert_eq!(resolve_gid("root").unwrap(), 0);
+/// assert_eq!(resolve_gid("1000").unwrap(), 1000);
+/// ```
+pub fn resolve_gid(group: &str) -> Result<u32> {
+    if let Ok(gid) = group.parse::<u32>() {
+        return Ok(gid);
+    }
+
+    let name = std::ffi::CString::new(group)?;
+    let mut buf = vec![0; 16384];
+    let mut grp: libc::group = unsafe { std::mem::zeroed() };
+    let mut result = std::ptr::null_mut();
+
+    let ret = unsafe {
+        libc::getgrnam_r(
+            name.as_ptr(),
+            &mut grp,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut result,
+        )
+    };
+
+    if ret != 0 || result.is_null() {
+        return Err(anyhow!("Unknown group: {group}"));
+    }
+
+    Ok(grp.gr_gid)
+}
+
+/// Change the owner and group of a path and everything inside it.
+///
+/// Symbolic links are changed themselves and not followed.
+///
+/// # Arguments
+///
+/// * `path`: The file or directory to change.
+/// * `owner`: The new owner, as a user name or numeric id, or `None` to keep
+///   the owner.
+/// * `group`: The new group, as a group name or numeric id, or `None` to
+///   keep the group.
+///
+/// # Returns
+///
+/// Returns an error if the owner or group can't be resolved or changed.
+pub fn chown_recursive<P>(path: P, owner: Option<&str>, group: Option<&str>) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let uid = owner.map(resolve_uid).transpose()?;
+    let gid = group.map(resolve_gid).transpose()?;
+
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .try_for_each(|entry| {
+            let entry = entry?;
+            std::os::unix::fs::lchown(entry.path(), uid, gid)
+                .map_err(|e| anyhow!("Failed to change the owner of {:#?}: {e}", entry.path()))
+        })
+}
+
 /// Get a pretty printer configuration for RON (Rusty Object Notation)
 /// serialization.
 ///
 /// This function returns a configuration for pretty-printing RON data with a
 /// depth limit and specific extensions.
 ///
+/// Optional values are written without `Some(...)` using the `implicit_some`
+/// extension, unless the `--explicit-some` option is set.
+///
 /// # Returns
 ///
 /// Returns a `PrettyConfig` that can be used with the RON serialization.
@@ -385,9 +1365,13 @@ where
 /// println!("Pretty RON:\n{}", ron_string);
 /// ```
 pub fn get_ron_formatter() -> PrettyConfig {
-    PrettyConfig::new()
-        .depth_limit(2)
-        .extensions(Extensions::IMPLICIT_SOME)
+    let formatter = PrettyConfig::new().depth_limit(2);
+
+    if options::get().explicit_some {
+        formatter
+    } else {
+        formatter.extensions(Extensions::IMPLICIT_SOME)
+    }
 }
 
 /// Escape privilege if necessary.
@@ -425,3 +1409,239 @@ pub fn escape_privilege() -> Result<()> {
         }
     }
 }
+
+/// Number of bytes at the beginning of a file scanned by `is_binary`.
+const BINARY_SCAN_SIZE: u64 = 8 * 1024;
+
+/// Check if a file is binary rather than text.
+///
+/// Only the first 8 KB of the file are scanned. The file is binary if they
+/// contain a NUL byte, or if more than a tenth of them are control
+/// characters that don't appear in text (i.e. other than tabs, line breaks,
+/// form feeds and escapes). Empty files are text.
+///
+/// # Arguments
+///
+/// * `path`: The path of the file to check.
+///
+/// # Returns
+///
+/// Returns a Result containing whether the file is binary, or an error if
+/// the file could not be read.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::utils::is_binary;
+///
+/// let path = std::env::temp_dir().join("sync-dotfiles-doctest-is-binary");
+/// std::fs::write(&path, [0x7f, b'E', b'L', b'F', 0x02, 0x01, 0x00, 0x00]).unwrap();
+///
+/// assert!(is_binary(&path).unwrap());
+/// assert!(!is_binary("./examples/config.ron").unwrap());
+/// assert!(is_binary("./examples/does-not-exist").is_err());
+///
+/// std::fs::remove_file(&path).unwrap();
+/// ```
+pub fn is_binary<P>(path: P) -> Result<bool>
+where
+    P: AsRef<Path>,
+{
+    let mut head = Vec::new();
+    fs::File::open(path)?
+        .take(BINARY_SCAN_SIZE)
+        .read_to_end(&mut head)?;
+
+    Ok(is_binary_content(&head))
+}
+
+/// Check if two files are identical, i.e. have the same permissions and
+/// contents, so copying one onto the other can be skipped.
+///
+/// The sizes and permissions are compared first, and the contents are only
+/// read if they match.
+///
+/// # Arguments
+///
+/// * `a`: The path of the first file.
+/// * `b`: The path of the second file.
+///
+/// # Returns
+///
+/// Returns a Result containing whether the files are identical, `false` if
+/// the second file doesn't exist, or an error if a file could not be read.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::utils::same_file_contents;
+///
+/// let dir = std::env::temp_dir().join("sync-dotfiles-doctest-same-contents");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("a"), "set number").unwrap();
+/// std::fs::write(dir.join("b"), "set number").unwrap();
+/// std::fs::write(dir.join("c"), "set nonumber").unwrap();
+///
+/// assert!(same_file_contents(dir.join("a"), dir.join("b")).unwrap());
+/// assert!(!same_file_contents(dir.join("a"), dir.join("c")).unwrap());
+/// assert!(!same_file_contents(dir.join("a"), dir.join("d")).unwrap());
+///
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn same_file_contents<P, Q>(a: P, b: Q) -> Result<bool>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let metadata_a = fs::metadata(&a)?;
+    let metadata_b = match fs::metadata(&b) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    if !metadata_b.is_file()
+        || metadata_a.len() != metadata_b.len()
+        || metadata_a.permissions().mode() != metadata_b.permissions().mode()
+    {
+        return Ok(false);
+    }
+
+    let (mut file_a, mut file_b) = (fs::File::open(&a)?, fs::File::open(&b)?);
+    let (mut buf_a, mut buf_b) = ([0; 8192], [0; 8192]);
+    loop {
+        let read = file_a.read(&mut buf_a)?;
+        if read == 0 {
+            return Ok(true);
+        }
+
+        file_b.read_exact(&mut buf_b[..read])?;
+        if buf_a[..read] != buf_b[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+/// Check if the contents of a file are binary rather than text, see
+/// `is_binary`.
+///
+/// Only the first 8 KB of the contents are scanned.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::utils::is_binary_content;
+///
+/// assert!(is_binary_content(b"\x7fELF\x02\x01\x00\x00"));
+/// assert!(!is_binary_content(b"set number\n"));
+/// assert!(!is_binary_content(b""));
+/// ```
+pub fn is_binary_content(contents: &[u8]) -> bool {
+    let head = &contents[..contents.len().min(BINARY_SCAN_SIZE as usize)];
+
+    if head.contains(&0) {
+        return true;
+    }
+
+    let control = head
+        .iter()
+        .filter(|&&byte| byte < 0x20 && !b"\t\n\r\x0c\x1b".contains(&byte) || byte == 0x7f)
+        .count();
+
+    control * 10 > head.len()
+}
+
+/// Format a byte count as a human-readable size.
+///
+/// The size is scaled to the largest unit (B, KB, MB, GB, TB) that keeps the
+/// value above one, using powers of 1024.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::utils::format_bytes;
+///
+/// assert_eq!(format_bytes(512), "512 B");
+/// assert_eq!(format_bytes(84 * 1024 * 1024), "84.0 MB");
+/// ```
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// Parse a human-readable duration, e.g. `45d`.
+///
+/// The duration is a whole number followed by a unit: `s` (seconds), `m`
+/// (minutes), `h` (hours), `d` (days) or `w` (weeks). A number without a unit
+/// is a number of days.
+///
+/// # Example
+///
+/// ```rust
+/// use sync_dotfiles_rs::utils::parse_duration;
+/// use std::time::Duration;
+///
+/// assert_eq!(parse_duration("12h").unwrap(), Duration::from_secs(12 * 60 * 60));
+/// assert_eq!(parse_duration("2w").unwrap(), parse_duration("14").unwrap());
+/// assert!(parse_duration("soon").is_err());
+/// ```
+pub fn parse_duration(duration: &str) -> Result<Duration> {
+    let duration = duration.trim();
+    let (number, unit) = duration.split_at(
+        duration
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(duration.len()),
+    );
+
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" | "" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(anyhow!("Invalid duration unit in {:#?}", duration)),
+    };
+
+    let number = number
+        .parse::<u64>()
+        .map_err(|_| anyhow!("Invalid duration {:#?}", duration))?;
+
+    Ok(Duration::from_secs(number * seconds))
+}
+
+/// Get the current time in seconds since the Unix epoch.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Ask the user for confirmation on the standard input.
+///
+/// The `prompt` is printed followed by `[y/N]`, and only an explicit `y` or
+/// `yes` answer (case-insensitive) is treated as a confirmation.
+///
+/// # Returns
+///
+/// Returns `true` if the user confirmed, `false` otherwise, or an error if
+/// reading from the standard input fails.
+pub fn confirm(prompt: &str) -> Result<bool> {
+    print!("{prompt} [y/N] ");
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}